@@ -25,11 +25,33 @@ class Assignable {
     }
 }
 
+// Typed value of a key/value pair, a borsh enum with one of
+// string, u64, i64, bytes, pubkey or bool set. i64 values are
+// read as their unsigned two's complement
+export class Value extends Assignable { }
+
 // Our instruction payload vocabulary
 class Payload extends Assignable { }
+class KeyPayload extends Assignable { }
+
+const valueSchema: [any, any] = [
+    Value,
+    {
+        kind: "enum",
+        field: "enum",
+        values: [
+            ["string", "string"],
+            ["u64", "u64"],
+            ["i64", "u64"],
+            ["bytes", ["u8"]],
+            ["pubkey", [32]],
+            ["bool", "u8"],
+        ]
+    }
+];
 
 // Borsh needs a schema describing the payload
-const payloadSchema = new Map([
+const payloadSchema = new Map<any, any>([
     [
         Payload,
         {
@@ -37,26 +59,39 @@ const payloadSchema = new Map([
             fields: [
                 ["id", "u8"],
                 ["key", "string"],
-                ["value", "string"]
+                ["value", Value]
             ]
         }
-    ]
+    ],
+    [
+        KeyPayload,
+        {
+            kind: "struct",
+            fields: [
+                ["id", "u8"],
+                ["key", "string"]
+            ]
+        }
+    ],
+    valueSchema
 ]);
 
 export class AccoundData extends Assignable { }
 
-const dataSchema = new Map([
+const dataSchema = new Map<any, any>([
     [
         AccoundData,
         {
             kind: "struct",
             fields: [
-                ["initialized", "u8"],
+                ["version", "u8"],
+                ["authority", [32]],
                 ["tree_length", "u32"],
-                ["map", { kind: 'map', key: 'string', value: 'string' }]
+                ["map", { kind: 'map', key: 'string', value: Value }]
             ]
         }
-    ]
+    ],
+    valueSchema
 ]);
 
 /**
@@ -75,6 +110,34 @@ export async function getAccountData(
     return Ok(deserializeUnchecked(dataSchema, AccoundData, nameAccount.data))
 }
 
+/**
+ * Derive the address of the program config, which every mutating
+ * instruction takes first so the program can read its pause switch
+ * @param {PublicKey} progId - Sample Program public key
+ * @return {Promise<PublicKey>} - Config account public key
+ */
+async function configAddress(progId: PublicKey): Promise<PublicKey> {
+    const [config] = await PublicKey.findProgramAddress(
+        [Buffer.from('config')],
+        progId
+    );
+    return config;
+}
+
+/**
+ * Derive the address of the program registry, which mints and burns
+ * take after the account so the program can count them
+ * @param {PublicKey} progId - Sample Program public key
+ * @return {Promise<PublicKey>} - Registry account public key
+ */
+async function registryAddress(progId: PublicKey): Promise<PublicKey> {
+    const [registry] = await PublicKey.findProgramAddress(
+        [Buffer.from('registry')],
+        progId
+    );
+    return registry;
+}
+
 // Instruction variant indexes
 enum InstructionVariant {
     InitializeAccount = 0,
@@ -134,7 +197,7 @@ async function submitTxn(
  * @param {PublicKey} account - Target program owned account for Mint
  * @param {Keypair} wallet - Wallet for signing and payment
  * @param {string} mintKey - The key being minted key
- * @param {string} mintValue - The value being minted
+ * @param {string} mintValue - The value being minted, as a string value
  * @return {Promise<Keypair>} - Keypair
  */
 export async function mintKV(
@@ -149,7 +212,7 @@ export async function mintKV(
     const mint = new Payload({
         id: InstructionVariant.MintKeypair,
         key: mintKey,
-        value: mintValue
+        value: new Value({ enum: 'string', string: mintValue })
     });
 
     // Serialize the payload
@@ -159,8 +222,10 @@ export async function mintKV(
     }
 
     let signature = await submitTxn(connection, progId, signer, mintSerBuf, [
+        { pubkey: await configAddress(progId), isSigner: false, isWritable: false },
         { pubkey: account, isSigner: false, isWritable: true },
-        { pubkey: signer.publicKey, isSigner: false, isWritable: false },
+        { pubkey: await registryAddress(progId), isSigner: false, isWritable: true },
+        { pubkey: signer.publicKey, isSigner: true, isWritable: false },
     ])
     return signature;
 }
@@ -184,10 +249,9 @@ export async function transferKV(
     transferKey: string): Promise<Result<string, Error>> {
 
     // Construct the payload
-    const mint = new Payload({
+    const mint = new KeyPayload({
         id: InstructionVariant.TransferKeypair,
-        key: transferKey,
-        value: ''
+        key: transferKey
     });
 
     // Serialize the payload
@@ -197,9 +261,10 @@ export async function transferKV(
     }
 
     let signature = await submitTxn(connection, progId, signer, mintSerBuf, [
+        { pubkey: await configAddress(progId), isSigner: false, isWritable: false },
         { pubkey: from_account, isSigner: false, isWritable: true },
         { pubkey: to_account, isSigner: false, isWritable: true },
-        { pubkey: signer.publicKey, isSigner: false, isWritable: false },
+        { pubkey: signer.publicKey, isSigner: true, isWritable: false },
     ])
     return signature;
 }
@@ -222,10 +287,9 @@ export async function burnK(
     mintKey: string): Promise<Result<string, Error>> {
 
     // Construct the payload
-    const mint = new Payload({
+    const mint = new KeyPayload({
         id: InstructionVariant.BurnKeypair,
-        key: mintKey,
-        value: ''
+        key: mintKey
     });
 
     // Serialize the payload
@@ -235,8 +299,10 @@ export async function burnK(
     }
 
     let signature = await submitTxn(connection, progId, wallet, mintSerBuf, [
+        { pubkey: await configAddress(progId), isSigner: false, isWritable: false },
         { pubkey: account, isSigner: false, isWritable: true },
-        { pubkey: wallet.publicKey, isSigner: false, isWritable: false },
+        { pubkey: await registryAddress(progId), isSigner: false, isWritable: true },
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
     ])
     return signature;
 }
@@ -38,7 +38,7 @@ describe("Chain testing", async () => {
         assert(deser_result.ok)
         const account = deser_result.val
         assert(account["map"].size > 0)
-        assert(account["map"].get("ts key2") === "ts first value2")
+        assert(account["map"].get("ts key2").string === "ts first value2")
 
 
     })
@@ -80,7 +80,7 @@ describe("Chain testing", async () => {
         assert(deser_result.ok)
         const account = deser_result.val
         assert(account["map"].size > 0)
-        assert(account["map"].get("ts key2") === "ts first value2")
+        assert(account["map"].get("ts key2").string === "ts first value2")
 
     })
     it('test_burn_devnet_pass', async () => {
@@ -1,69 +1,520 @@
 //! @brief account_state manages account data
 
 use crate::error::SampleError;
-use sol_template_shared::ACCOUNT_STATE_SPACE;
+use sol_template_shared::{
+    error::AccountStateError,
+    zero_copy::{self, ZeroCopyAccountState, ZeroCopyError},
+    AccountStateData, Expiry, KeyValueLimits, LayoutVersion, Value,
+};
 use solana_program::{
-    entrypoint::ProgramResult,
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack, Sealed},
+    clock::Clock, entrypoint::ProgramResult, program_error::ProgramError,
+    program_pack::IsInitialized, pubkey::Pubkey,
 };
-use std::collections::BTreeMap;
 
-/// Maintains global accumulator
-#[derive(Debug, Default, PartialEq)]
-pub struct ProgramAccountState {
-    is_initialized: bool,
-    btree_storage: BTreeMap<String, String>,
+/// Where the account state is kept while the instruction operates on it
+#[derive(Debug)]
+enum Backend<'a> {
+    /// Decoded from the borsh layout, and encoded back to the data area on pack
+    Borsh(AccountStateData, &'a mut [u8]),
+    /// Operated on in place in the zero-copy layout
+    ZeroCopy(ZeroCopyAccountState<&'a mut [u8]>),
+}
+
+/// The program's state of a program account, in the account's layout
+#[derive(Debug)]
+pub struct ProgramAccountState<'a> {
+    backend: Backend<'a>,
+}
+
+/// Maps an error of the zero-copy layout to the program's errors
+fn zero_copy_error(e: ZeroCopyError) -> ProgramError {
+    match e {
+        ZeroCopyError::KeyAlreadyExists => SampleError::KeyAlreadyExists.into(),
+        ZeroCopyError::KeyNotFound => SampleError::KeyNotFoundInAccount.into(),
+        ZeroCopyError::InsufficientSpace => SampleError::AccountDataTooSmall.into(),
+        ZeroCopyError::KeyTooLong => SampleError::KeyTooLong.into(),
+        ZeroCopyError::InvalidLayout => SampleError::DeserializationFailure.into(),
+    }
 }
 
-impl ProgramAccountState {
-    ///
+/// Maps an error of unpacking or packing account data to the program's errors
+fn account_state_error(e: AccountStateError) -> ProgramError {
+    match e {
+        AccountStateError::AccountDataTooSmall { .. } => SampleError::AccountDataTooSmall.into(),
+        AccountStateError::ZeroCopy(e) => zero_copy_error(e),
+        AccountStateError::EmptyKey => SampleError::EmptyKey.into(),
+        AccountStateError::KeyTooLong { .. } => SampleError::KeyTooLong.into(),
+        AccountStateError::InvalidKeyCharacter(_) => SampleError::InvalidKeyCharacter.into(),
+        AccountStateError::ValueTooLong { .. } => SampleError::ValueTooLong.into(),
+        AccountStateError::TooManyEntries { .. } => SampleError::TooManyEntries.into(),
+        _ => SampleError::DeserializationFailure.into(),
+    }
+}
+
+impl ProgramAccountState<'_> {
+    /// Marks the account as initialized
     pub fn set_initialized(&mut self) {
-        self.is_initialized = true;
-    }
-    /// Adds a new key/value pair to the account
-    pub fn add(&mut self, key: String, value: String) -> ProgramResult {
-        match self.btree_storage.contains_key(&key) {
-            true => Err(SampleError::KeyAlreadyExists.into()),
-            false => {
-                self.btree_storage.insert(key, value);
-                Ok(())
+        if let Backend::Borsh(data, _) = &mut self.backend {
+            data.is_initialized = true;
+        }
+    }
+    /// Sets the wallet that must sign mutations of the account
+    pub fn set_authority(&mut self, authority: &Pubkey) {
+        match &mut self.backend {
+            Backend::Borsh(data, _) => data.authority = *authority,
+            Backend::ZeroCopy(state) => state.set_authority(authority),
+        }
+    }
+    /// Returns the wallet that must sign mutations of the account
+    pub fn authority(&self) -> Pubkey {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.authority,
+            Backend::ZeroCopy(state) => state.authority(),
+        }
+    }
+    /// Returns true if the account holds no key/value pairs
+    pub fn is_empty(&self) -> bool {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.btree_storage.is_empty(),
+            Backend::ZeroCopy(state) => state.is_empty(),
+        }
+    }
+    /// Returns the number of key/value pairs the account holds
+    pub fn len(&self) -> usize {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.btree_storage.len(),
+            Backend::ZeroCopy(state) => state.len(),
+        }
+    }
+    /// Returns true if the account is frozen
+    pub fn is_frozen(&self) -> bool {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.frozen,
+            Backend::ZeroCopy(state) => state.is_frozen(),
+        }
+    }
+    /// Freezes or thaws the account
+    pub fn set_frozen(&mut self, frozen: bool) {
+        match &mut self.backend {
+            Backend::Borsh(data, _) => data.frozen = frozen,
+            Backend::ZeroCopy(state) => state.set_frozen(frozen),
+        }
+    }
+    /// Returns the wallet that may freeze and thaw the account and lock and
+    /// unlock its keys, the authority until another is set
+    pub fn freeze_authority(&self) -> Pubkey {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.freeze_authority(),
+            Backend::ZeroCopy(state) => state
+                .freeze_authority()
+                .unwrap_or_else(|| state.authority()),
+        }
+    }
+    /// Sets the wallet that may freeze and thaw the account and lock and
+    /// unlock its keys
+    pub fn set_freeze_authority(&mut self, freeze_authority: &Pubkey) {
+        match &mut self.backend {
+            Backend::Borsh(data, _) => data.freeze_authority = Some(*freeze_authority),
+            Backend::ZeroCopy(state) => state.set_freeze_authority(Some(freeze_authority)),
+        }
+    }
+    /// Fails with `AccountFrozen` if the account is frozen
+    fn check_not_frozen(&self) -> ProgramResult {
+        match self.is_frozen() {
+            true => Err(SampleError::AccountFrozen.into()),
+            false => Ok(()),
+        }
+    }
+    /// Returns true if the key is locked
+    pub fn is_locked(&self, key: &str) -> bool {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.locked_keys.contains(key),
+            Backend::ZeroCopy(state) => {
+                matches!(state.get(key), Ok(Some(entry)) if entry.is_locked())
             }
         }
     }
-    /// Removes a key from account and returns the keys value
-    pub fn remove(&mut self, key: &str) -> Result<String, SampleError> {
-        match self.btree_storage.contains_key(key) {
-            true => Ok(self.btree_storage.remove(key).unwrap()),
-            false => Err(SampleError::KeyNotFoundInAccount),
+    /// Returns true if any key of the account is locked
+    pub fn has_locked_keys(&self) -> bool {
+        match &self.backend {
+            Backend::Borsh(data, _) => !data.locked_keys.is_empty(),
+            Backend::ZeroCopy(state) => state
+                .entries()
+                .any(|entry| matches!(entry, Ok(entry) if entry.is_locked())),
+        }
+    }
+    /// Locks or unlocks an existing key. A locked key may not be transferred,
+    /// burned or updated
+    pub fn set_locked(&mut self, key: &str, locked: bool) -> ProgramResult {
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.contains_key(key) {
+                true => {
+                    match locked {
+                        true => data.locked_keys.insert(key.to_string()),
+                        false => data.locked_keys.remove(key),
+                    };
+                    Ok(())
+                }
+                false => Err(SampleError::KeyNotFoundInAccount.into()),
+            },
+            Backend::ZeroCopy(state) => state.set_locked(key, locked).map_err(zero_copy_error),
+        }
+    }
+    /// Fails with `KeyLocked` if the key is locked
+    fn check_not_locked(&self, key: &str) -> ProgramResult {
+        match self.is_locked(key) {
+            true => Err(SampleError::KeyLocked.into()),
+            false => Ok(()),
+        }
+    }
+    /// Checks a key/value pair about to be added to the account against the
+    /// key/value limits
+    fn check_new_key_value(
+        &self,
+        limits: &KeyValueLimits,
+        key: &str,
+        value: &Value,
+    ) -> ProgramResult {
+        limits
+            .check_key(key)
+            .and_then(|_| limits.check_value(value))
+            .and_then(|_| limits.check_entry_count(self.len() + 1))
+            .map_err(account_state_error)
+    }
+    /// Adds a new key/value pair, within the limits, to the account
+    pub fn add(&mut self, limits: &KeyValueLimits, key: String, value: Value) -> ProgramResult {
+        self.check_not_frozen()?;
+        self.check_new_key_value(limits, &key, &value)?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.contains_key(&key) {
+                true => Err(SampleError::KeyAlreadyExists.into()),
+                false => {
+                    data.btree_storage.insert(key, value);
+                    Ok(())
+                }
+            },
+            Backend::ZeroCopy(state) => state
+                .insert(&key, &value, None, None)
+                .map_err(zero_copy_error),
+        }
+    }
+    /// Adds a new key/value pair, within the limits, to the account that
+    /// expires
+    pub fn add_with_expiry(
+        &mut self,
+        limits: &KeyValueLimits,
+        key: String,
+        value: Value,
+        expiry: Expiry,
+    ) -> ProgramResult {
+        self.check_not_frozen()?;
+        self.check_new_key_value(limits, &key, &value)?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.contains_key(&key) {
+                true => Err(SampleError::KeyAlreadyExists.into()),
+                false => {
+                    data.expiries.insert(key.clone(), expiry);
+                    data.btree_storage.insert(key, value);
+                    Ok(())
+                }
+            },
+            Backend::ZeroCopy(state) => state
+                .insert(&key, &value, None, Some(&expiry))
+                .map_err(zero_copy_error),
+        }
+    }
+    /// Replaces the value of an existing key in the account with a value
+    /// within the limits, treating an expired key as not found
+    pub fn update(
+        &mut self,
+        limits: &KeyValueLimits,
+        key: String,
+        value: Value,
+        clock: &Clock,
+    ) -> ProgramResult {
+        self.check_not_frozen()?;
+        if self.is_expired(&key, clock) {
+            return Err(SampleError::KeyNotFoundInAccount.into());
+        }
+        self.check_not_locked(&key)?;
+        limits.check_value(&value).map_err(account_state_error)?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.get_mut(&key) {
+                Some(current) => {
+                    *current = value;
+                    Ok(())
+                }
+                None => Err(SampleError::KeyNotFoundInAccount.into()),
+            },
+            Backend::ZeroCopy(state) => state.update(&key, &value).map_err(zero_copy_error),
+        }
+    }
+    /// Sets the value of a key, adding the key if it does not exist, and
+    /// returns true if the key was added. An expired key is removed, with
+    /// its expiry, and added again
+    pub fn upsert(
+        &mut self,
+        limits: &KeyValueLimits,
+        key: String,
+        value: Value,
+        clock: &Clock,
+    ) -> Result<bool, ProgramError> {
+        if self.is_expired(&key, clock) {
+            self.remove(&key)?;
+        }
+        let contains_key = match &self.backend {
+            Backend::Borsh(data, _) => data.btree_storage.contains_key(&key),
+            Backend::ZeroCopy(state) => state.contains_key(&key).map_err(zero_copy_error)?,
+        };
+        match contains_key {
+            true => self.update(limits, key, value, clock).map(|_| false),
+            false => self.add(limits, key, value).map(|_| true),
+        }
+    }
+    /// Removes a key, and any delegate and expiry of the key, from account
+    /// and returns the keys value
+    pub fn remove(&mut self, key: &str) -> Result<Value, ProgramError> {
+        self.check_not_frozen()?;
+        self.check_not_locked(key)?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.remove(key) {
+                Some(value) => {
+                    data.delegates.remove(key);
+                    data.expiries.remove(key);
+                    Ok(value)
+                }
+                None => Err(SampleError::KeyNotFoundInAccount.into()),
+            },
+            Backend::ZeroCopy(state) => state.remove(key).map_err(zero_copy_error),
+        }
+    }
+    /// Removes a key, as `remove` does, treating an expired key as not found
+    pub fn remove_unexpired(&mut self, key: &str, clock: &Clock) -> Result<Value, ProgramError> {
+        match self.is_expired(key, clock) {
+            true => Err(SampleError::KeyNotFoundInAccount.into()),
+            false => self.remove(key),
+        }
+    }
+    /// Returns the value of the key, or none if the account does not hold it
+    pub fn get(&self, key: &str) -> Result<Option<Value>, ProgramError> {
+        match &self.backend {
+            Backend::Borsh(data, _) => Ok(data.btree_storage.get(key).cloned()),
+            Backend::ZeroCopy(state) => match state.get(key).map_err(zero_copy_error)? {
+                Some(entry) => entry.value().map(Some).map_err(zero_copy_error),
+                None => Ok(None),
+            },
+        }
+    }
+    /// Returns the value of the key, as `get` does, treating an expired key
+    /// as not held
+    pub fn get_unexpired(&self, key: &str, clock: &Clock) -> Result<Option<Value>, ProgramError> {
+        match self.is_expired(key, clock) {
+            true => Ok(None),
+            false => self.get(key),
+        }
+    }
+    /// Returns true if the key has an expiry that the clock has reached
+    pub fn is_expired(&self, key: &str, clock: &Clock) -> bool {
+        let expiry = match &self.backend {
+            Backend::Borsh(data, _) => data.expiry(key).copied(),
+            Backend::ZeroCopy(state) => match state.get(key) {
+                Ok(Some(entry)) => entry.expiry().unwrap_or(None),
+                _ => None,
+            },
+        };
+        match expiry {
+            Some(expiry) => expiry.is_expired(clock.slot, clock.unix_timestamp),
+            None => false,
+        }
+    }
+    /// Removes all expired keys, other than locked keys, and returns how many
+    /// were removed
+    pub fn purge_expired(&mut self, clock: &Clock) -> Result<usize, ProgramError> {
+        self.check_not_frozen()?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => {
+                let expired_keys: Vec<String> = data
+                    .expiries
+                    .iter()
+                    .filter(|(key, expiry)| {
+                        expiry.is_expired(clock.slot, clock.unix_timestamp)
+                            && !data.locked_keys.contains(*key)
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in &expired_keys {
+                    data.btree_storage.remove(key);
+                    data.delegates.remove(key);
+                    data.expiries.remove(key);
+                }
+                Ok(expired_keys.len())
+            }
+            Backend::ZeroCopy(state) => state
+                .remove_expired(clock.slot, clock.unix_timestamp)
+                .map_err(zero_copy_error),
+        }
+    }
+    /// Approves the delegate to transfer an existing key, replacing any
+    /// previous delegate of the key
+    pub fn approve(&mut self, key: String, delegate: &Pubkey) -> ProgramResult {
+        self.check_not_frozen()?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.contains_key(&key) {
+                true => {
+                    data.delegates.insert(key, *delegate);
+                    Ok(())
+                }
+                false => Err(SampleError::KeyNotFoundInAccount.into()),
+            },
+            Backend::ZeroCopy(state) => state
+                .set_delegate(&key, Some(delegate))
+                .map_err(zero_copy_error),
+        }
+    }
+    /// Revokes the delegate, if any, of an existing key
+    pub fn revoke(&mut self, key: &str) -> ProgramResult {
+        self.check_not_frozen()?;
+        match &mut self.backend {
+            Backend::Borsh(data, _) => match data.btree_storage.contains_key(key) {
+                true => {
+                    data.delegates.remove(key);
+                    Ok(())
+                }
+                false => Err(SampleError::KeyNotFoundInAccount.into()),
+            },
+            Backend::ZeroCopy(state) => state.set_delegate(key, None).map_err(zero_copy_error),
+        }
+    }
+    /// Returns the delegate approved to transfer the key, if any
+    pub fn delegate(&self, key: &str) -> Option<Pubkey> {
+        match &self.backend {
+            Backend::Borsh(data, _) => data.delegates.get(key).copied(),
+            Backend::ZeroCopy(state) => match state.get(key) {
+                Ok(Some(entry)) => entry.delegate(),
+                _ => None,
+            },
         }
     }
 }
 
-impl Sealed for ProgramAccountState {}
-
-impl IsInitialized for ProgramAccountState {
+impl IsInitialized for ProgramAccountState<'_> {
     fn is_initialized(&self) -> bool {
-        self.is_initialized
+        match &self.backend {
+            Backend::Borsh(data, _) => data.is_initialized,
+            Backend::ZeroCopy(_) => true,
+        }
     }
 }
 
-impl Pack for ProgramAccountState {
-    const LEN: usize = ACCOUNT_STATE_SPACE;
+/// Accounts may be resized so, unlike `Pack`, packing and unpacking
+/// accept account data areas of any length. The state borrows the data
+/// area it is unpacked from and packs back into it
+impl<'a> ProgramAccountState<'a> {
+    /// Returns the account data size needed to hold the state
+    pub fn packed_len(&self) -> usize {
+        match &self.backend {
+            Backend::Borsh(data, _) => sol_template_shared::account_state_space_for(data),
+            Backend::ZeroCopy(state) => state.packed_len(),
+        }
+    }
+
+    /// Moves the entries of the zero-copy layout ending at `from_end` of the
+    /// data area to end at `to_end`, before shrinking or after growing the
+    /// account. The borsh layout is left as is
+    pub fn relocate(src: &mut [u8], from_end: usize, to_end: usize) -> ProgramResult {
+        zero_copy::relocate_entries(src, from_end, to_end).map_err(zero_copy_error)
+    }
 
-    /// Store 'state' of account to its data area
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        sol_template_shared::pack_into_slice(self.is_initialized, &self.btree_storage, dst);
+    /// Store 'state' of account to the data area it was unpacked from. The
+    /// zero-copy layout is already up to date
+    pub fn pack(src: Self) -> ProgramResult {
+        if let Backend::Borsh(data, dst) = src.backend {
+            sol_template_shared::pack_into_slice(&data, dst).map_err(account_state_error)?;
+        }
+        Ok(())
+    }
+
+    /// Initialize the uninitialized account data area in the zero-copy
+    /// layout, recording the authority
+    pub fn initialize_zero_copy(
+        src: &'a mut [u8],
+        authority: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let state = ZeroCopyAccountState::initialize(src, authority).map_err(zero_copy_error)?;
+        Ok(ProgramAccountState {
+            backend: Backend::ZeroCopy(state),
+        })
+    }
+
+    /// Retrieve 'state' of account from account data area, failing if
+    /// the account is not initialized
+    pub fn unpack(src: &'a mut [u8]) -> Result<Self, ProgramError> {
+        let account_state = Self::unpack_unchecked(src)?;
+        match account_state.is_initialized() {
+            true => Ok(account_state),
+            false => Err(ProgramError::UninitializedAccount),
+        }
     }
 
-    /// Retrieve 'state' of account from account data area
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    /// Retrieve 'state' of account from account data area, failing if
+    /// the account is of an earlier layout
+    pub fn unpack_unchecked(src: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if zero_copy::is_zero_copy(src) {
+            return Ok(ProgramAccountState {
+                backend: Backend::ZeroCopy(
+                    ZeroCopyAccountState::new(src).map_err(zero_copy_error)?,
+                ),
+            });
+        }
         match sol_template_shared::unpack_from_slice(src) {
-            Ok((is_initialized, btree_map)) => Ok(ProgramAccountState {
-                is_initialized,
-                btree_storage: btree_map,
+            Ok(data) => Ok(ProgramAccountState {
+                backend: Backend::Borsh(data, src),
             }),
-            Err(_) => Err(ProgramError::InvalidAccountData),
+            Err(_) if sol_template_shared::needs_migration(src) => {
+                Err(SampleError::AccountNeedsMigration.into())
+            }
+            Err(e) => Err(account_state_error(e)),
+        }
+    }
+
+    /// Retrieve the layout version and 'state' of account from account data
+    /// area of any layout, failing if the account is not initialized. Packing
+    /// the state of an earlier layout stores it in the current layout
+    pub fn unpack_versioned(src: &'a mut [u8]) -> Result<(LayoutVersion, Self), ProgramError> {
+        if zero_copy::is_zero_copy(src) {
+            return Ok((
+                LayoutVersion::ZeroCopy,
+                ProgramAccountState {
+                    backend: Backend::ZeroCopy(
+                        ZeroCopyAccountState::new(src).map_err(zero_copy_error)?,
+                    ),
+                },
+            ));
+        }
+        match sol_template_shared::unpack_versioned_from_slice(src) {
+            Ok((_, data)) if !data.is_initialized => Err(ProgramError::UninitializedAccount),
+            Ok((version, data)) => Ok((
+                version,
+                ProgramAccountState {
+                    backend: Backend::Borsh(data, src),
+                },
+            )),
+            Err(e) => Err(account_state_error(e)),
+        }
+    }
+}
+
+/// Retrieve the account state content from the account data area, of the
+/// current or the zero-copy layout, without borrowing it mutably, for the
+/// instructions that only read the account. Fails if the account is not
+/// initialized
+pub fn unpack_account_data(src: &[u8]) -> Result<AccountStateData, ProgramError> {
+    match sol_template_shared::unpack_from_slice(src) {
+        Ok(data) if !data.is_initialized => Err(ProgramError::UninitializedAccount),
+        Ok(data) => Ok(data),
+        Err(_) if sol_template_shared::needs_migration(src) => {
+            Err(SampleError::AccountNeedsMigration.into())
         }
+        Err(e) => Err(account_state_error(e)),
     }
 }
@@ -0,0 +1,118 @@
+//! @brief config_state manages the program configuration account data
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sol_template_shared::{KeyValueLimits, KEY_VALUE_LIMITS};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Space allocated for the configuration account, leaving room to grow
+pub const CONFIG_STATE_SPACE: usize = 128;
+
+/// Service fees, in lamports, debited from participating accounts and
+/// credited to a 'service' account
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq)]
+pub struct ServiceFees {
+    pub minting: u64,
+    pub transfering: u64,
+    pub burning: u64,
+    pub updating: u64,
+}
+
+impl Default for ServiceFees {
+    fn default() -> Self {
+        ServiceFees {
+            minting: 10,
+            transfering: 30,
+            burning: 15,
+            updating: 10,
+        }
+    }
+}
+
+/// Program wide configuration, administered by the admin
+#[derive(BorshDeserialize, BorshSerialize, Debug, Default, PartialEq)]
+pub struct ProgramConfigState {
+    is_initialized: bool,
+    admin: Pubkey,
+    fees: ServiceFees,
+    treasury: Pubkey,
+    paused: bool,
+    key_value_limits: Option<KeyValueLimits>,
+}
+
+impl ProgramConfigState {
+    ///
+    pub fn set_initialized(&mut self) {
+        self.is_initialized = true;
+    }
+    /// Sets the wallet that must sign configuration changes
+    pub fn set_admin(&mut self, admin: &Pubkey) {
+        self.admin = *admin;
+    }
+    /// Returns the wallet that must sign configuration changes
+    pub fn admin(&self) -> &Pubkey {
+        &self.admin
+    }
+    /// Sets the fee schedule
+    pub fn set_fees(&mut self, fees: ServiceFees) {
+        self.fees = fees;
+    }
+    /// Returns the fee schedule
+    pub fn fees(&self) -> &ServiceFees {
+        &self.fees
+    }
+    /// Sets the account that fees are credited to
+    pub fn set_treasury(&mut self, treasury: &Pubkey) {
+        self.treasury = *treasury;
+    }
+    /// Returns the account that fees are credited to
+    pub fn treasury(&self) -> &Pubkey {
+        &self.treasury
+    }
+    /// Pauses, or unpauses, every instruction but the admin's and the
+    /// read-only ones
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    /// Returns whether the program is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Sets the limits on the key/value pairs of every account
+    pub fn set_key_value_limits(&mut self, limits: KeyValueLimits) {
+        self.key_value_limits = Some(limits);
+    }
+    /// Returns the limits on the key/value pairs of every account, the
+    /// default limits until the admin sets others
+    pub fn key_value_limits(&self) -> KeyValueLimits {
+        self.key_value_limits.unwrap_or(KEY_VALUE_LIMITS)
+    }
+}
+
+impl Sealed for ProgramConfigState {}
+
+impl IsInitialized for ProgramConfigState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProgramConfigState {
+    const LEN: usize = CONFIG_STATE_SPACE;
+
+    /// Store 'state' of configuration to its data area
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let config_data = self.try_to_vec().unwrap();
+        dst[..config_data.len()].copy_from_slice(&config_data);
+    }
+
+    /// Retrieve 'state' of configuration from its data area
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_unchecked::<ProgramConfigState>(src)
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
@@ -0,0 +1,997 @@
+//! cpi Typed cross-program invocation of the program's instructions
+//!
+//! Other programs depend on this crate with the `no-entrypoint` feature and
+//! call these helpers, one per `ProgramInstruction`, with the account infos
+//! in the roles the instruction expects. Each helper builds the instruction
+//! with the builder of the `instruction` module, so the account order is
+//! that of the clients, and invokes the program with `invoke_signed`, the
+//! `signers_seeds` signing for the calling programs derived addresses, so
+//! passing `&[]` is a plain `invoke`. The `config` and `registry` infos are
+//! those of the program's derived config and registry accounts, which the
+//! builders add to the instructions that take them
+
+use crate::{config_state::ServiceFees, instruction};
+use borsh::BorshDeserialize;
+use sol_template_shared::{Expiry, KeyValueLimits, Value};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::{get_return_data, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Invokes the program, whose account info is `program`, with an
+/// instruction built by the `instruction` module and the infos of its
+/// accounts, in any order. The typed helpers call this, callers use it with
+/// the builders for account roles the helpers do not cover, such as a
+/// multisig authority followed by its signers
+pub fn invoke_instruction<'a>(
+    program: &AccountInfo<'a>,
+    instruction: &Instruction,
+    account_infos: &[AccountInfo<'a>],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut account_infos = account_infos.to_vec();
+    account_infos.push(program.clone());
+    invoke_signed(instruction, &account_infos, signers_seeds)
+}
+
+/// `InitializeAccount`, recording the authority
+pub fn initialize_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::initialize_account(program.key, account.key, authority.key),
+        &[
+            config.clone(),
+            account.clone(),
+            authority.clone(),
+            registry.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `InitializeZeroCopyAccount`, recording the authority
+pub fn initialize_zero_copy_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::initialize_zero_copy_account(program.key, account.key, authority.key),
+        &[
+            config.clone(),
+            account.clone(),
+            authority.clone(),
+            registry.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `InitializeDerivedAccount`, the wallet funds the account at the address
+/// derived from it and the seed and becomes its authority
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_derived_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    wallet: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    seed: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::initialize_derived_account(program.key, wallet.key, seed),
+        &[
+            config.clone(),
+            account.clone(),
+            wallet.clone(),
+            system_program.clone(),
+            registry.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `MintToAccount`
+#[allow(clippy::too_many_arguments)]
+pub fn mint_to_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::mint_to_account(program.key, account.key, authority.key, &[], key, value),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `MintToAccountWithExpiry`
+#[allow(clippy::too_many_arguments)]
+pub fn mint_to_account_with_expiry<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    expiry: Expiry,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::mint_to_account_with_expiry(
+            program.key,
+            account.key,
+            authority.key,
+            &[],
+            key,
+            value,
+            expiry,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `MintToAccountWithFee`, the fee credited to the treasury of the config
+#[allow(clippy::too_many_arguments)]
+pub fn mint_to_account_with_fee<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::mint_to_account_with_fee(
+            program.key,
+            account.key,
+            treasury.key,
+            authority.key,
+            &[],
+            key,
+            value,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            treasury.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `MintMany`, all or none of the key/value pairs
+pub fn mint_many<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key_values: &[(String, Value)],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::mint_many(program.key, account.key, authority.key, &[], key_values),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `TransferBetweenAccounts`, the authority of the from account, or the
+/// keys delegate, signs
+pub fn transfer_between_accounts<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    from_account: &AccountInfo<'a>,
+    to_account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::transfer_between_accounts(
+            program.key,
+            from_account.key,
+            to_account.key,
+            authority.key,
+            &[],
+            key,
+        ),
+        &[
+            config.clone(),
+            from_account.clone(),
+            to_account.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `TransferBetweenAccountsWithFee`, the fee charged to both accounts
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_between_accounts_with_fee<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    from_account: &AccountInfo<'a>,
+    to_account: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::transfer_between_accounts_with_fee(
+            program.key,
+            from_account.key,
+            to_account.key,
+            treasury.key,
+            authority.key,
+            &[],
+            key,
+        ),
+        &[
+            config.clone(),
+            from_account.clone(),
+            to_account.clone(),
+            treasury.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `TransferMany`, all or none of the keys
+pub fn transfer_many<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    from_account: &AccountInfo<'a>,
+    to_account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    keys: &[String],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::transfer_many(
+            program.key,
+            from_account.key,
+            to_account.key,
+            authority.key,
+            &[],
+            keys,
+        ),
+        &[
+            config.clone(),
+            from_account.clone(),
+            to_account.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `BurnFromAccount`
+pub fn burn_from_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::burn_from_account(program.key, account.key, authority.key, &[], key),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `BurnFromAccountWithFee`, the fee credited to the treasury of the config
+#[allow(clippy::too_many_arguments)]
+pub fn burn_from_account_with_fee<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::burn_from_account_with_fee(
+            program.key,
+            account.key,
+            treasury.key,
+            authority.key,
+            &[],
+            key,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            treasury.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `BurnMany`, all or none of the keys
+pub fn burn_many<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    keys: &[String],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::burn_many(program.key, account.key, authority.key, &[], keys),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `UpdateValue` of an existing key
+pub fn update_value<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::update_value(program.key, account.key, authority.key, &[], key, value),
+        &[config.clone(), account.clone(), authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `UpdateValueWithFee`, the fee credited to the treasury of the config
+#[allow(clippy::too_many_arguments)]
+pub fn update_value_with_fee<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::update_value_with_fee(
+            program.key,
+            account.key,
+            treasury.key,
+            authority.key,
+            &[],
+            key,
+            value,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            treasury.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `UpsertValue`, adding the key if it does not exist
+#[allow(clippy::too_many_arguments)]
+pub fn upsert_value<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::upsert_value(program.key, account.key, authority.key, &[], key, value),
+        &[
+            config.clone(),
+            account.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `UpsertValueWithFee`, the fee credited to the treasury of the config
+#[allow(clippy::too_many_arguments)]
+pub fn upsert_value_with_fee<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    value: &Value,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::upsert_value_with_fee(
+            program.key,
+            account.key,
+            treasury.key,
+            authority.key,
+            &[],
+            key,
+            value,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            treasury.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `Resize`, the funder funding growth or refunded when shrinking
+#[allow(clippy::too_many_arguments)]
+pub fn resize<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    funder: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    new_size: u64,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::resize(
+            program.key,
+            account.key,
+            funder.key,
+            authority.key,
+            &[],
+            new_size,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            system_program.clone(),
+            funder.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `MigrateAccount`, the funder funding growth. An account of the `V0`
+/// layout signs as well, so is passed signing when its info is
+pub fn migrate_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    funder: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::migrate_account(
+            program.key,
+            account.key,
+            funder.key,
+            authority.key,
+            &[],
+            account.is_signer,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            system_program.clone(),
+            funder.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `CloseAccount`, moving its lamports to the destination
+#[allow(clippy::too_many_arguments)]
+pub fn close_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    require_empty: bool,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::close_account(
+            program.key,
+            account.key,
+            destination.key,
+            authority.key,
+            &[],
+            require_empty,
+        ),
+        &[
+            config.clone(),
+            account.clone(),
+            destination.clone(),
+            registry.clone(),
+            authority.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `PurgeExpired`, which needs no signer
+pub fn purge_expired<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::purge_expired(program.key, account.key),
+        &[config.clone(), account.clone(), registry.clone()],
+        signers_seeds,
+    )
+}
+
+/// `Approve` the delegate to transfer the key
+pub fn approve<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    delegate: &Pubkey,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::approve(program.key, account.key, authority.key, &[], key, delegate),
+        &[config.clone(), account.clone(), authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `Revoke` the delegate of the key
+pub fn revoke<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::revoke(program.key, account.key, authority.key, &[], key),
+        &[config.clone(), account.clone(), authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `SwapKeys`, the authorities of both accounts sign
+#[allow(clippy::too_many_arguments)]
+pub fn swap_keys<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account_a: &AccountInfo<'a>,
+    account_b: &AccountInfo<'a>,
+    authority_a: &AccountInfo<'a>,
+    authority_b: &AccountInfo<'a>,
+    key_a: &str,
+    key_b: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::swap_keys(
+            program.key,
+            account_a.key,
+            account_b.key,
+            authority_a.key,
+            &[],
+            authority_b.key,
+            &[],
+            key_a,
+            key_b,
+        ),
+        &[
+            config.clone(),
+            account_a.clone(),
+            account_b.clone(),
+            authority_a.clone(),
+            authority_b.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `FreezeAccount`, the freeze authority signs
+pub fn freeze_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::freeze_account(program.key, account.key, freeze_authority.key, &[]),
+        &[config.clone(), account.clone(), freeze_authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `ThawAccount`, the freeze authority signs
+pub fn thaw_account<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::thaw_account(program.key, account.key, freeze_authority.key, &[]),
+        &[config.clone(), account.clone(), freeze_authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `LockKey`, the freeze authority signs
+pub fn lock_key<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::lock_key(program.key, account.key, freeze_authority.key, &[], key),
+        &[config.clone(), account.clone(), freeze_authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `UnlockKey`, the freeze authority signs
+pub fn unlock_key<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    key: &str,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::unlock_key(program.key, account.key, freeze_authority.key, &[], key),
+        &[config.clone(), account.clone(), freeze_authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `SetFreezeAuthority`, the current freeze authority signs
+pub fn set_freeze_authority<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    new_freeze_authority: &Pubkey,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::set_freeze_authority(
+            program.key,
+            account.key,
+            freeze_authority.key,
+            &[],
+            new_freeze_authority,
+        ),
+        &[config.clone(), account.clone(), freeze_authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `InitializeMultisig` requiring `m` of the signers
+pub fn initialize_multisig<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    multisig: &AccountInfo<'a>,
+    signers: &[AccountInfo<'a>],
+    m: u8,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let signer_pubkeys: Vec<Pubkey> = signers.iter().map(|signer| *signer.key).collect();
+    let mut account_infos = vec![config.clone(), multisig.clone()];
+    account_infos.extend_from_slice(signers);
+    invoke_instruction(
+        program,
+        &instruction::initialize_multisig(program.key, multisig.key, &signer_pubkeys, m),
+        &account_infos,
+        signers_seeds,
+    )
+}
+
+/// `SetAuthority`, the current authority signs
+pub fn set_authority<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    new_authority: &Pubkey,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::set_authority(program.key, account.key, authority.key, &[], new_authority),
+        &[config.clone(), account.clone(), authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// `InitializeConfig`, the admin, which must be the program's upgrade
+/// authority recorded in the program data account, funds the config account
+/// and becomes its admin
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_config<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_data: &AccountInfo<'a>,
+    fees: ServiceFees,
+    treasury: &Pubkey,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::initialize_config(program.key, admin.key, fees, treasury),
+        &[
+            config.clone(),
+            admin.clone(),
+            system_program.clone(),
+            program_data.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// `SetFees`, the admin signs
+pub fn set_fees<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    fees: ServiceFees,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::set_fees(program.key, admin.key, fees),
+        &[config.clone(), admin.clone()],
+        signers_seeds,
+    )
+}
+
+/// `SetTreasury`, the admin signs
+pub fn set_treasury<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    treasury: &Pubkey,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::set_treasury(program.key, admin.key, treasury),
+        &[config.clone(), admin.clone()],
+        signers_seeds,
+    )
+}
+
+/// `SetKeyValueLimits`, the admin signs
+pub fn set_key_value_limits<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    limits: KeyValueLimits,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::set_key_value_limits(program.key, admin.key, limits),
+        &[config.clone(), admin.clone()],
+        signers_seeds,
+    )
+}
+
+/// `Pause`, the admin signs
+pub fn pause<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::pause(program.key, admin.key),
+        &[config.clone(), admin.clone()],
+        signers_seeds,
+    )
+}
+
+/// `Unpause`, the admin signs
+pub fn unpause<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    admin: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::unpause(program.key, admin.key),
+        &[config.clone(), admin.clone()],
+        signers_seeds,
+    )
+}
+
+/// Decodes the borsh encoded data the program returned to the caller
+fn return_data_of<T: BorshDeserialize>(program: &AccountInfo) -> Result<T, ProgramError> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => {
+            T::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// `GetValue`, returning the value of the key or none if the account does
+/// not hold it or it expired
+pub fn get_value<'a>(
+    program: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    key: &str,
+) -> Result<Option<Value>, ProgramError> {
+    invoke_instruction(
+        program,
+        &instruction::get_value(program.key, account.key, key),
+        &[account.clone()],
+        &[],
+    )?;
+    return_data_of(program)
+}
+
+/// `ContainsKey`, returning true if the account holds the key and it has
+/// not expired
+pub fn contains_key<'a>(
+    program: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    key: &str,
+) -> Result<bool, ProgramError> {
+    invoke_instruction(
+        program,
+        &instruction::contains_key(program.key, account.key, key),
+        &[account.clone()],
+        &[],
+    )?;
+    return_data_of(program)
+}
+
+/// `InitializeRegistry`, the payer funds the registry account
+pub fn initialize_registry<'a>(
+    program: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    registry: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_instruction(
+        program,
+        &instruction::initialize_registry(program.key, payer.key),
+        &[
+            config.clone(),
+            registry.clone(),
+            payer.clone(),
+            system_program.clone(),
+        ],
+        signers_seeds,
+    )
+}
@@ -18,6 +18,31 @@ pub enum SampleError {
     KeyAlreadyExists,
     InsufficientFundsForTransaction,
     UnknownError,
+    MissingAuthoritySignature,
+    InvalidAuthority,
+    InvalidDerivedAccount,
+    InvalidAccountResize,
+    AccountNotEmpty,
+    InvalidConfigAccount,
+    InvalidTreasuryAccount,
+    FeeBreaksRentExemption,
+    ArithmeticOverflow,
+    AccountNeedsMigration,
+    AccountDataTooSmall,
+    EmptyKey,
+    KeyTooLong,
+    InvalidKeyCharacter,
+    ValueTooLong,
+    TooManyEntries,
+    AccountFrozen,
+    KeyLocked,
+    InvalidFreezeAuthority,
+    InvalidMultisig,
+    MissingMultisigSignatures,
+    InvalidRegistryAccount,
+    ProgramPaused,
+    InvalidProgramDataAccount,
+    InvalidKeyValueLimits,
 }
 
 impl From<SampleError> for ProgramError {
@@ -43,6 +68,61 @@ impl fmt::Display for SampleError {
             SampleError::InsufficientFundsForTransaction => {
                 f.write_str("Not enough funds to process transaction")
             }
+            SampleError::MissingAuthoritySignature => {
+                f.write_str("Account authority did not sign transaction")
+            }
+            SampleError::InvalidAuthority => f.write_str("Signer is not the account authority"),
+            SampleError::InvalidDerivedAccount => {
+                f.write_str("Account does not match the derived address")
+            }
+            SampleError::InvalidAccountResize => {
+                f.write_str("Account size can not hold the account state")
+            }
+            SampleError::AccountNotEmpty => f.write_str("Account still contains keys"),
+            SampleError::InvalidConfigAccount => {
+                f.write_str("Account is not the program configuration account")
+            }
+            SampleError::InvalidTreasuryAccount => {
+                f.write_str("Account is not the registered treasury account")
+            }
+            SampleError::FeeBreaksRentExemption => {
+                f.write_str("Fee would leave the account below rent exemption")
+            }
+            SampleError::ArithmeticOverflow => f.write_str("Lamport arithmetic overflow"),
+            SampleError::AccountNeedsMigration => {
+                f.write_str("Account needs migrating to the current layout")
+            }
+            SampleError::AccountDataTooSmall => {
+                f.write_str("Account data can not hold the account state")
+            }
+            SampleError::EmptyKey => f.write_str("Key is empty"),
+            SampleError::KeyTooLong => f.write_str("Key exceeds the key length limit"),
+            SampleError::InvalidKeyCharacter => f.write_str("Key holds a control character"),
+            SampleError::ValueTooLong => f.write_str("Value exceeds the value length limit"),
+            SampleError::TooManyEntries => {
+                f.write_str("Account would exceed the key/value pair limit")
+            }
+            SampleError::AccountFrozen => f.write_str("Account is frozen"),
+            SampleError::KeyLocked => f.write_str("Key is locked"),
+            SampleError::InvalidFreezeAuthority => {
+                f.write_str("Signer is not the account freeze authority")
+            }
+            SampleError::InvalidMultisig => {
+                f.write_str("Multisig threshold or signer count is out of range")
+            }
+            SampleError::MissingMultisigSignatures => {
+                f.write_str("Too few multisig signers signed transaction")
+            }
+            SampleError::InvalidRegistryAccount => {
+                f.write_str("Account is not the program registry account")
+            }
+            SampleError::ProgramPaused => f.write_str("Program is paused by its admin"),
+            SampleError::InvalidProgramDataAccount => {
+                f.write_str("Account is not the program's program data account")
+            }
+            SampleError::InvalidKeyValueLimits => {
+                f.write_str("Key/value limits must be non zero and fit every layout")
+            }
         }
     }
 }
@@ -62,6 +142,61 @@ impl PrintProgramError for SampleError {
             SampleError::InsufficientFundsForTransaction => {
                 println!("Not enough funds to process transaction")
             }
+            SampleError::MissingAuthoritySignature => {
+                println!("Account authority did not sign transaction")
+            }
+            SampleError::InvalidAuthority => println!("Signer is not the account authority"),
+            SampleError::InvalidDerivedAccount => {
+                println!("Account does not match the derived address")
+            }
+            SampleError::InvalidAccountResize => {
+                println!("Account size can not hold the account state")
+            }
+            SampleError::AccountNotEmpty => println!("Account still contains keys"),
+            SampleError::InvalidConfigAccount => {
+                println!("Account is not the program configuration account")
+            }
+            SampleError::InvalidTreasuryAccount => {
+                println!("Account is not the registered treasury account")
+            }
+            SampleError::FeeBreaksRentExemption => {
+                println!("Fee would leave the account below rent exemption")
+            }
+            SampleError::ArithmeticOverflow => println!("Lamport arithmetic overflow"),
+            SampleError::AccountNeedsMigration => {
+                println!("Account needs migrating to the current layout")
+            }
+            SampleError::AccountDataTooSmall => {
+                println!("Account data can not hold the account state")
+            }
+            SampleError::EmptyKey => println!("Key is empty"),
+            SampleError::KeyTooLong => println!("Key exceeds the key length limit"),
+            SampleError::InvalidKeyCharacter => println!("Key holds a control character"),
+            SampleError::ValueTooLong => println!("Value exceeds the value length limit"),
+            SampleError::TooManyEntries => {
+                println!("Account would exceed the key/value pair limit")
+            }
+            SampleError::AccountFrozen => println!("Account is frozen"),
+            SampleError::KeyLocked => println!("Key is locked"),
+            SampleError::InvalidFreezeAuthority => {
+                println!("Signer is not the account freeze authority")
+            }
+            SampleError::InvalidMultisig => {
+                println!("Multisig threshold or signer count is out of range")
+            }
+            SampleError::MissingMultisigSignatures => {
+                println!("Too few multisig signers signed transaction")
+            }
+            SampleError::InvalidRegistryAccount => {
+                println!("Account is not the program registry account")
+            }
+            SampleError::ProgramPaused => println!("Program is paused by its admin"),
+            SampleError::InvalidProgramDataAccount => {
+                println!("Account is not the program's program data account")
+            }
+            SampleError::InvalidKeyValueLimits => {
+                println!("Key/value limits must be non zero and fit every layout")
+            }
         }
     }
 }
@@ -0,0 +1,490 @@
+//! event defines the structured events the program logs
+
+use crate::config_state::ServiceFees;
+use borsh::{BorshDeserialize, BorshSerialize};
+use sol_template_shared::{Expiry, KeyValueLimits, Value};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+use std::io::{self, Write};
+
+/// The version byte that starts the event data, followed by the event's tag
+/// and its borsh encoded fields
+pub const EVENT_VERSION: u8 = 1;
+
+/// The fixed tag of each event. A tag is never reused or renumbered, and new
+/// events take the next unused tag
+pub mod tag {
+    pub const ACCOUNT_INITIALIZED: u8 = 0;
+    pub const MINTED: u8 = 1;
+    pub const TRANSFERRED: u8 = 2;
+    pub const BURNED: u8 = 3;
+    pub const VALUE_UPDATED: u8 = 4;
+    pub const EXPIRED_PURGED: u8 = 5;
+    pub const ACCOUNT_RESIZED: u8 = 6;
+    pub const ACCOUNT_MIGRATED: u8 = 7;
+    pub const ACCOUNT_CLOSED: u8 = 8;
+    pub const KEYS_SWAPPED: u8 = 9;
+    pub const DELEGATE_APPROVED: u8 = 10;
+    pub const DELEGATE_REVOKED: u8 = 11;
+    pub const CONFIG_INITIALIZED: u8 = 12;
+    pub const FEES_SET: u8 = 13;
+    pub const TREASURY_SET: u8 = 14;
+    pub const FEE_CHARGED: u8 = 15;
+    pub const ACCOUNT_FROZEN: u8 = 16;
+    pub const ACCOUNT_THAWED: u8 = 17;
+    pub const KEY_LOCKED: u8 = 18;
+    pub const KEY_UNLOCKED: u8 = 19;
+    pub const FREEZE_AUTHORITY_SET: u8 = 20;
+    pub const MULTISIG_INITIALIZED: u8 = 21;
+    pub const AUTHORITY_SET: u8 = 22;
+    pub const REGISTRY_INITIALIZED: u8 = 23;
+    pub const PROGRAM_PAUSED: u8 = 24;
+    pub const PROGRAM_UNPAUSED: u8 = 25;
+    pub const KEY_VALUE_LIMITS_SET: u8 = 26;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Program events, logged with `sol_log_data` as the version byte, the tag and
+/// the borsh encoded fields once an instruction has changed the state so
+/// indexers can follow the activity
+pub enum ProgramEvent {
+    AccountInitialized {
+        account: Pubkey,
+        authority: Pubkey,
+    },
+    Minted {
+        account: Pubkey,
+        key: String,
+        value: Value,
+        expiry: Option<Expiry>,
+    },
+    Transferred {
+        from: Pubkey,
+        to: Pubkey,
+        key: String,
+    },
+    Burned {
+        account: Pubkey,
+        key: String,
+    },
+    ValueUpdated {
+        account: Pubkey,
+        key: String,
+        value: Value,
+    },
+    ExpiredPurged {
+        account: Pubkey,
+        count: u64,
+    },
+    AccountResized {
+        account: Pubkey,
+        size: u64,
+    },
+    AccountMigrated {
+        account: Pubkey,
+        from_version: u8,
+        to_version: u8,
+    },
+    AccountClosed {
+        account: Pubkey,
+        destination: Pubkey,
+        lamports: u64,
+    },
+    KeysSwapped {
+        account_a: Pubkey,
+        key_a: String,
+        account_b: Pubkey,
+        key_b: String,
+    },
+    DelegateApproved {
+        account: Pubkey,
+        key: String,
+        delegate: Pubkey,
+    },
+    DelegateRevoked {
+        account: Pubkey,
+        key: String,
+    },
+    ConfigInitialized {
+        admin: Pubkey,
+        treasury: Pubkey,
+        fees: ServiceFees,
+    },
+    FeesSet {
+        fees: ServiceFees,
+    },
+    TreasurySet {
+        treasury: Pubkey,
+    },
+    FeeCharged {
+        account: Pubkey,
+        treasury: Pubkey,
+        amount: u64,
+    },
+    AccountFrozen {
+        account: Pubkey,
+    },
+    AccountThawed {
+        account: Pubkey,
+    },
+    KeyLocked {
+        account: Pubkey,
+        key: String,
+    },
+    KeyUnlocked {
+        account: Pubkey,
+        key: String,
+    },
+    FreezeAuthoritySet {
+        account: Pubkey,
+        freeze_authority: Pubkey,
+    },
+    MultisigInitialized {
+        multisig: Pubkey,
+        m: u8,
+        signers: Vec<Pubkey>,
+    },
+    AuthoritySet {
+        account: Pubkey,
+        authority: Pubkey,
+    },
+    RegistryInitialized {
+        registry: Pubkey,
+    },
+    ProgramPaused {
+        admin: Pubkey,
+    },
+    ProgramUnpaused {
+        admin: Pubkey,
+    },
+    KeyValueLimitsSet {
+        limits: KeyValueLimits,
+    },
+}
+
+impl BorshSerialize for ProgramEvent {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            ProgramEvent::AccountInitialized { account, authority } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_INITIALIZED])?;
+                account.serialize(writer)?;
+                authority.serialize(writer)?;
+            }
+            ProgramEvent::Minted {
+                account,
+                key,
+                value,
+                expiry,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::MINTED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+                expiry.serialize(writer)?;
+            }
+            ProgramEvent::Transferred { from, to, key } => {
+                writer.write_all(&[EVENT_VERSION, tag::TRANSFERRED])?;
+                from.serialize(writer)?;
+                to.serialize(writer)?;
+                key.serialize(writer)?;
+            }
+            ProgramEvent::Burned { account, key } => {
+                writer.write_all(&[EVENT_VERSION, tag::BURNED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+            }
+            ProgramEvent::ValueUpdated {
+                account,
+                key,
+                value,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::VALUE_UPDATED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramEvent::ExpiredPurged { account, count } => {
+                writer.write_all(&[EVENT_VERSION, tag::EXPIRED_PURGED])?;
+                account.serialize(writer)?;
+                count.serialize(writer)?;
+            }
+            ProgramEvent::AccountResized { account, size } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_RESIZED])?;
+                account.serialize(writer)?;
+                size.serialize(writer)?;
+            }
+            ProgramEvent::AccountMigrated {
+                account,
+                from_version,
+                to_version,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_MIGRATED])?;
+                account.serialize(writer)?;
+                from_version.serialize(writer)?;
+                to_version.serialize(writer)?;
+            }
+            ProgramEvent::AccountClosed {
+                account,
+                destination,
+                lamports,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_CLOSED])?;
+                account.serialize(writer)?;
+                destination.serialize(writer)?;
+                lamports.serialize(writer)?;
+            }
+            ProgramEvent::KeysSwapped {
+                account_a,
+                key_a,
+                account_b,
+                key_b,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::KEYS_SWAPPED])?;
+                account_a.serialize(writer)?;
+                key_a.serialize(writer)?;
+                account_b.serialize(writer)?;
+                key_b.serialize(writer)?;
+            }
+            ProgramEvent::DelegateApproved {
+                account,
+                key,
+                delegate,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::DELEGATE_APPROVED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+                delegate.serialize(writer)?;
+            }
+            ProgramEvent::DelegateRevoked { account, key } => {
+                writer.write_all(&[EVENT_VERSION, tag::DELEGATE_REVOKED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+            }
+            ProgramEvent::ConfigInitialized {
+                admin,
+                treasury,
+                fees,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::CONFIG_INITIALIZED])?;
+                admin.serialize(writer)?;
+                treasury.serialize(writer)?;
+                fees.serialize(writer)?;
+            }
+            ProgramEvent::FeesSet { fees } => {
+                writer.write_all(&[EVENT_VERSION, tag::FEES_SET])?;
+                fees.serialize(writer)?;
+            }
+            ProgramEvent::TreasurySet { treasury } => {
+                writer.write_all(&[EVENT_VERSION, tag::TREASURY_SET])?;
+                treasury.serialize(writer)?;
+            }
+            ProgramEvent::FeeCharged {
+                account,
+                treasury,
+                amount,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::FEE_CHARGED])?;
+                account.serialize(writer)?;
+                treasury.serialize(writer)?;
+                amount.serialize(writer)?;
+            }
+            ProgramEvent::AccountFrozen { account } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_FROZEN])?;
+                account.serialize(writer)?;
+            }
+            ProgramEvent::AccountThawed { account } => {
+                writer.write_all(&[EVENT_VERSION, tag::ACCOUNT_THAWED])?;
+                account.serialize(writer)?;
+            }
+            ProgramEvent::KeyLocked { account, key } => {
+                writer.write_all(&[EVENT_VERSION, tag::KEY_LOCKED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+            }
+            ProgramEvent::KeyUnlocked { account, key } => {
+                writer.write_all(&[EVENT_VERSION, tag::KEY_UNLOCKED])?;
+                account.serialize(writer)?;
+                key.serialize(writer)?;
+            }
+            ProgramEvent::FreezeAuthoritySet {
+                account,
+                freeze_authority,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::FREEZE_AUTHORITY_SET])?;
+                account.serialize(writer)?;
+                freeze_authority.serialize(writer)?;
+            }
+            ProgramEvent::MultisigInitialized {
+                multisig,
+                m,
+                signers,
+            } => {
+                writer.write_all(&[EVENT_VERSION, tag::MULTISIG_INITIALIZED])?;
+                multisig.serialize(writer)?;
+                m.serialize(writer)?;
+                signers.serialize(writer)?;
+            }
+            ProgramEvent::AuthoritySet { account, authority } => {
+                writer.write_all(&[EVENT_VERSION, tag::AUTHORITY_SET])?;
+                account.serialize(writer)?;
+                authority.serialize(writer)?;
+            }
+            ProgramEvent::RegistryInitialized { registry } => {
+                writer.write_all(&[EVENT_VERSION, tag::REGISTRY_INITIALIZED])?;
+                registry.serialize(writer)?;
+            }
+            ProgramEvent::ProgramPaused { admin } => {
+                writer.write_all(&[EVENT_VERSION, tag::PROGRAM_PAUSED])?;
+                admin.serialize(writer)?;
+            }
+            ProgramEvent::ProgramUnpaused { admin } => {
+                writer.write_all(&[EVENT_VERSION, tag::PROGRAM_UNPAUSED])?;
+                admin.serialize(writer)?;
+            }
+            ProgramEvent::KeyValueLimitsSet { limits } => {
+                writer.write_all(&[EVENT_VERSION, tag::KEY_VALUE_LIMITS_SET])?;
+                limits.serialize(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for ProgramEvent {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let version = u8::deserialize(buf)?;
+        if version != EVENT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown event version {}", version),
+            ));
+        }
+        let event_tag = u8::deserialize(buf)?;
+        Ok(match event_tag {
+            tag::ACCOUNT_INITIALIZED => ProgramEvent::AccountInitialized {
+                account: BorshDeserialize::deserialize(buf)?,
+                authority: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::MINTED => ProgramEvent::Minted {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+                value: BorshDeserialize::deserialize(buf)?,
+                expiry: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::TRANSFERRED => ProgramEvent::Transferred {
+                from: BorshDeserialize::deserialize(buf)?,
+                to: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::BURNED => ProgramEvent::Burned {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::VALUE_UPDATED => ProgramEvent::ValueUpdated {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+                value: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::EXPIRED_PURGED => ProgramEvent::ExpiredPurged {
+                account: BorshDeserialize::deserialize(buf)?,
+                count: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::ACCOUNT_RESIZED => ProgramEvent::AccountResized {
+                account: BorshDeserialize::deserialize(buf)?,
+                size: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::ACCOUNT_MIGRATED => ProgramEvent::AccountMigrated {
+                account: BorshDeserialize::deserialize(buf)?,
+                from_version: BorshDeserialize::deserialize(buf)?,
+                to_version: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::ACCOUNT_CLOSED => ProgramEvent::AccountClosed {
+                account: BorshDeserialize::deserialize(buf)?,
+                destination: BorshDeserialize::deserialize(buf)?,
+                lamports: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::KEYS_SWAPPED => ProgramEvent::KeysSwapped {
+                account_a: BorshDeserialize::deserialize(buf)?,
+                key_a: BorshDeserialize::deserialize(buf)?,
+                account_b: BorshDeserialize::deserialize(buf)?,
+                key_b: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::DELEGATE_APPROVED => ProgramEvent::DelegateApproved {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+                delegate: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::DELEGATE_REVOKED => ProgramEvent::DelegateRevoked {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::CONFIG_INITIALIZED => ProgramEvent::ConfigInitialized {
+                admin: BorshDeserialize::deserialize(buf)?,
+                treasury: BorshDeserialize::deserialize(buf)?,
+                fees: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::FEES_SET => ProgramEvent::FeesSet {
+                fees: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::TREASURY_SET => ProgramEvent::TreasurySet {
+                treasury: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::FEE_CHARGED => ProgramEvent::FeeCharged {
+                account: BorshDeserialize::deserialize(buf)?,
+                treasury: BorshDeserialize::deserialize(buf)?,
+                amount: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::ACCOUNT_FROZEN => ProgramEvent::AccountFrozen {
+                account: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::ACCOUNT_THAWED => ProgramEvent::AccountThawed {
+                account: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::KEY_LOCKED => ProgramEvent::KeyLocked {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::KEY_UNLOCKED => ProgramEvent::KeyUnlocked {
+                account: BorshDeserialize::deserialize(buf)?,
+                key: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::FREEZE_AUTHORITY_SET => ProgramEvent::FreezeAuthoritySet {
+                account: BorshDeserialize::deserialize(buf)?,
+                freeze_authority: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::MULTISIG_INITIALIZED => ProgramEvent::MultisigInitialized {
+                multisig: BorshDeserialize::deserialize(buf)?,
+                m: BorshDeserialize::deserialize(buf)?,
+                signers: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::AUTHORITY_SET => ProgramEvent::AuthoritySet {
+                account: BorshDeserialize::deserialize(buf)?,
+                authority: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::REGISTRY_INITIALIZED => ProgramEvent::RegistryInitialized {
+                registry: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::PROGRAM_PAUSED => ProgramEvent::ProgramPaused {
+                admin: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::PROGRAM_UNPAUSED => ProgramEvent::ProgramUnpaused {
+                admin: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::KEY_VALUE_LIMITS_SET => ProgramEvent::KeyValueLimitsSet {
+                limits: BorshDeserialize::deserialize(buf)?,
+            },
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown event tag {}", event_tag),
+                ))
+            }
+        })
+    }
+}
+
+impl ProgramEvent {
+    /// Logs the event as a single `sol_log_data` field
+    pub fn emit(&self) {
+        sol_log_data(&[&self.try_to_vec().unwrap()]);
+    }
+}
@@ -1,33 +1,1278 @@
-//! instruction Contains the main ProgramInstruction enum
+//! instruction Contains the main ProgramInstruction enum and the builders
+//! of each instruction with its accounts
 
+use crate::{
+    config_state::ServiceFees, error::SampleError, find_config_address,
+    find_derived_account_address, find_registry_address,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{borsh::try_from_slice_unchecked, program_error::ProgramError};
+use sol_template_shared::{Expiry, KeyValueLimits, Value};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use std::io::{self, Write};
 
-#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+/// The version byte that starts the instruction data, followed by the
+/// instruction's tag and its borsh encoded fields. Version bytes are 128 and
+/// up, above every tag, so the data of earlier clients, which starts with the
+/// tag, still decodes as the same instruction
+pub const INSTRUCTION_VERSION: u8 = 128;
+
+/// The fixed tag of each instruction. A tag is never reused or renumbered,
+/// and new instructions take the next unused tag, below 128
+pub mod tag {
+    pub const INITIALIZE_ACCOUNT: u8 = 0;
+    pub const MINT_TO_ACCOUNT: u8 = 1;
+    pub const TRANSFER_BETWEEN_ACCOUNTS: u8 = 2;
+    pub const BURN_FROM_ACCOUNT: u8 = 3;
+    pub const MINT_TO_ACCOUNT_WITH_FEE: u8 = 4;
+    pub const TRANSFER_BETWEEN_ACCOUNTS_WITH_FEE: u8 = 5;
+    pub const BURN_FROM_ACCOUNT_WITH_FEE: u8 = 6;
+    pub const INITIALIZE_DERIVED_ACCOUNT: u8 = 7;
+    pub const UPDATE_VALUE: u8 = 8;
+    pub const UPSERT_VALUE: u8 = 9;
+    pub const UPDATE_VALUE_WITH_FEE: u8 = 10;
+    pub const UPSERT_VALUE_WITH_FEE: u8 = 11;
+    pub const RESIZE: u8 = 12;
+    pub const CLOSE_ACCOUNT: u8 = 13;
+    pub const MINT_MANY: u8 = 14;
+    pub const BURN_MANY: u8 = 15;
+    pub const TRANSFER_MANY: u8 = 16;
+    pub const INITIALIZE_CONFIG: u8 = 17;
+    pub const SET_FEES: u8 = 18;
+    pub const SET_TREASURY: u8 = 19;
+    pub const APPROVE: u8 = 20;
+    pub const REVOKE: u8 = 21;
+    pub const SWAP_KEYS: u8 = 22;
+    pub const MINT_TO_ACCOUNT_WITH_EXPIRY: u8 = 23;
+    pub const PURGE_EXPIRED: u8 = 24;
+    pub const MIGRATE_ACCOUNT: u8 = 25;
+    pub const INITIALIZE_ZERO_COPY_ACCOUNT: u8 = 26;
+    pub const FREEZE_ACCOUNT: u8 = 27;
+    pub const THAW_ACCOUNT: u8 = 28;
+    pub const LOCK_KEY: u8 = 29;
+    pub const UNLOCK_KEY: u8 = 30;
+    pub const SET_FREEZE_AUTHORITY: u8 = 31;
+    pub const INITIALIZE_MULTISIG: u8 = 32;
+    pub const SET_AUTHORITY: u8 = 33;
+    pub const GET_VALUE: u8 = 34;
+    pub const CONTAINS_KEY: u8 = 35;
+    pub const INITIALIZE_REGISTRY: u8 = 36;
+    pub const PAUSE: u8 = 37;
+    pub const UNPAUSE: u8 = 38;
+    pub const SET_KEY_VALUE_LIMITS: u8 = 39;
+}
+
+#[derive(Debug, PartialEq)]
 /// All custom program instructions
 pub enum ProgramInstruction {
     InitializeAccount,
-    MintToAccount(String, String),
+    MintToAccount(String, Value),
     TransferBetweenAccounts(String),
     BurnFromAccount(String),
-    MintToAccountWithFee(String, String),
+    MintToAccountWithFee(String, Value),
     TransferBetweenAccountsWithFee(String),
     BurnFromAccountWithFee(String),
+    InitializeDerivedAccount(String),
+    UpdateValue(String, Value),
+    UpsertValue(String, Value),
+    UpdateValueWithFee(String, Value),
+    UpsertValueWithFee(String, Value),
+    Resize(u64),
+    CloseAccount(bool),
+    MintMany(Vec<(String, Value)>),
+    BurnMany(Vec<String>),
+    TransferMany(Vec<String>),
+    InitializeConfig(ServiceFees, Pubkey),
+    SetFees(ServiceFees),
+    SetTreasury(Pubkey),
+    Approve(String, Pubkey),
+    Revoke(String),
+    SwapKeys {
+        key_a: String,
+        key_b: String,
+        signer_count_a: u8,
+        signer_count_b: u8,
+    },
+    MintToAccountWithExpiry(String, Value, Expiry),
+    PurgeExpired,
+    MigrateAccount,
+    InitializeZeroCopyAccount,
+    FreezeAccount,
+    ThawAccount,
+    LockKey(String),
+    UnlockKey(String),
+    SetFreezeAuthority(Pubkey),
+    InitializeMultisig(u8),
+    SetAuthority(Pubkey),
+    GetValue(String),
+    ContainsKey(String),
+    InitializeRegistry,
+    Pause,
+    Unpause,
+    SetKeyValueLimits(KeyValueLimits),
 }
 
 impl ProgramInstruction {
     /// Unpack inbound buffer to associated Instruction
-    /// The expected format for input is a Borsh serialized vector
+    /// The expected format for input is the version byte, the tag and the
+    /// borsh encoded fields, or the tag and the fields of earlier clients
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let payload = try_from_slice_unchecked::<ProgramInstruction>(input).unwrap();
-        match payload {
-            ProgramInstruction::InitializeAccount => Ok(payload),
-            ProgramInstruction::MintToAccount(_, _) => Ok(payload),
-            ProgramInstruction::TransferBetweenAccounts(_) => Ok(payload),
-            ProgramInstruction::BurnFromAccount(_) => Ok(payload),
-            ProgramInstruction::MintToAccountWithFee(_, _) => Ok(payload),
-            ProgramInstruction::TransferBetweenAccountsWithFee(_) => Ok(payload),
-            ProgramInstruction::BurnFromAccountWithFee(_) => Ok(payload),
+        try_from_slice_unchecked::<ProgramInstruction>(input)
+            .map_err(|_| SampleError::InvalidInstruction.into())
+    }
+
+    /// Returns whether the pause switch holds the instruction, which is every
+    /// instruction but the admin's and the read-only ones
+    pub fn is_pausable(&self) -> bool {
+        match self {
+            ProgramInstruction::InitializeConfig(_, _)
+            | ProgramInstruction::SetFees(_)
+            | ProgramInstruction::SetTreasury(_)
+            | ProgramInstruction::SetKeyValueLimits(_)
+            | ProgramInstruction::Pause
+            | ProgramInstruction::Unpause
+            | ProgramInstruction::GetValue(_)
+            | ProgramInstruction::ContainsKey(_) => false,
+            ProgramInstruction::InitializeAccount
+            | ProgramInstruction::MintToAccount(_, _)
+            | ProgramInstruction::TransferBetweenAccounts(_)
+            | ProgramInstruction::BurnFromAccount(_)
+            | ProgramInstruction::MintToAccountWithFee(_, _)
+            | ProgramInstruction::TransferBetweenAccountsWithFee(_)
+            | ProgramInstruction::BurnFromAccountWithFee(_)
+            | ProgramInstruction::InitializeDerivedAccount(_)
+            | ProgramInstruction::UpdateValue(_, _)
+            | ProgramInstruction::UpsertValue(_, _)
+            | ProgramInstruction::UpdateValueWithFee(_, _)
+            | ProgramInstruction::UpsertValueWithFee(_, _)
+            | ProgramInstruction::Resize(_)
+            | ProgramInstruction::CloseAccount(_)
+            | ProgramInstruction::MintMany(_)
+            | ProgramInstruction::BurnMany(_)
+            | ProgramInstruction::TransferMany(_)
+            | ProgramInstruction::Approve(_, _)
+            | ProgramInstruction::Revoke(_)
+            | ProgramInstruction::SwapKeys { .. }
+            | ProgramInstruction::MintToAccountWithExpiry(_, _, _)
+            | ProgramInstruction::PurgeExpired
+            | ProgramInstruction::MigrateAccount
+            | ProgramInstruction::InitializeZeroCopyAccount
+            | ProgramInstruction::FreezeAccount
+            | ProgramInstruction::ThawAccount
+            | ProgramInstruction::LockKey(_)
+            | ProgramInstruction::UnlockKey(_)
+            | ProgramInstruction::SetFreezeAuthority(_)
+            | ProgramInstruction::InitializeMultisig(_)
+            | ProgramInstruction::SetAuthority(_)
+            | ProgramInstruction::InitializeRegistry => true,
+        }
+    }
+}
+
+impl BorshSerialize for ProgramInstruction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            ProgramInstruction::InitializeAccount => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_ACCOUNT])?
+            }
+            ProgramInstruction::MintToAccount(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::MINT_TO_ACCOUNT])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::TransferBetweenAccounts(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::TRANSFER_BETWEEN_ACCOUNTS])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::BurnFromAccount(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::BURN_FROM_ACCOUNT])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::MintToAccountWithFee(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::MINT_TO_ACCOUNT_WITH_FEE])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::TransferBetweenAccountsWithFee(key) => {
+                writer
+                    .write_all(&[INSTRUCTION_VERSION, tag::TRANSFER_BETWEEN_ACCOUNTS_WITH_FEE])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::BurnFromAccountWithFee(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::BURN_FROM_ACCOUNT_WITH_FEE])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::InitializeDerivedAccount(seed) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_DERIVED_ACCOUNT])?;
+                seed.serialize(writer)?;
+            }
+            ProgramInstruction::UpdateValue(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UPDATE_VALUE])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::UpsertValue(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UPSERT_VALUE])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::UpdateValueWithFee(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UPDATE_VALUE_WITH_FEE])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::UpsertValueWithFee(key, value) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UPSERT_VALUE_WITH_FEE])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+            ProgramInstruction::Resize(size) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::RESIZE])?;
+                size.serialize(writer)?;
+            }
+            ProgramInstruction::CloseAccount(require_empty) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::CLOSE_ACCOUNT])?;
+                require_empty.serialize(writer)?;
+            }
+            ProgramInstruction::MintMany(key_values) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::MINT_MANY])?;
+                key_values.serialize(writer)?;
+            }
+            ProgramInstruction::BurnMany(keys) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::BURN_MANY])?;
+                keys.serialize(writer)?;
+            }
+            ProgramInstruction::TransferMany(keys) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::TRANSFER_MANY])?;
+                keys.serialize(writer)?;
+            }
+            ProgramInstruction::InitializeConfig(fees, treasury) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_CONFIG])?;
+                fees.serialize(writer)?;
+                treasury.serialize(writer)?;
+            }
+            ProgramInstruction::SetFees(fees) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SET_FEES])?;
+                fees.serialize(writer)?;
+            }
+            ProgramInstruction::SetTreasury(pubkey) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SET_TREASURY])?;
+                pubkey.serialize(writer)?;
+            }
+            ProgramInstruction::Approve(key, delegate) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::APPROVE])?;
+                key.serialize(writer)?;
+                delegate.serialize(writer)?;
+            }
+            ProgramInstruction::Revoke(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::REVOKE])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::SwapKeys {
+                key_a,
+                key_b,
+                signer_count_a,
+                signer_count_b,
+            } => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SWAP_KEYS])?;
+                key_a.serialize(writer)?;
+                key_b.serialize(writer)?;
+                signer_count_a.serialize(writer)?;
+                signer_count_b.serialize(writer)?;
+            }
+            ProgramInstruction::MintToAccountWithExpiry(key, value, expiry) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::MINT_TO_ACCOUNT_WITH_EXPIRY])?;
+                key.serialize(writer)?;
+                value.serialize(writer)?;
+                expiry.serialize(writer)?;
+            }
+            ProgramInstruction::PurgeExpired => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::PURGE_EXPIRED])?
+            }
+            ProgramInstruction::MigrateAccount => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::MIGRATE_ACCOUNT])?
+            }
+            ProgramInstruction::InitializeZeroCopyAccount => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_ZERO_COPY_ACCOUNT])?
+            }
+            ProgramInstruction::FreezeAccount => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::FREEZE_ACCOUNT])?
+            }
+            ProgramInstruction::ThawAccount => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::THAW_ACCOUNT])?
+            }
+            ProgramInstruction::LockKey(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::LOCK_KEY])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::UnlockKey(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UNLOCK_KEY])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::SetFreezeAuthority(pubkey) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SET_FREEZE_AUTHORITY])?;
+                pubkey.serialize(writer)?;
+            }
+            ProgramInstruction::InitializeMultisig(m) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_MULTISIG])?;
+                m.serialize(writer)?;
+            }
+            ProgramInstruction::SetAuthority(pubkey) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SET_AUTHORITY])?;
+                pubkey.serialize(writer)?;
+            }
+            ProgramInstruction::GetValue(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::GET_VALUE])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::ContainsKey(key) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::CONTAINS_KEY])?;
+                key.serialize(writer)?;
+            }
+            ProgramInstruction::InitializeRegistry => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::INITIALIZE_REGISTRY])?
+            }
+            ProgramInstruction::Pause => writer.write_all(&[INSTRUCTION_VERSION, tag::PAUSE])?,
+            ProgramInstruction::Unpause => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::UNPAUSE])?
+            }
+            ProgramInstruction::SetKeyValueLimits(limits) => {
+                writer.write_all(&[INSTRUCTION_VERSION, tag::SET_KEY_VALUE_LIMITS])?;
+                limits.serialize(writer)?;
+            }
         }
+        Ok(())
     }
 }
+
+impl BorshDeserialize for ProgramInstruction {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        // Data of earlier clients starts with the tag, without a version byte
+        let mut instruction_tag = u8::deserialize(buf)?;
+        if instruction_tag >= INSTRUCTION_VERSION {
+            if instruction_tag != INSTRUCTION_VERSION {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown instruction version {}", instruction_tag),
+                ));
+            }
+            instruction_tag = u8::deserialize(buf)?;
+        }
+        Ok(match instruction_tag {
+            tag::INITIALIZE_ACCOUNT => ProgramInstruction::InitializeAccount,
+            tag::MINT_TO_ACCOUNT => ProgramInstruction::MintToAccount(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::TRANSFER_BETWEEN_ACCOUNTS => {
+                ProgramInstruction::TransferBetweenAccounts(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::BURN_FROM_ACCOUNT => {
+                ProgramInstruction::BurnFromAccount(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::MINT_TO_ACCOUNT_WITH_FEE => ProgramInstruction::MintToAccountWithFee(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::TRANSFER_BETWEEN_ACCOUNTS_WITH_FEE => {
+                ProgramInstruction::TransferBetweenAccountsWithFee(BorshDeserialize::deserialize(
+                    buf,
+                )?)
+            }
+            tag::BURN_FROM_ACCOUNT_WITH_FEE => {
+                ProgramInstruction::BurnFromAccountWithFee(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::INITIALIZE_DERIVED_ACCOUNT => {
+                ProgramInstruction::InitializeDerivedAccount(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::UPDATE_VALUE => ProgramInstruction::UpdateValue(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::UPSERT_VALUE => ProgramInstruction::UpsertValue(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::UPDATE_VALUE_WITH_FEE => ProgramInstruction::UpdateValueWithFee(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::UPSERT_VALUE_WITH_FEE => ProgramInstruction::UpsertValueWithFee(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::RESIZE => ProgramInstruction::Resize(BorshDeserialize::deserialize(buf)?),
+            tag::CLOSE_ACCOUNT => {
+                ProgramInstruction::CloseAccount(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::MINT_MANY => ProgramInstruction::MintMany(BorshDeserialize::deserialize(buf)?),
+            tag::BURN_MANY => ProgramInstruction::BurnMany(BorshDeserialize::deserialize(buf)?),
+            tag::TRANSFER_MANY => {
+                ProgramInstruction::TransferMany(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::INITIALIZE_CONFIG => ProgramInstruction::InitializeConfig(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::SET_FEES => ProgramInstruction::SetFees(BorshDeserialize::deserialize(buf)?),
+            tag::SET_TREASURY => {
+                ProgramInstruction::SetTreasury(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::APPROVE => ProgramInstruction::Approve(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::REVOKE => ProgramInstruction::Revoke(BorshDeserialize::deserialize(buf)?),
+            tag::SWAP_KEYS => ProgramInstruction::SwapKeys {
+                key_a: BorshDeserialize::deserialize(buf)?,
+                key_b: BorshDeserialize::deserialize(buf)?,
+                signer_count_a: BorshDeserialize::deserialize(buf)?,
+                signer_count_b: BorshDeserialize::deserialize(buf)?,
+            },
+            tag::MINT_TO_ACCOUNT_WITH_EXPIRY => ProgramInstruction::MintToAccountWithExpiry(
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+                BorshDeserialize::deserialize(buf)?,
+            ),
+            tag::PURGE_EXPIRED => ProgramInstruction::PurgeExpired,
+            tag::MIGRATE_ACCOUNT => ProgramInstruction::MigrateAccount,
+            tag::INITIALIZE_ZERO_COPY_ACCOUNT => ProgramInstruction::InitializeZeroCopyAccount,
+            tag::FREEZE_ACCOUNT => ProgramInstruction::FreezeAccount,
+            tag::THAW_ACCOUNT => ProgramInstruction::ThawAccount,
+            tag::LOCK_KEY => ProgramInstruction::LockKey(BorshDeserialize::deserialize(buf)?),
+            tag::UNLOCK_KEY => ProgramInstruction::UnlockKey(BorshDeserialize::deserialize(buf)?),
+            tag::SET_FREEZE_AUTHORITY => {
+                ProgramInstruction::SetFreezeAuthority(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::INITIALIZE_MULTISIG => {
+                ProgramInstruction::InitializeMultisig(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::SET_AUTHORITY => {
+                ProgramInstruction::SetAuthority(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::GET_VALUE => ProgramInstruction::GetValue(BorshDeserialize::deserialize(buf)?),
+            tag::CONTAINS_KEY => {
+                ProgramInstruction::ContainsKey(BorshDeserialize::deserialize(buf)?)
+            }
+            tag::INITIALIZE_REGISTRY => ProgramInstruction::InitializeRegistry,
+            tag::PAUSE => ProgramInstruction::Pause,
+            tag::UNPAUSE => ProgramInstruction::Unpause,
+            tag::SET_KEY_VALUE_LIMITS => {
+                ProgramInstruction::SetKeyValueLimits(BorshDeserialize::deserialize(buf)?)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown instruction tag {}", instruction_tag),
+                ))
+            }
+        })
+    }
+}
+
+/// Builds the instruction, passing the config account, from which the
+/// program reads the pause switch and the fees, as the first account of the
+/// instructions the switch holds
+fn new_instruction(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    mut accounts: Vec<AccountMeta>,
+) -> Instruction {
+    if instruction.is_pausable() {
+        accounts.insert(
+            0,
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        );
+    }
+    Instruction::new_with_borsh(*program_id, instruction, accounts)
+}
+
+/// The registry account, which the instructions counted in the registry are
+/// passed
+fn registry_meta(program_id: &Pubkey) -> AccountMeta {
+    AccountMeta::new(find_registry_address(program_id).0, false)
+}
+
+/// Appends the authority, signing, or when `signer_pubkeys` are given the
+/// multisig authority followed by its signers
+fn push_authority(accounts: &mut Vec<AccountMeta>, authority: &Pubkey, signer_pubkeys: &[Pubkey]) {
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.extend(
+        signer_pubkeys
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true)),
+    );
+}
+
+/// Builds an instruction of the account, the registry and the authority,
+/// the accounts of the minting and burning instructions
+fn with_registry_and_authority(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*account, false), registry_meta(program_id)];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(program_id, instruction, accounts)
+}
+
+/// Builds an instruction of the account, the system program, the funder of
+/// its rent, signing, and the authority, the accounts of the instructions
+/// that change the size of the account
+fn with_funder_and_authority(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    account: AccountMeta,
+    funder: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        account,
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*funder, true),
+    ];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(program_id, instruction, accounts)
+}
+
+/// Builds an instruction of the account and its authority, the common roles
+/// of most instructions
+fn with_authority(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*account, false)];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(program_id, instruction, accounts)
+}
+
+/// Builds an instruction of the accounts, a fee charged to them, credited to
+/// the treasury of the config, the registry and the authority. The fee is
+/// read from the config account, which leads the accounts
+fn with_fee(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    accounts: &[&Pubkey],
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    let mut account_metas: Vec<AccountMeta> = accounts
+        .iter()
+        .map(|account| AccountMeta::new(**account, false))
+        .collect();
+    account_metas.extend([
+        AccountMeta::new(*treasury, false),
+        registry_meta(program_id),
+    ]);
+    push_authority(&mut account_metas, authority, signer_pubkeys);
+    new_instruction(program_id, instruction, account_metas)
+}
+
+/// `InitializeAccount`, recording the authority
+pub fn initialize_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeAccount,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*authority, true),
+            registry_meta(program_id),
+        ],
+    )
+}
+
+/// `InitializeZeroCopyAccount`, recording the authority
+pub fn initialize_zero_copy_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeZeroCopyAccount,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*authority, true),
+            registry_meta(program_id),
+        ],
+    )
+}
+
+/// `InitializeDerivedAccount`, the wallet funds the account at the address
+/// derived from it and the seed and becomes its authority
+pub fn initialize_derived_account(program_id: &Pubkey, wallet: &Pubkey, seed: &str) -> Instruction {
+    let (account, _) = find_derived_account_address(program_id, wallet, seed);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeDerivedAccount(seed.to_string()),
+        vec![
+            AccountMeta::new(account, false),
+            AccountMeta::new(*wallet, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            registry_meta(program_id),
+        ],
+    )
+}
+
+/// `MintToAccount`
+pub fn mint_to_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::MintToAccount(key.to_string(), value.clone()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `MintToAccountWithExpiry`
+pub fn mint_to_account_with_expiry(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+    expiry: Expiry,
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::MintToAccountWithExpiry(key.to_string(), value.clone(), expiry),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `MintToAccountWithFee`, the fee credited to the treasury of the config
+pub fn mint_to_account_with_fee(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_fee(
+        program_id,
+        &ProgramInstruction::MintToAccountWithFee(key.to_string(), value.clone()),
+        &[account],
+        treasury,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `MintMany`, all or none of the key/value pairs
+pub fn mint_many(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key_values: &[(String, Value)],
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::MintMany(key_values.to_vec()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `TransferBetweenAccounts`, the authority of the from account, or the
+/// keys delegate, signs
+pub fn transfer_between_accounts(
+    program_id: &Pubkey,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*from_account, false),
+        AccountMeta::new(*to_account, false),
+    ];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::TransferBetweenAccounts(key.to_string()),
+        accounts,
+    )
+}
+
+/// `TransferBetweenAccountsWithFee`, the fee charged to both accounts
+pub fn transfer_between_accounts_with_fee(
+    program_id: &Pubkey,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_fee(
+        program_id,
+        &ProgramInstruction::TransferBetweenAccountsWithFee(key.to_string()),
+        &[from_account, to_account],
+        treasury,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `TransferMany`, all or none of the keys
+pub fn transfer_many(
+    program_id: &Pubkey,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    keys: &[String],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*from_account, false),
+        AccountMeta::new(*to_account, false),
+    ];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::TransferMany(keys.to_vec()),
+        accounts,
+    )
+}
+
+/// `BurnFromAccount`
+pub fn burn_from_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::BurnFromAccount(key.to_string()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `BurnFromAccountWithFee`, the fee credited to the treasury of the config
+pub fn burn_from_account_with_fee(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_fee(
+        program_id,
+        &ProgramInstruction::BurnFromAccountWithFee(key.to_string()),
+        &[account],
+        treasury,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `BurnMany`, all or none of the keys
+pub fn burn_many(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    keys: &[String],
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::BurnMany(keys.to_vec()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `UpdateValue` of an existing key
+pub fn update_value(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::UpdateValue(key.to_string(), value.clone()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `UpdateValueWithFee`, the fee credited to the treasury of the config
+pub fn update_value_with_fee(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_fee(
+        program_id,
+        &ProgramInstruction::UpdateValueWithFee(key.to_string(), value.clone()),
+        &[account],
+        treasury,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `UpsertValue`, adding the key if it does not exist
+pub fn upsert_value(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_registry_and_authority(
+        program_id,
+        &ProgramInstruction::UpsertValue(key.to_string(), value.clone()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `UpsertValueWithFee`, the fee credited to the treasury of the config
+pub fn upsert_value_with_fee(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    treasury: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    value: &Value,
+) -> Instruction {
+    with_fee(
+        program_id,
+        &ProgramInstruction::UpsertValueWithFee(key.to_string(), value.clone()),
+        &[account],
+        treasury,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `Resize`, the funder funding growth or refunded when shrinking, so the
+/// authority may be a multisig
+pub fn resize(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    funder: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    new_size: u64,
+) -> Instruction {
+    with_funder_and_authority(
+        program_id,
+        &ProgramInstruction::Resize(new_size),
+        AccountMeta::new(*account, false),
+        funder,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `MigrateAccount`, the funder funding growth. An account of the `V0`
+/// layout has no authority, so the account signs as well
+pub fn migrate_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    funder: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    account_is_signer: bool,
+) -> Instruction {
+    with_funder_and_authority(
+        program_id,
+        &ProgramInstruction::MigrateAccount,
+        AccountMeta::new(*account, account_is_signer),
+        funder,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `CloseAccount`, moving its lamports to the destination
+pub fn close_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    require_empty: bool,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*account, false),
+        AccountMeta::new(*destination, false),
+        registry_meta(program_id),
+    ];
+    push_authority(&mut accounts, authority, signer_pubkeys);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CloseAccount(require_empty),
+        accounts,
+    )
+}
+
+/// `PurgeExpired`, which needs no signer
+pub fn purge_expired(program_id: &Pubkey, account: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::PurgeExpired,
+        vec![AccountMeta::new(*account, false), registry_meta(program_id)],
+    )
+}
+
+/// `Approve` the delegate to transfer the key
+pub fn approve(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+    delegate: &Pubkey,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::Approve(key.to_string(), *delegate),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `Revoke` the delegate of the key
+pub fn revoke(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::Revoke(key.to_string()),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `SwapKeys`, the authorities of both accounts sign, each a wallet or a
+/// multisig with its `signer_pubkeys`. The signer counts tell the program
+/// where the accounts of A's authority end and those of B's begin
+#[allow(clippy::too_many_arguments)]
+pub fn swap_keys(
+    program_id: &Pubkey,
+    account_a: &Pubkey,
+    account_b: &Pubkey,
+    authority_a: &Pubkey,
+    signer_pubkeys_a: &[Pubkey],
+    authority_b: &Pubkey,
+    signer_pubkeys_b: &[Pubkey],
+    key_a: &str,
+    key_b: &str,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*account_a, false),
+        AccountMeta::new(*account_b, false),
+    ];
+    push_authority(&mut accounts, authority_a, signer_pubkeys_a);
+    push_authority(&mut accounts, authority_b, signer_pubkeys_b);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::SwapKeys {
+            key_a: key_a.to_string(),
+            key_b: key_b.to_string(),
+            signer_count_a: signer_pubkeys_a.len() as u8,
+            signer_count_b: signer_pubkeys_b.len() as u8,
+        },
+        accounts,
+    )
+}
+
+/// `FreezeAccount`, the freeze authority signs, a wallet or a multisig with its
+/// `signer_pubkeys`
+pub fn freeze_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::FreezeAccount,
+        account,
+        freeze_authority,
+        signer_pubkeys,
+    )
+}
+
+/// `ThawAccount`, the freeze authority signs, a wallet or a multisig with its
+/// `signer_pubkeys`
+pub fn thaw_account(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::ThawAccount,
+        account,
+        freeze_authority,
+        signer_pubkeys,
+    )
+}
+
+/// `LockKey`, the freeze authority signs, a wallet or a multisig with its
+/// `signer_pubkeys`
+pub fn lock_key(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::LockKey(key.to_string()),
+        account,
+        freeze_authority,
+        signer_pubkeys,
+    )
+}
+
+/// `UnlockKey`, the freeze authority signs, a wallet or a multisig with its
+/// `signer_pubkeys`
+pub fn unlock_key(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    key: &str,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::UnlockKey(key.to_string()),
+        account,
+        freeze_authority,
+        signer_pubkeys,
+    )
+}
+
+/// `SetFreezeAuthority`, the current freeze authority signs, a wallet or a
+/// multisig with its `signer_pubkeys`
+pub fn set_freeze_authority(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    new_freeze_authority: &Pubkey,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::SetFreezeAuthority(*new_freeze_authority),
+        account,
+        freeze_authority,
+        signer_pubkeys,
+    )
+}
+
+/// `InitializeMultisig` requiring `m` of the signers
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    m: u8,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*multisig, false)];
+    accounts.extend(
+        signer_pubkeys
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, false)),
+    );
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeMultisig(m),
+        accounts,
+    )
+}
+
+/// `SetAuthority`, the current authority signs
+pub fn set_authority(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signer_pubkeys: &[Pubkey],
+    new_authority: &Pubkey,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::SetAuthority(*new_authority),
+        account,
+        authority,
+        signer_pubkeys,
+    )
+}
+
+/// `InitializeConfig`, the admin, which must be the program's upgrade
+/// authority, funds the config account and becomes its admin
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fees: ServiceFees,
+    treasury: &Pubkey,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeConfig(fees, *treasury),
+        vec![
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(
+                bpf_loader_upgradeable::get_program_data_address(program_id),
+                false,
+            ),
+        ],
+    )
+}
+
+/// `SetFees`, the admin signs
+pub fn set_fees(program_id: &Pubkey, admin: &Pubkey, fees: ServiceFees) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::SetFees(fees),
+        &find_config_address(program_id).0,
+        admin,
+        &[],
+    )
+}
+
+/// `SetTreasury`, the admin signs
+pub fn set_treasury(program_id: &Pubkey, admin: &Pubkey, treasury: &Pubkey) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::SetTreasury(*treasury),
+        &find_config_address(program_id).0,
+        admin,
+        &[],
+    )
+}
+
+/// `GetValue`, which only reads the account
+pub fn get_value(program_id: &Pubkey, account: &Pubkey, key: &str) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::GetValue(key.to_string()),
+        vec![AccountMeta::new_readonly(*account, false)],
+    )
+}
+
+/// `ContainsKey`, which only reads the account
+pub fn contains_key(program_id: &Pubkey, account: &Pubkey, key: &str) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ContainsKey(key.to_string()),
+        vec![AccountMeta::new_readonly(*account, false)],
+    )
+}
+
+/// `InitializeRegistry`, the payer funds the registry account
+pub fn initialize_registry(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeRegistry,
+        vec![
+            AccountMeta::new(find_registry_address(program_id).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `Pause`, the admin signs
+pub fn pause(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::Pause,
+        &find_config_address(program_id).0,
+        admin,
+        &[],
+    )
+}
+
+/// `Unpause`, the admin signs
+pub fn unpause(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::Unpause,
+        &find_config_address(program_id).0,
+        admin,
+        &[],
+    )
+}
+
+/// `SetKeyValueLimits`, the admin signs
+pub fn set_key_value_limits(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    limits: KeyValueLimits,
+) -> Instruction {
+    with_authority(
+        program_id,
+        &ProgramInstruction::SetKeyValueLimits(limits),
+        &find_config_address(program_id).0,
+        admin,
+        &[],
+    )
+}
@@ -1,10 +1,56 @@
 pub mod account_state;
+pub mod config_state;
+pub mod cpi;
 pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod multisig_state;
 pub mod processor;
+pub mod registry_state;
 pub use solana_program;
 
 #[cfg(not(feature = "no-entrypoint"))]
 mod entry_point;
 
 solana_program::declare_id!("SampGgdt3wioaoMZhC6LTSbg4pnuvQnSfJpDYeuXQBv");
+
+/// Derives the program account address for a wallet and seed
+///
+/// The program creates and initializes the account at this address with
+/// `ProgramInstruction::InitializeDerivedAccount`
+pub fn find_derived_account_address(
+    program_id: &solana_program::pubkey::Pubkey,
+    wallet: &solana_program::pubkey::Pubkey,
+    seed: &str,
+) -> (solana_program::pubkey::Pubkey, u8) {
+    solana_program::pubkey::Pubkey::find_program_address(
+        &[wallet.as_ref(), seed.as_bytes()],
+        program_id,
+    )
+}
+
+/// Seed used to derive the program configuration account address
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Derives the program configuration account address
+///
+/// The program creates and initializes the account at this address with
+/// `ProgramInstruction::InitializeConfig`
+pub fn find_config_address(
+    program_id: &solana_program::pubkey::Pubkey,
+) -> (solana_program::pubkey::Pubkey, u8) {
+    solana_program::pubkey::Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Seed used to derive the registry account address
+pub const REGISTRY_SEED: &[u8] = b"registry";
+
+/// Derives the registry account address
+///
+/// The program creates and initializes the account at this address with
+/// `ProgramInstruction::InitializeRegistry`
+pub fn find_registry_address(
+    program_id: &solana_program::pubkey::Pubkey,
+) -> (solana_program::pubkey::Pubkey, u8) {
+    solana_program::pubkey::Pubkey::find_program_address(&[REGISTRY_SEED], program_id)
+}
@@ -0,0 +1,80 @@
+//! @brief multisig_state manages the data of M-of-N multisig accounts
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Most signers a multisig may list
+pub const MAX_SIGNERS: usize = 11;
+
+/// Space allocated for a multisig account, enough for the most signers
+pub const MULTISIG_STATE_SPACE: usize = 1 + 1 + 4 + MAX_SIGNERS * 32;
+
+/// A multisig, like SPL Token's `Multisig`, that program accounts may name
+/// as their authority. Mutations of those accounts require at least `m` of
+/// the listed signers to sign
+#[derive(BorshDeserialize, BorshSerialize, Debug, Default, PartialEq)]
+pub struct ProgramMultisigState {
+    is_initialized: bool,
+    m: u8,
+    signers: Vec<Pubkey>,
+}
+
+impl ProgramMultisigState {
+    /// Marks the account as initialized
+    pub fn set_initialized(&mut self) {
+        self.is_initialized = true;
+    }
+    /// Sets the number of signers required
+    pub fn set_m(&mut self, m: u8) {
+        self.m = m;
+    }
+    /// Returns the number of signers required
+    pub fn m(&self) -> u8 {
+        self.m
+    }
+    /// Sets the signers that may sign for the multisig
+    pub fn set_signers(&mut self, signers: &[Pubkey]) {
+        self.signers = signers.to_vec();
+    }
+    /// Returns the signers that may sign for the multisig
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.signers
+    }
+    /// Returns how many of the listed signers are among the signing wallets,
+    /// each listed signer counting once
+    pub fn count_signed(&self, signed: &[&Pubkey]) -> usize {
+        self.signers
+            .iter()
+            .filter(|signer| signed.contains(signer))
+            .count()
+    }
+}
+
+impl Sealed for ProgramMultisigState {}
+
+impl IsInitialized for ProgramMultisigState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProgramMultisigState {
+    const LEN: usize = MULTISIG_STATE_SPACE;
+
+    /// Store 'state' of the multisig to its data area
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let multisig_data = self.try_to_vec().unwrap();
+        dst[..multisig_data.len()].copy_from_slice(&multisig_data);
+    }
+
+    /// Retrieve 'state' of the multisig from its data area
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_unchecked::<ProgramMultisigState>(src)
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
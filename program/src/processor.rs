@@ -1,33 +1,160 @@
 //! processor conducts instruction execution
 
 use crate::{
-    account_state::ProgramAccountState, error::SampleError, instruction::ProgramInstruction,
+    account_state::{unpack_account_data, ProgramAccountState},
+    config_state::{ProgramConfigState, ServiceFees, CONFIG_STATE_SPACE},
+    error::SampleError,
+    event::ProgramEvent,
+    find_config_address, find_derived_account_address, find_registry_address,
+    instruction::ProgramInstruction,
+    multisig_state::{ProgramMultisigState, MAX_SIGNERS},
+    registry_state::{ProgramRegistryState, REGISTRY_STATE_SPACE},
+    CONFIG_SEED, REGISTRY_SEED,
 };
 
+use borsh::BorshSerialize;
+use sol_template_shared::{
+    Expiry, KeyValueLimits, LayoutVersion, Value, ACCOUNT_STATE_SPACE, KEY_VALUE_LIMITS,
+};
 use solana_program::{
     account_info::next_account_info,
     account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
     entrypoint::ProgramResult,
     msg,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack},
-    pubkey::Pubkey,
+    pubkey::{Pubkey, MAX_SEED_LEN},
+    system_instruction, system_program,
+    sysvar::{rent::Rent, Sysvar},
 };
 
-/// Service fees debited from participating accounts and
-/// credited to a 'service' account
-enum SampleServiceFees {
-    Minting = 10,
-    Transfering = 30,
-    Burning = 15,
+/// Returns how many accounts, from the start of the accounts array, the
+/// instruction operates on as tracking accounts
+fn tracking_account_count(instruction: &ProgramInstruction) -> usize {
+    match instruction {
+        ProgramInstruction::InitializeDerivedAccount(_)
+        | ProgramInstruction::InitializeConfig(_, _)
+        | ProgramInstruction::InitializeRegistry => 0,
+        ProgramInstruction::InitializeAccount
+        | ProgramInstruction::InitializeZeroCopyAccount
+        | ProgramInstruction::MintToAccount(_, _)
+        | ProgramInstruction::BurnFromAccount(_)
+        | ProgramInstruction::UpdateValue(_, _)
+        | ProgramInstruction::UpsertValue(_, _)
+        | ProgramInstruction::Resize(_)
+        | ProgramInstruction::CloseAccount(_)
+        | ProgramInstruction::MintMany(_)
+        | ProgramInstruction::BurnMany(_)
+        | ProgramInstruction::SetFees(_)
+        | ProgramInstruction::SetTreasury(_)
+        | ProgramInstruction::SetKeyValueLimits(_)
+        | ProgramInstruction::Pause
+        | ProgramInstruction::Unpause
+        | ProgramInstruction::Approve(_, _)
+        | ProgramInstruction::Revoke(_)
+        | ProgramInstruction::MintToAccountWithExpiry(_, _, _)
+        | ProgramInstruction::PurgeExpired
+        | ProgramInstruction::MigrateAccount
+        | ProgramInstruction::FreezeAccount
+        | ProgramInstruction::ThawAccount
+        | ProgramInstruction::LockKey(_)
+        | ProgramInstruction::UnlockKey(_)
+        | ProgramInstruction::SetFreezeAuthority(_)
+        | ProgramInstruction::InitializeMultisig(_)
+        | ProgramInstruction::SetAuthority(_)
+        | ProgramInstruction::GetValue(_)
+        | ProgramInstruction::ContainsKey(_) => 1,
+        ProgramInstruction::TransferBetweenAccounts(_)
+        | ProgramInstruction::TransferMany(_)
+        | ProgramInstruction::SwapKeys { .. }
+        | ProgramInstruction::MintToAccountWithFee(_, _)
+        | ProgramInstruction::BurnFromAccountWithFee(_)
+        | ProgramInstruction::UpdateValueWithFee(_, _)
+        | ProgramInstruction::UpsertValueWithFee(_, _) => 2,
+        ProgramInstruction::TransferBetweenAccountsWithFee(_) => 3,
+    }
+}
+
+/// Returns the position of the registry among the accounts of the
+/// instructions counted in it, those that initialize accounts, add or remove
+/// keys or charge a fee
+fn registry_position(instruction: &ProgramInstruction) -> Option<usize> {
+    match instruction {
+        ProgramInstruction::TransferBetweenAccounts(_)
+        | ProgramInstruction::UpdateValue(_, _)
+        | ProgramInstruction::Resize(_)
+        | ProgramInstruction::TransferMany(_)
+        | ProgramInstruction::InitializeConfig(_, _)
+        | ProgramInstruction::SetFees(_)
+        | ProgramInstruction::SetTreasury(_)
+        | ProgramInstruction::SetKeyValueLimits(_)
+        | ProgramInstruction::Approve(_, _)
+        | ProgramInstruction::Revoke(_)
+        | ProgramInstruction::SwapKeys { .. }
+        | ProgramInstruction::MigrateAccount
+        | ProgramInstruction::FreezeAccount
+        | ProgramInstruction::ThawAccount
+        | ProgramInstruction::LockKey(_)
+        | ProgramInstruction::UnlockKey(_)
+        | ProgramInstruction::SetFreezeAuthority(_)
+        | ProgramInstruction::InitializeMultisig(_)
+        | ProgramInstruction::SetAuthority(_)
+        | ProgramInstruction::GetValue(_)
+        | ProgramInstruction::ContainsKey(_)
+        | ProgramInstruction::InitializeRegistry
+        | ProgramInstruction::Pause
+        | ProgramInstruction::Unpause => None,
+        ProgramInstruction::MintToAccount(_, _)
+        | ProgramInstruction::BurnFromAccount(_)
+        | ProgramInstruction::UpsertValue(_, _)
+        | ProgramInstruction::MintMany(_)
+        | ProgramInstruction::BurnMany(_)
+        | ProgramInstruction::MintToAccountWithExpiry(_, _, _)
+        | ProgramInstruction::PurgeExpired => Some(1),
+        ProgramInstruction::InitializeAccount
+        | ProgramInstruction::InitializeZeroCopyAccount
+        | ProgramInstruction::CloseAccount(_)
+        | ProgramInstruction::MintToAccountWithFee(_, _)
+        | ProgramInstruction::BurnFromAccountWithFee(_)
+        | ProgramInstruction::UpdateValueWithFee(_, _)
+        | ProgramInstruction::UpsertValueWithFee(_, _) => Some(2),
+        ProgramInstruction::InitializeDerivedAccount(_)
+        | ProgramInstruction::TransferBetweenAccountsWithFee(_) => Some(3),
+    }
+}
+
+/// Confirms the account at the registry position of the instructions counted
+/// in the registry is the registry, so the counters can not be skipped by
+/// leaving it out
+fn check_registry_account(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let position = match registry_position(instruction) {
+        Some(position) => position,
+        None => return Ok(()),
+    };
+    let registry_account = accounts
+        .get(position)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if registry_account.key != &find_registry_address(program_id).0 {
+        msg!(
+            "Fail: The account {} is not the registry account.",
+            registry_account.key
+        );
+        return Err(SampleError::InvalidRegistryAccount.into());
+    }
+    Ok(())
 }
 
 /// Checks each tracking account to confirm it is owned by our program
-/// This function assumes that the program account is always the last
-/// in the array
 fn check_account_ownership(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     // Accounts must be owned by the program.
-    for account in accounts.iter().take(accounts.len() - 1) {
+    for account in accounts.iter() {
         if account.owner != program_id {
             msg!(
                 "Fail: The tracking account owner is {} and it should be {}.",
@@ -40,149 +167,1550 @@ fn check_account_ownership(program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
     Ok(())
 }
 
-/// Extracts the service fee from the users program account and
-/// credits the service account
+/// Confirms the authority recorded in the account state signed the
+/// transaction. This function assumes that the authority (wallet) is
+/// always the last in the array or, when the authority is a multisig, that
+/// the multisig is followed by its signing wallets at the end of the array
+fn check_authority(account_state: &ProgramAccountState, accounts: &[AccountInfo]) -> ProgramResult {
+    let program_account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    check_authority_among(program_account.owner, account_state, accounts)
+}
+
+/// Confirms the authority recorded in the account state signed the
+/// transaction, the authority (wallet) being the last of the accounts or,
+/// when the authority is a multisig owned by the program, the multisig
+/// being followed by its signing wallets at the end of the accounts
+fn check_authority_among(
+    program_id: &Pubkey,
+    account_state: &ProgramAccountState,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if let Some((multisig_state, signers)) =
+        find_multisig_authority(program_id, &account_state.authority(), accounts)
+    {
+        return check_multisig_signers(&multisig_state, signers);
+    }
+    let authority = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    check_authority_signer(account_state, authority)
+}
+
+/// Returns the multisig and the accounts following it when the authority is
+/// a multisig, owned by the program, among the accounts
+fn find_multisig_authority<'a, 'b>(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+) -> Option<(ProgramMultisigState, &'b [AccountInfo<'a>])> {
+    let position = accounts
+        .iter()
+        .position(|account| account.key == authority && account.owner == program_id)?;
+    let multisig_state =
+        ProgramMultisigState::unpack(&accounts[position].try_borrow_data().ok()?).ok()?;
+    Some((multisig_state, &accounts[position + 1..]))
+}
+
+/// Confirms at least the multisig's threshold of its listed signers signed
+/// the transaction
+fn check_multisig_signers(
+    multisig_state: &ProgramMultisigState,
+    signers: &[AccountInfo],
+) -> ProgramResult {
+    let signed: Vec<&Pubkey> = signers
+        .iter()
+        .filter(|signer| signer.is_signer)
+        .map(|signer| signer.key)
+        .collect();
+    let signed_count = multisig_state.count_signed(&signed);
+    if signed_count < multisig_state.m() as usize {
+        msg!(
+            "Fail: {} multisig signers signed and {} must sign.",
+            signed_count,
+            multisig_state.m()
+        );
+        return Err(SampleError::MissingMultisigSignatures.into());
+    }
+    Ok(())
+}
+
+/// Confirms the authority recorded in the account state is the signer
+fn check_authority_signer(
+    account_state: &ProgramAccountState,
+    authority: &AccountInfo,
+) -> ProgramResult {
+    if !authority.is_signer {
+        msg!("Fail: The authority {} did not sign.", authority.key);
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    if *authority.key != account_state.authority() {
+        msg!(
+            "Fail: The signer is {} and it should be {}.",
+            authority.key,
+            account_state.authority()
+        );
+        return Err(SampleError::InvalidAuthority.into());
+    }
+    Ok(())
+}
+
+/// Confirms either the authority recorded in the account state or the
+/// delegate approved for the key signed the transaction. This function
+/// assumes that the signer is always the last in the array
+fn check_authority_or_delegate(
+    account_state: &ProgramAccountState,
+    key: &str,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let signer = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    match account_state.delegate(key) {
+        Some(delegate) if signer.is_signer && *signer.key == delegate => Ok(()),
+        _ => check_authority(account_state, accounts),
+    }
+}
+
+/// Confirms the freeze authority of the account state, which is the
+/// authority until another is set, signed the transaction. This function
+/// assumes that the freeze authority is always the last in the array or,
+/// when it is a multisig, that the multisig is followed by its signing
+/// wallets at the end of the array
+fn check_freeze_authority(
+    account_state: &ProgramAccountState,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let program_account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if let Some((multisig_state, signers)) = find_multisig_authority(
+        program_account.owner,
+        &account_state.freeze_authority(),
+        accounts,
+    ) {
+        return check_multisig_signers(&multisig_state, signers);
+    }
+    let freeze_authority = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !freeze_authority.is_signer {
+        msg!(
+            "Fail: The freeze authority {} did not sign.",
+            freeze_authority.key
+        );
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    if *freeze_authority.key != account_state.freeze_authority() {
+        msg!(
+            "Fail: The signer is {} and it should be {}.",
+            freeze_authority.key,
+            account_state.freeze_authority()
+        );
+        return Err(SampleError::InvalidFreezeAuthority.into());
+    }
+    Ok(())
+}
+
+/// Confirms the admin recorded in the config signed the transaction. This
+/// function assumes that the admin is always the last in the array
+fn check_admin(config_state: &ProgramConfigState, accounts: &[AccountInfo]) -> ProgramResult {
+    let admin = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !admin.is_signer {
+        msg!("Fail: The admin {} did not sign.", admin.key);
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    if admin.key != config_state.admin() {
+        msg!(
+            "Fail: The signer is {} and it should be {}.",
+            admin.key,
+            config_state.admin()
+        );
+        return Err(SampleError::InvalidAuthority.into());
+    }
+    Ok(())
+}
+
+/// Confirms the admin is the upgrade authority recorded in the program's
+/// program data account, so only the deployer of the program may create its
+/// config
+fn check_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_account: &AccountInfo,
+    admin: &AccountInfo,
+) -> ProgramResult {
+    if program_data_account.key != &bpf_loader_upgradeable::get_program_data_address(program_id)
+        || program_data_account.owner != &bpf_loader_upgradeable::id()
+    {
+        msg!(
+            "Fail: The account {} is not the program data account.",
+            program_data_account.key
+        );
+        return Err(SampleError::InvalidProgramDataAccount.into());
+    }
+    match program_data_account.deserialize_data::<UpgradeableLoaderState>() {
+        Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address: Some(upgrade_authority),
+            ..
+        }) if upgrade_authority == *admin.key => Ok(()),
+        _ => {
+            msg!(
+                "Fail: The signer {} is not the program upgrade authority.",
+                admin.key
+            );
+            Err(SampleError::InvalidAuthority.into())
+        }
+    }
+}
+
+/// Splits off the config account, which the instructions the pause switch
+/// holds take as their first account, and refuses the instruction while the
+/// program is paused. Returns the config account, if the instruction takes
+/// it, and the accounts the instruction operates on, those after it
+fn check_not_paused<'a, 'b>(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    accounts: &'b [AccountInfo<'a>],
+) -> Result<(Option<&'b AccountInfo<'a>>, &'b [AccountInfo<'a>]), ProgramError> {
+    if !instruction.is_pausable() {
+        return Ok((None, accounts));
+    }
+    let (config_account, instruction_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if config_account.key != &find_config_address(program_id).0 {
+        msg!(
+            "Fail: The account {} is not the config account.",
+            config_account.key
+        );
+        return Err(SampleError::InvalidConfigAccount.into());
+    }
+    // The program is never paused before the config account is created
+    if config_account.owner == program_id
+        && ProgramConfigState::unpack(&config_account.data.borrow())?.is_paused()
+    {
+        msg!("Fail: The program is paused.");
+        return Err(SampleError::ProgramPaused.into());
+    }
+    Ok((Some(config_account), instruction_accounts))
+}
+
+/// Reads the program configuration from the config account
+fn load_config_state(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<ProgramConfigState, ProgramError> {
+    if config_account.key != &find_config_address(program_id).0 {
+        msg!(
+            "Fail: The account {} is not the config account.",
+            config_account.key
+        );
+        return Err(SampleError::InvalidConfigAccount.into());
+    }
+    ProgramConfigState::unpack(&config_account.data.borrow())
+}
+
+/// Reads the key/value limits from the config account, falling back to the
+/// default limits for instructions that do not take the config account and
+/// until the config account is created
+fn load_key_value_limits(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
+) -> Result<KeyValueLimits, ProgramError> {
+    match config_account {
+        Some(config_account) if config_account.owner == program_id => {
+            Ok(ProgramConfigState::unpack(&config_account.data.borrow())?.key_value_limits())
+        }
+        _ => Ok(KEY_VALUE_LIMITS),
+    }
+}
+
+/// Adds to the counters of the registry, which `process` confirmed is
+/// passed to the instructions counted in it. The counters are left as they
+/// are until the registry has been initialized
+fn update_registry<F>(program_id: &Pubkey, accounts: &[AccountInfo], update: F) -> ProgramResult
+where
+    F: FnOnce(&mut ProgramRegistryState),
+{
+    let registry_address = find_registry_address(program_id).0;
+    let registry_account = match accounts
+        .iter()
+        .find(|account| account.key == &registry_address && account.owner == program_id)
+    {
+        Some(registry_account) => registry_account,
+        None => return Ok(()),
+    };
+    let mut registry_data = registry_account.try_borrow_mut_data()?;
+    let mut registry_state = ProgramRegistryState::unpack(&registry_data)?;
+    update(&mut registry_state);
+    ProgramRegistryState::pack(registry_state, &mut registry_data)
+}
+
+/// Extracts the service fee from the users program account and credits
+/// the treasury account, which must be the one registered in the config.
+/// The users program account must remain rent exempt after the fee
 fn charge_service_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     program_account: &AccountInfo,
-    service_account: &AccountInfo,
+    treasury_account: &AccountInfo,
+    config_state: &ProgramConfigState,
     amount: u64,
 ) -> ProgramResult {
+    if treasury_account.key != config_state.treasury() {
+        msg!(
+            "Fail: The treasury is {} and it should be {}.",
+            treasury_account.key,
+            config_state.treasury()
+        );
+        return Err(SampleError::InvalidTreasuryAccount.into());
+    }
     // If tracking can not afford transaction fee
-    if **program_account.try_borrow_lamports()? < amount {
-        return Err(SampleError::InsufficientFundsForTransaction.into());
+    let remaining_lamports = program_account
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(SampleError::InsufficientFundsForTransaction)?;
+    if remaining_lamports < Rent::get()?.minimum_balance(program_account.data_len()) {
+        return Err(SampleError::FeeBreaksRentExemption.into());
+    }
+    // Debit user and credit treasury
+    **program_account.try_borrow_mut_lamports()? = remaining_lamports;
+    let treasury_lamports = treasury_account
+        .lamports()
+        .checked_add(amount)
+        .ok_or(SampleError::ArithmeticOverflow)?;
+    **treasury_account.try_borrow_mut_lamports()? = treasury_lamports;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_fees_collected(amount)
+    })?;
+    ProgramEvent::FeeCharged {
+        account: *program_account.key,
+        treasury: *treasury_account.key,
+        amount,
     }
-    // Debit user and credit service
-    **program_account.try_borrow_mut_lamports()? -= amount;
-    **service_account.try_borrow_mut_lamports()? += amount;
+    .emit();
     Ok(())
 }
 
-/// Initialize the programs account, which is the first in accounts
-fn initialize_account(accounts: &[AccountInfo]) -> ProgramResult {
+/// Initialize the programs account, which is the first in accounts,
+/// recording the signing wallet, which is the second, as its authority
+fn initialize_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     msg!("Initialize account");
     let account_info_iter = &mut accounts.iter();
     let program_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    if !authority.is_signer {
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
     let mut account_data = program_account.data.borrow_mut();
     // Just using unpack will check to see if initialized and will
     // fail if not
-    let mut account_state = ProgramAccountState::unpack_unchecked(&account_data)?;
+    let mut account_state = ProgramAccountState::unpack_unchecked(&mut account_data)?;
     // Where this is a logic error in trying to initialize the same
     // account more than once
     if account_state.is_initialized() {
         return Err(SampleError::AlreadyInitializedState.into());
     } else {
         account_state.set_initialized();
+        account_state.set_authority(authority.key);
     }
 
-    ProgramAccountState::pack(account_state, &mut account_data).unwrap();
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_account_initialized()
+    })?;
+    ProgramEvent::AccountInitialized {
+        account: *program_account.key,
+        authority: *authority.key,
+    }
+    .emit();
+    Ok(())
+}
+/// Initialize the programs account, which is the first in accounts, in the
+/// zero-copy layout, recording the signing wallet, which is the second, as
+/// its authority
+fn initialize_zero_copy_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Initialize zero-copy account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    if !authority.is_signer {
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    let mut account_data = program_account.data.borrow_mut();
+    if ProgramAccountState::unpack_unchecked(&mut account_data)?.is_initialized() {
+        return Err(SampleError::AlreadyInitializedState.into());
+    }
+    ProgramAccountState::initialize_zero_copy(&mut account_data, authority.key)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_account_initialized()
+    })?;
+    ProgramEvent::AccountInitialized {
+        account: *program_account.key,
+        authority: *authority.key,
+    }
+    .emit();
+    Ok(())
+}
+/// Create and initialize the programs account at the address derived from
+/// the wallet, which is the second in accounts, and the seed. The wallet
+/// funds the account and becomes its authority
+fn initialize_derived_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seed: String,
+) -> ProgramResult {
+    msg!("Initialize derived account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let wallet = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    if !wallet.is_signer {
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    if system_program_account.key != &system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if seed.len() > MAX_SEED_LEN {
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+    // Confirm the account is the one derived from wallet and seed
+    let (derived_address, bump) = find_derived_account_address(program_id, wallet.key, &seed);
+    if program_account.key != &derived_address {
+        msg!(
+            "Fail: The account is {} and it should be {}.",
+            program_account.key,
+            derived_address
+        );
+        return Err(SampleError::InvalidDerivedAccount.into());
+    }
+    if program_account.owner == program_id {
+        return Err(SampleError::AlreadyInitializedState.into());
+    }
+    // Create the account, signing for the derived address
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            wallet.key,
+            program_account.key,
+            rent.minimum_balance(ACCOUNT_STATE_SPACE),
+            ACCOUNT_STATE_SPACE as u64,
+            program_id,
+        ),
+        &[
+            wallet.clone(),
+            program_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[wallet.key.as_ref(), seed.as_bytes(), &[bump]]],
+    )?;
+    // Initialize the state with the wallet as authority
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack_unchecked(&mut account_data)?;
+    account_state.set_initialized();
+    account_state.set_authority(wallet.key);
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_account_initialized()
+    })?;
+    ProgramEvent::AccountInitialized {
+        account: *program_account.key,
+        authority: *wallet.key,
+    }
+    .emit();
     Ok(())
 }
 /// Mint a key/pair to the programs account, which is the first in accounts
-fn mint_keypair_to_account(accounts: &[AccountInfo], key: String, value: String) -> ProgramResult {
+fn mint_keypair_to_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+) -> ProgramResult {
     msg!("Mint to account");
     let account_info_iter = &mut accounts.iter();
     let program_account = next_account_info(account_info_iter)?;
     let mut account_data = program_account.data.borrow_mut();
     // Unpacking an uninitialized account state will fail
-    let mut account_state = ProgramAccountState::unpack(&account_data)?;
-    account_state.add(key, value)?;
-    ProgramAccountState::pack(account_state, &mut account_data)?;
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.add(limits, key.clone(), value.clone())?;
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| registry.add_keys_minted(1))?;
+    ProgramEvent::Minted {
+        account: *program_account.key,
+        key,
+        value,
+        expiry: None,
+    }
+    .emit();
+    Ok(())
+}
+/// Mint a key/pair that expires to the programs account, which is the first
+/// in accounts
+fn mint_keypair_to_account_with_expiry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+    expiry: Expiry,
+) -> ProgramResult {
+    msg!("Mint to account with expiry");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.add_with_expiry(limits, key.clone(), value.clone(), expiry)?;
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| registry.add_keys_minted(1))?;
+    ProgramEvent::Minted {
+        account: *program_account.key,
+        key,
+        value,
+        expiry: Some(expiry),
+    }
+    .emit();
+    Ok(())
+}
+/// Remove the expired key/value pairs from the programs account, which is
+/// the first in accounts, counting them as burned in the registry, which is
+/// second. Anyone may purge expired key/value pairs
+fn purge_expired_from_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Purge expired from account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    let purged = account_state.purge_expired(&Clock::get()?)?;
+    msg!("Purged {} expired keys", purged);
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_keys_burned(purged as u64)
+    })?;
+    ProgramEvent::ExpiredPurged {
+        account: *program_account.key,
+        count: purged as u64,
+    }
+    .emit();
     Ok(())
 }
 /// Mint a key/value pair extracting a service fee for the effort
 fn mint_keypair_to_account_with_fee(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
     accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
     key: String,
-    value: String,
+    value: Value,
 ) -> ProgramResult {
     // Charge for service
     let account_info_iter = &mut accounts.iter();
     let program_account = next_account_info(account_info_iter)?;
     let service_account = next_account_info(account_info_iter)?;
+    let config_state = load_config_state(
+        program_id,
+        config_account.ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    let fee = config_state.fees().minting;
     charge_service_fee(
+        program_id,
+        accounts,
         program_account,
         service_account,
-        SampleServiceFees::Minting as u64,
+        &config_state,
+        fee,
     )?;
     // Invoke the actual mint
-    mint_keypair_to_account(accounts, key, value)?;
+    mint_keypair_to_account(program_id, accounts, limits, key, value)?;
     Ok(())
 }
 /// Transfer a key/pair from one program account to another
-/// "from" account is first and "to" account is second  in accounts
-fn transfer_keypair_to_account(accounts: &[AccountInfo], key: String) -> ProgramResult {
+/// "from" account is first and "to" account is second  in accounts. The
+/// keys delegate, if any, may sign in place of the authority and the
+/// transfer clears the delegation
+fn transfer_keypair_to_account(
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+) -> ProgramResult {
     msg!("Transfer from account");
     let account_info_iter = &mut accounts.iter();
     // Transfer from this account
     let from_program_account = next_account_info(account_info_iter)?;
+    let to_program_account = next_account_info(account_info_iter)?;
+    if from_program_account.key == to_program_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
     let mut from_account_data = from_program_account.data.borrow_mut();
-    let mut from_account_state = ProgramAccountState::unpack(&from_account_data)?;
+    let mut from_account_state = ProgramAccountState::unpack(&mut from_account_data)?;
+    check_authority_or_delegate(&from_account_state, &key, accounts)?;
     // To this account
-    let to_program_account = next_account_info(account_info_iter)?;
     let mut to_account_data = to_program_account.data.borrow_mut();
-    let mut to_account_state = ProgramAccountState::unpack(&to_account_data)?;
-    // Transfer the goods
-    match from_account_state.remove(&key) {
-        Ok(value) => {
-            to_account_state.add(key, value)?;
-            ProgramAccountState::pack(from_account_state, &mut from_account_data)?;
-            ProgramAccountState::pack(to_account_state, &mut to_account_data)?;
-            Ok(())
-        }
-        Err(e) => Err(e.into()),
+    let mut to_account_state = ProgramAccountState::unpack(&mut to_account_data)?;
+    // Transfer the goods, unless expired
+    let clock = Clock::get()?;
+    let value = from_account_state.remove_unexpired(&key, &clock)?;
+    to_account_state.add(limits, key.clone(), value)?;
+    ProgramAccountState::pack(from_account_state)?;
+    ProgramAccountState::pack(to_account_state)?;
+    ProgramEvent::Transferred {
+        from: *from_program_account.key,
+        to: *to_program_account.key,
+        key,
     }
+    .emit();
+    Ok(())
 }
 /// Transfer key/value pair extracting a service fee for the effort
-fn transfer_keypair_to_account_with_fee(accounts: &[AccountInfo], key: String) -> ProgramResult {
+fn transfer_keypair_to_account_with_fee(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let from_account = next_account_info(account_info_iter)?;
     let to_account = next_account_info(account_info_iter)?;
+    if from_account.key == to_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
     let service_account = next_account_info(account_info_iter)?;
+    let config_state = load_config_state(
+        program_id,
+        config_account.ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    let fees = config_state.fees();
 
     // Cost to "from account"
     charge_service_fee(
+        program_id,
+        accounts,
         from_account,
         service_account,
-        SampleServiceFees::Transfering as u64,
+        &config_state,
+        fees.transfering,
     )?;
     // Cost to "to account"
     charge_service_fee(
+        program_id,
+        accounts,
         to_account,
         service_account,
-        SampleServiceFees::Minting as u64,
+        &config_state,
+        fees.minting,
     )?;
     // Invoke the actual transfer
-    transfer_keypair_to_account(accounts, key)?;
+    transfer_keypair_to_account(accounts, limits, key)?;
     Ok(())
 }
 /// Burn a key/pair from the programs account, which is the first in accounts
-fn burn_keypair_from_account(accounts: &[AccountInfo], key: String) -> ProgramResult {
+fn burn_keypair_from_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: String,
+) -> ProgramResult {
     msg!("Burn from account");
     let account_info_iter = &mut accounts.iter();
     let program_account = next_account_info(account_info_iter)?;
     let mut account_data = program_account.data.borrow_mut();
-    let mut account_state = ProgramAccountState::unpack_unchecked(&account_data)?;
-    match account_state.remove(&key) {
-        Ok(_) => {
-            ProgramAccountState::pack(account_state, &mut account_data)?;
-            Ok(())
-        }
-        Err(e) => Err(e.into()),
+    let mut account_state = ProgramAccountState::unpack_unchecked(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    let clock = Clock::get()?;
+    account_state.remove_unexpired(&key, &clock)?;
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| registry.add_keys_burned(1))?;
+    ProgramEvent::Burned {
+        account: *program_account.key,
+        key,
     }
+    .emit();
+    Ok(())
 }
 /// Burn a key/pair extracting a service fee for the effort
-fn burn_keypair_from_account_with_fee(accounts: &[AccountInfo], key: String) -> ProgramResult {
+fn burn_keypair_from_account_with_fee(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
+    accounts: &[AccountInfo],
+    key: String,
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let program_account = next_account_info(account_info_iter)?;
     let service_account = next_account_info(account_info_iter)?;
+    let config_state = load_config_state(
+        program_id,
+        config_account.ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
     // Charge for service
+    let fee = config_state.fees().burning;
     charge_service_fee(
+        program_id,
+        accounts,
         program_account,
         service_account,
-        SampleServiceFees::Burning as u64,
+        &config_state,
+        fee,
     )?;
     // Invoke the actual burn
-    burn_keypair_from_account(accounts, key)?;
+    burn_keypair_from_account(program_id, accounts, key)?;
+    Ok(())
+}
+/// Update the value of an existing key in the programs account, which is
+/// the first in accounts
+fn update_value_in_account(
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+) -> ProgramResult {
+    msg!("Update value in account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.update(limits, key.clone(), value.clone(), &Clock::get()?)?;
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::ValueUpdated {
+        account: *program_account.key,
+        key,
+        value,
+    }
+    .emit();
+    Ok(())
+}
+/// Update a key/value pair extracting a service fee for the effort
+fn update_value_in_account_with_fee(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+) -> ProgramResult {
+    // Charge for service
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let service_account = next_account_info(account_info_iter)?;
+    let config_state = load_config_state(
+        program_id,
+        config_account.ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    let fee = config_state.fees().updating;
+    charge_service_fee(
+        program_id,
+        accounts,
+        program_account,
+        service_account,
+        &config_state,
+        fee,
+    )?;
+    // Invoke the actual update
+    update_value_in_account(accounts, limits, key, value)?;
+    Ok(())
+}
+/// Update the value of a key in the programs account, which is the first
+/// in accounts, adding the key if it does not exist and counting it as
+/// minted in the registry. An expired key is counted as burned and minted
+/// again
+fn upsert_value_in_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+) -> ProgramResult {
+    msg!("Upsert value in account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    let clock = Clock::get()?;
+    let expired = account_state.is_expired(&key, &clock);
+    let inserted = account_state.upsert(limits, key.clone(), value.clone(), &clock)?;
+    ProgramAccountState::pack(account_state)?;
+    if inserted {
+        update_registry(program_id, accounts, |registry| {
+            if expired {
+                registry.add_keys_burned(1);
+            }
+            registry.add_keys_minted(1)
+        })?;
+    }
+    ProgramEvent::ValueUpdated {
+        account: *program_account.key,
+        key,
+        value,
+    }
+    .emit();
+    Ok(())
+}
+/// Upsert a key/value pair extracting a service fee for the effort
+fn upsert_value_in_account_with_fee(
+    program_id: &Pubkey,
+    config_account: Option<&AccountInfo>,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key: String,
+    value: Value,
+) -> ProgramResult {
+    // Charge for service
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let service_account = next_account_info(account_info_iter)?;
+    let config_state = load_config_state(
+        program_id,
+        config_account.ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    let fee = config_state.fees().updating;
+    charge_service_fee(
+        program_id,
+        accounts,
+        program_account,
+        service_account,
+        &config_state,
+        fee,
+    )?;
+    // Invoke the actual upsert
+    upsert_value_in_account(program_id, accounts, limits, key, value)?;
+    Ok(())
+}
+/// Resize the programs account, which is the first in accounts, keeping it
+/// rent exempt. Growing is funded by the funder, which is third, and
+/// shrinking refunds the funder the excess lamports. The funder signs for
+/// either, so the authority, which follows, may be a multisig
+fn resize_account(accounts: &[AccountInfo], new_size: u64) -> ProgramResult {
+    msg!("Resize account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let funder = next_account_info(account_info_iter)?;
+    if system_program_account.key != &system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // The resized account must still hold the current state
+    let packed_len = {
+        let mut account_data = program_account.data.borrow_mut();
+        let account_state = ProgramAccountState::unpack(&mut account_data)?;
+        check_authority(&account_state, accounts)?;
+        account_state.packed_len()
+    };
+    let new_size = usize::try_from(new_size).map_err(|_| SampleError::InvalidAccountResize)?;
+    if new_size < packed_len {
+        msg!(
+            "Fail: The size {} is smaller than the {} needed for the state.",
+            new_size,
+            packed_len
+        );
+        return Err(SampleError::InvalidAccountResize.into());
+    }
+    balance_rent_exemption(program_account, system_program_account, funder, new_size)?;
+    // The zero-copy layout keeps its entries at the end of the data area
+    let current_size = program_account.data_len();
+    if new_size < current_size {
+        ProgramAccountState::relocate(
+            &mut program_account.data.borrow_mut(),
+            current_size,
+            new_size,
+        )?;
+    }
+    program_account.realloc(new_size, false)?;
+    if new_size > current_size {
+        ProgramAccountState::relocate(
+            &mut program_account.data.borrow_mut(),
+            current_size,
+            new_size,
+        )?;
+    }
+    ProgramEvent::AccountResized {
+        account: *program_account.key,
+        size: new_size as u64,
+    }
+    .emit();
+    Ok(())
+}
+
+/// Balances the lamports of the programs account to keep it rent exempt at
+/// the new size, funding the shortfall from the funder or refunding the
+/// excess to it. The funder must sign, so that the refund goes to a wallet
+/// the signers chose
+fn balance_rent_exemption<'a>(
+    program_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    funder: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    if !funder.is_signer {
+        msg!("Fail: The funder {} did not sign.", funder.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let required_lamports = Rent::get()?.minimum_balance(new_size);
+    let current_lamports = program_account.lamports();
+    if required_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(
+                funder.key,
+                program_account.key,
+                required_lamports - current_lamports,
+            ),
+            &[
+                funder.clone(),
+                program_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+    } else if current_lamports > required_lamports {
+        let funder_lamports = funder
+            .lamports()
+            .checked_add(current_lamports - required_lamports)
+            .ok_or(SampleError::ArithmeticOverflow)?;
+        **program_account.try_borrow_mut_lamports()? = required_lamports;
+        **funder.try_borrow_mut_lamports()? = funder_lamports;
+    }
+    Ok(())
+}
+
+/// Migrate the programs account, which is the first in accounts, from an
+/// earlier layout to the current layout, growing the account if the current
+/// layout needs more room, funded by the funder, which is third. The
+/// authority follows the funder, so may be a multisig. The `V0` layout has
+/// no authority so the account itself must sign and the signing wallet,
+/// which is last, becomes its authority
+fn migrate_account(accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Migrate account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let funder = next_account_info(account_info_iter)?;
+    let authority = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if system_program_account.key != &system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (version, packed_len) = {
+        let mut account_data = program_account.data.borrow_mut();
+        let (version, account_state) = ProgramAccountState::unpack_versioned(&mut account_data)?;
+        if !version.is_legacy() {
+            msg!("Account is already at layout version {}", version);
+            return Ok(());
+        }
+        if version == LayoutVersion::V0 {
+            if !program_account.is_signer {
+                msg!("Fail: The account {} did not sign.", program_account.key);
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !authority.is_signer {
+                return Err(SampleError::MissingAuthoritySignature.into());
+            }
+        } else {
+            check_authority(&account_state, accounts)?;
+        }
+        (version, account_state.packed_len())
+    };
+    if packed_len > program_account.data_len() {
+        balance_rent_exemption(program_account, system_program_account, funder, packed_len)?;
+        program_account.realloc(packed_len, false)?;
+    }
+    msg!(
+        "Migrated account from layout version {} to {}",
+        version,
+        LayoutVersion::CURRENT
+    );
+    // The state borrows the data area, so is unpacked again once reallocated
+    let mut account_data = program_account.data.borrow_mut();
+    let (_, mut account_state) = ProgramAccountState::unpack_versioned(&mut account_data)?;
+    if version == LayoutVersion::V0 {
+        account_state.set_authority(authority.key);
+    }
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::AccountMigrated {
+        account: *program_account.key,
+        from_version: version as u8,
+        to_version: LayoutVersion::CURRENT as u8,
+    }
+    .emit();
+    Ok(())
+}
+/// Close the programs account, which is the first in accounts, zeroing its
+/// data and moving all of its lamports to the destination, which is the
+/// second. The keys it held are counted as burned in the registry
+fn close_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    require_empty: bool,
+) -> ProgramResult {
+    msg!("Close account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    if program_account.key == destination_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let burned = {
+        let mut account_data = program_account.data.borrow_mut();
+        let account_state = ProgramAccountState::unpack(&mut account_data)?;
+        check_authority(&account_state, accounts)?;
+        if require_empty && !account_state.is_empty() {
+            return Err(SampleError::AccountNotEmpty.into());
+        }
+        // Closing would release the frozen account or its locked keys
+        if account_state.is_frozen() {
+            return Err(SampleError::AccountFrozen.into());
+        }
+        if account_state.has_locked_keys() {
+            return Err(SampleError::KeyLocked.into());
+        }
+        account_state.len()
+    };
+    // Reclaim the rent and clear the state
+    let account_lamports = program_account.lamports();
+    let destination_lamports = destination_account
+        .lamports()
+        .checked_add(account_lamports)
+        .ok_or(SampleError::ArithmeticOverflow)?;
+    **program_account.try_borrow_mut_lamports()? = 0;
+    **destination_account.try_borrow_mut_lamports()? = destination_lamports;
+    program_account.data.borrow_mut().fill(0);
+    update_registry(program_id, accounts, |registry| {
+        registry.add_keys_burned(burned as u64)
+    })?;
+    ProgramEvent::AccountClosed {
+        account: *program_account.key,
+        destination: *destination_account.key,
+        lamports: account_lamports,
+    }
+    .emit();
+    Ok(())
+}
+/// Mint many key/value pairs to the programs account, which is the first in
+/// accounts. Fails without minting any if one of the keys already exists
+fn mint_many_to_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key_values: Vec<(String, Value)>,
+) -> ProgramResult {
+    msg!("Mint many to account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    for (key, value) in &key_values {
+        account_state.add(limits, key.clone(), value.clone())?;
+    }
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_keys_minted(key_values.len() as u64)
+    })?;
+    for (key, value) in key_values {
+        ProgramEvent::Minted {
+            account: *program_account.key,
+            key,
+            value,
+            expiry: None,
+        }
+        .emit();
+    }
+    Ok(())
+}
+/// Burn many key/value pairs from the programs account, which is the first in
+/// accounts. Fails without burning any if one of the keys does not exist
+fn burn_many_from_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    keys: Vec<String>,
+) -> ProgramResult {
+    msg!("Burn many from account");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    let clock = Clock::get()?;
+    for key in &keys {
+        account_state.remove_unexpired(key, &clock)?;
+    }
+    ProgramAccountState::pack(account_state)?;
+    update_registry(program_id, accounts, |registry| {
+        registry.add_keys_burned(keys.len() as u64)
+    })?;
+    for key in keys {
+        ProgramEvent::Burned {
+            account: *program_account.key,
+            key,
+        }
+        .emit();
+    }
+    Ok(())
+}
+/// Transfer many key/value pairs from one program account to another
+/// "from" account is first and "to" account is second in accounts. Fails
+/// without transferring any if one of the keys can not be transferred
+fn transfer_many_to_account(
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    keys: Vec<String>,
+) -> ProgramResult {
+    msg!("Transfer many from account");
+    let account_info_iter = &mut accounts.iter();
+    // Transfer from this account
+    let from_program_account = next_account_info(account_info_iter)?;
+    let to_program_account = next_account_info(account_info_iter)?;
+    if from_program_account.key == to_program_account.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut from_account_data = from_program_account.data.borrow_mut();
+    let mut from_account_state = ProgramAccountState::unpack(&mut from_account_data)?;
+    check_authority(&from_account_state, accounts)?;
+    // To this account
+    let mut to_account_data = to_program_account.data.borrow_mut();
+    let mut to_account_state = ProgramAccountState::unpack(&mut to_account_data)?;
+    // Transfer the goods
+    let clock = Clock::get()?;
+    for key in &keys {
+        let value = from_account_state.remove_unexpired(key, &clock)?;
+        to_account_state.add(limits, key.clone(), value)?;
+    }
+    ProgramAccountState::pack(from_account_state)?;
+    ProgramAccountState::pack(to_account_state)?;
+    for key in keys {
+        ProgramEvent::Transferred {
+            from: *from_program_account.key,
+            to: *to_program_account.key,
+            key,
+        }
+        .emit();
+    }
+    Ok(())
+}
+/// Swap key_a from account A, which is first in accounts, for key_b from
+/// account B, which is second. The authority of A follows, then the
+/// authority of B, each a wallet or a multisig followed by the given count of
+/// its signers, and both must sign. Fails without moving either key on any
+/// conflict
+fn swap_keys(
+    accounts: &[AccountInfo],
+    limits: &KeyValueLimits,
+    key_a: String,
+    key_b: String,
+    signer_count_a: u8,
+    signer_count_b: u8,
+) -> ProgramResult {
+    msg!("Swap keys");
+    let account_info_iter = &mut accounts.iter();
+    let account_a = next_account_info(account_info_iter)?;
+    let account_b = next_account_info(account_info_iter)?;
+    if account_a.key == account_b.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut account_a_data = account_a.data.borrow_mut();
+    let mut account_a_state = ProgramAccountState::unpack(&mut account_a_data)?;
+    let mut account_b_data = account_b.data.borrow_mut();
+    let mut account_b_state = ProgramAccountState::unpack(&mut account_b_data)?;
+    // Each authority is followed by the count of its multisig signers given
+    let authority_a_len = 1 + signer_count_a as usize;
+    let authority_b_len = 1 + signer_count_b as usize;
+    let (authority_a_accounts, authority_b_accounts) = accounts
+        .get(2..2 + authority_a_len + authority_b_len)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?
+        .split_at(authority_a_len);
+    check_authority_among(account_a.owner, &account_a_state, authority_a_accounts)?;
+    check_authority_among(account_b.owner, &account_b_state, authority_b_accounts)?;
+    // Exchange the goods
+    let clock = Clock::get()?;
+    let value_a = account_a_state.remove_unexpired(&key_a, &clock)?;
+    let value_b = account_b_state.remove_unexpired(&key_b, &clock)?;
+    account_b_state.add(limits, key_a.clone(), value_a)?;
+    account_a_state.add(limits, key_b.clone(), value_b)?;
+    ProgramAccountState::pack(account_a_state)?;
+    ProgramAccountState::pack(account_b_state)?;
+    ProgramEvent::KeysSwapped {
+        account_a: *account_a.key,
+        key_a,
+        account_b: *account_b.key,
+        key_b,
+    }
+    .emit();
+    Ok(())
+}
+/// Approve a delegate to transfer a key in the programs account, which is
+/// the first in accounts
+fn approve_delegate(accounts: &[AccountInfo], key: String, delegate: Pubkey) -> ProgramResult {
+    msg!("Approve delegate");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.approve(key.clone(), &delegate)?;
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::DelegateApproved {
+        account: *program_account.key,
+        key,
+        delegate,
+    }
+    .emit();
+    Ok(())
+}
+/// Revoke the delegate of a key in the programs account, which is the first
+/// in accounts
+fn revoke_delegate(accounts: &[AccountInfo], key: String) -> ProgramResult {
+    msg!("Revoke delegate");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.revoke(&key)?;
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::DelegateRevoked {
+        account: *program_account.key,
+        key,
+    }
+    .emit();
+    Ok(())
+}
+/// Freeze or thaw the programs account, which is the first in accounts. The
+/// freeze authority, which is the last, must sign. A frozen account fails
+/// every change to its key/value pairs with `AccountFrozen`
+fn set_account_frozen(accounts: &[AccountInfo], frozen: bool) -> ProgramResult {
+    match frozen {
+        true => msg!("Freeze account"),
+        false => msg!("Thaw account"),
+    }
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_freeze_authority(&account_state, accounts)?;
+    account_state.set_frozen(frozen);
+    ProgramAccountState::pack(account_state)?;
+    let account = *program_account.key;
+    match frozen {
+        true => ProgramEvent::AccountFrozen { account },
+        false => ProgramEvent::AccountThawed { account },
+    }
+    .emit();
+    Ok(())
+}
+/// Lock or unlock a key in the programs account, which is the first in
+/// accounts. The freeze authority, which is the last, must sign. A locked
+/// key fails transfers, swaps, burns and updates with `KeyLocked`
+fn set_key_locked(accounts: &[AccountInfo], key: String, locked: bool) -> ProgramResult {
+    match locked {
+        true => msg!("Lock key"),
+        false => msg!("Unlock key"),
+    }
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_freeze_authority(&account_state, accounts)?;
+    account_state.set_locked(&key, locked)?;
+    ProgramAccountState::pack(account_state)?;
+    let account = *program_account.key;
+    match locked {
+        true => ProgramEvent::KeyLocked { account, key },
+        false => ProgramEvent::KeyUnlocked { account, key },
+    }
+    .emit();
+    Ok(())
+}
+/// Hand the freezing and locking of the programs account, which is the first
+/// in accounts, to a new freeze authority. The current freeze authority,
+/// which is the last, must sign
+fn set_freeze_authority(accounts: &[AccountInfo], freeze_authority: Pubkey) -> ProgramResult {
+    msg!("Set freeze authority");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_freeze_authority(&account_state, accounts)?;
+    account_state.set_freeze_authority(&freeze_authority);
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::FreezeAuthoritySet {
+        account: *program_account.key,
+        freeze_authority,
+    }
+    .emit();
+    Ok(())
+}
+/// Initialize the multisig account, which is the first in accounts, with
+/// the signers, which follow it, each listed once, and the number of them
+/// that must sign. The account must be rent exempt and of the multisig size, created in the same
+/// transaction
+fn initialize_multisig(accounts: &[AccountInfo], m: u8) -> ProgramResult {
+    msg!("Initialize multisig");
+    let account_info_iter = &mut accounts.iter();
+    let multisig_account = next_account_info(account_info_iter)?;
+    let signers: Vec<Pubkey> = account_info_iter.map(|signer| *signer.key).collect();
+    if signers.is_empty() || signers.len() > MAX_SIGNERS || m == 0 || m as usize > signers.len() {
+        msg!(
+            "Fail: {} of {} signers is not a valid multisig.",
+            m,
+            signers.len()
+        );
+        return Err(SampleError::InvalidMultisig.into());
+    }
+    // A signer listed twice would count twice towards m in the listing but
+    // only once when signing, leaving the multisig unable to reach m
+    if signers
+        .iter()
+        .enumerate()
+        .any(|(i, signer)| signers[..i].contains(signer))
+    {
+        msg!("Fail: The multisig lists a signer more than once.");
+        return Err(SampleError::InvalidMultisig.into());
+    }
+    if !Rent::get()?.is_exempt(multisig_account.lamports(), multisig_account.data_len()) {
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+    let mut multisig_data = multisig_account.data.borrow_mut();
+    let mut multisig_state = ProgramMultisigState::unpack_unchecked(&multisig_data)?;
+    if multisig_state.is_initialized() {
+        return Err(SampleError::AlreadyInitializedState.into());
+    }
+    multisig_state.set_initialized();
+    multisig_state.set_m(m);
+    multisig_state.set_signers(&signers);
+    ProgramMultisigState::pack(multisig_state, &mut multisig_data)?;
+    ProgramEvent::MultisigInitialized {
+        multisig: *multisig_account.key,
+        m,
+        signers,
+    }
+    .emit();
+    Ok(())
+}
+/// Hand the programs account, which is the first in accounts, to a new
+/// authority, a wallet or a multisig. The current authority must sign
+fn set_account_authority(accounts: &[AccountInfo], authority: Pubkey) -> ProgramResult {
+    msg!("Set authority");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let mut account_data = program_account.data.borrow_mut();
+    let mut account_state = ProgramAccountState::unpack(&mut account_data)?;
+    check_authority(&account_state, accounts)?;
+    account_state.set_authority(&authority);
+    ProgramAccountState::pack(account_state)?;
+    ProgramEvent::AuthoritySet {
+        account: *program_account.key,
+        authority,
+    }
+    .emit();
+    Ok(())
+}
+/// Return the value of the key in the programs account, which is the only
+/// account, as a borsh encoded `Option<Value>` through the return data. An
+/// expired key has no value. The account is left untouched
+fn get_value(accounts: &[AccountInfo], key: String) -> ProgramResult {
+    msg!("Get value");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let account_state = unpack_account_data(&program_account.data.borrow())?;
+    let clock = Clock::get()?;
+    let value = account_state.get_unexpired(&key, clock.slot, clock.unix_timestamp);
+    set_return_data(&value.cloned().try_to_vec().unwrap());
+    Ok(())
+}
+/// Return whether the programs account, which is the only account, holds the
+/// key as a borsh encoded `bool` through the return data. An expired key is
+/// not held. The account is left untouched
+fn contains_key(accounts: &[AccountInfo], key: String) -> ProgramResult {
+    msg!("Contains key");
+    let account_info_iter = &mut accounts.iter();
+    let program_account = next_account_info(account_info_iter)?;
+    let account_state = unpack_account_data(&program_account.data.borrow())?;
+    let clock = Clock::get()?;
+    let contains_key = account_state
+        .get_unexpired(&key, clock.slot, clock.unix_timestamp)
+        .is_some();
+    set_return_data(&contains_key.try_to_vec().unwrap());
+    Ok(())
+}
+/// Create and initialize the program configuration account, which is the
+/// first in accounts, with the fee schedule and the treasury fees are credited
+/// to. The signing wallet, which is the second, funds the account and becomes
+/// the admin. It must be the program's upgrade authority, recorded in the
+/// program data account, which is the fourth
+fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fees: ServiceFees,
+    treasury: Pubkey,
+) -> ProgramResult {
+    msg!("Initialize config");
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let admin = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+    if !admin.is_signer {
+        return Err(SampleError::MissingAuthoritySignature.into());
+    }
+    if system_program_account.key != &system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_upgrade_authority(program_id, program_data_account, admin)?;
+    let (config_address, bump) = find_config_address(program_id);
+    if config_account.key != &config_address {
+        msg!(
+            "Fail: The account is {} and it should be {}.",
+            config_account.key,
+            config_address
+        );
+        return Err(SampleError::InvalidConfigAccount.into());
+    }
+    if config_account.owner == program_id {
+        return Err(SampleError::AlreadyInitializedState.into());
+    }
+    // Create the account, signing for the config address
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            config_account.key,
+            rent.minimum_balance(CONFIG_STATE_SPACE),
+            CONFIG_STATE_SPACE as u64,
+            program_id,
+        ),
+        &[
+            admin.clone(),
+            config_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[CONFIG_SEED, &[bump]]],
+    )?;
+    let mut config_data = config_account.data.borrow_mut();
+    let mut config_state = ProgramConfigState::unpack_unchecked(&config_data)?;
+    config_state.set_initialized();
+    config_state.set_admin(admin.key);
+    config_state.set_fees(fees);
+    config_state.set_treasury(&treasury);
+    ProgramConfigState::pack(config_state, &mut config_data)?;
+    ProgramEvent::ConfigInitialized {
+        admin: *admin.key,
+        treasury,
+        fees,
+    }
+    .emit();
+    Ok(())
+}
+/// Replace the fee schedule in the program configuration account, which is
+/// the first in accounts. The admin, which is the last, must sign
+fn set_fees(program_id: &Pubkey, accounts: &[AccountInfo], fees: ServiceFees) -> ProgramResult {
+    msg!("Set fees");
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let mut config_state = load_config_state(program_id, config_account)?;
+    check_admin(&config_state, accounts)?;
+    config_state.set_fees(fees);
+    ProgramConfigState::pack(config_state, &mut config_account.data.borrow_mut())?;
+    ProgramEvent::FeesSet { fees }.emit();
+    Ok(())
+}
+/// Replace the treasury in the program configuration account, which is the
+/// first in accounts. The admin, which is the last, must sign
+fn set_treasury(program_id: &Pubkey, accounts: &[AccountInfo], treasury: Pubkey) -> ProgramResult {
+    msg!("Set treasury");
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let mut config_state = load_config_state(program_id, config_account)?;
+    check_admin(&config_state, accounts)?;
+    config_state.set_treasury(&treasury);
+    ProgramConfigState::pack(config_state, &mut config_account.data.borrow_mut())?;
+    ProgramEvent::TreasurySet { treasury }.emit();
+    Ok(())
+}
+/// Replace the key/value limits in the program configuration account, which
+/// is the first in accounts. The admin, which is the last, must sign
+fn set_key_value_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: KeyValueLimits,
+) -> ProgramResult {
+    msg!("Set key/value limits");
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let mut config_state = load_config_state(program_id, config_account)?;
+    check_admin(&config_state, accounts)?;
+    if !limits.is_valid() {
+        msg!("Fail: The key/value limits {:?} are not valid.", limits);
+        return Err(SampleError::InvalidKeyValueLimits.into());
+    }
+    config_state.set_key_value_limits(limits);
+    ProgramConfigState::pack(config_state, &mut config_account.data.borrow_mut())?;
+    ProgramEvent::KeyValueLimitsSet { limits }.emit();
+    Ok(())
+}
+/// Pause, or unpause, the program in the program configuration account,
+/// which is the first in accounts. The admin, which is the last, must sign
+fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    match paused {
+        true => msg!("Pause"),
+        false => msg!("Unpause"),
+    }
+    let account_info_iter = &mut accounts.iter();
+    let config_account = next_account_info(account_info_iter)?;
+    let mut config_state = load_config_state(program_id, config_account)?;
+    check_admin(&config_state, accounts)?;
+    config_state.set_paused(paused);
+    let admin = *config_state.admin();
+    ProgramConfigState::pack(config_state, &mut config_account.data.borrow_mut())?;
+    match paused {
+        true => ProgramEvent::ProgramPaused { admin },
+        false => ProgramEvent::ProgramUnpaused { admin },
+    }
+    .emit();
+    Ok(())
+}
+/// Create and initialize the registry account, which is the first in
+/// accounts, at the address derived from the `registry` seed. The payer,
+/// which is the second, funds the account
+fn initialize_registry(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Initialize registry");
+    let account_info_iter = &mut accounts.iter();
+    let registry_account = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if system_program_account.key != &system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (registry_address, bump) = find_registry_address(program_id);
+    if registry_account.key != &registry_address {
+        msg!(
+            "Fail: The account is {} and it should be {}.",
+            registry_account.key,
+            registry_address
+        );
+        return Err(SampleError::InvalidRegistryAccount.into());
+    }
+    if registry_account.owner == program_id {
+        return Err(SampleError::AlreadyInitializedState.into());
+    }
+    // Create the account, signing for the registry address
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            registry_account.key,
+            rent.minimum_balance(REGISTRY_STATE_SPACE),
+            REGISTRY_STATE_SPACE as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            registry_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[REGISTRY_SEED, &[bump]]],
+    )?;
+    let mut registry_data = registry_account.data.borrow_mut();
+    let mut registry_state = ProgramRegistryState::unpack_unchecked(&registry_data)?;
+    registry_state.set_initialized();
+    ProgramRegistryState::pack(registry_state, &mut registry_data)?;
+    ProgramEvent::RegistryInitialized {
+        registry: *registry_account.key,
+    }
+    .emit();
     Ok(())
 }
 /// Main processing entry point dispatches to specific
@@ -193,28 +1721,119 @@ pub fn process(
     instruction_data: &[u8],
 ) -> ProgramResult {
     msg!("Received process request");
-    // Check the account for program relationship
-    if let Err(error) = check_account_ownership(program_id, accounts) {
-        return Err(error);
-    }
     // Unpack the inbound data, mapping instruction to appropriate structure
-    match ProgramInstruction::unpack(instruction_data)? {
-        ProgramInstruction::InitializeAccount => initialize_account(accounts),
+    let instruction = ProgramInstruction::unpack(instruction_data)?;
+    // Refuse mutations while paused, splitting off the config account
+    let (config_account, accounts) = check_not_paused(program_id, &instruction, accounts)?;
+    // Read the key/value limits that adding keys and updating values enforce
+    let limits = load_key_value_limits(program_id, config_account)?;
+    // Check the tracking accounts for program relationship
+    let tracking_accounts = tracking_account_count(&instruction).min(accounts.len());
+    check_account_ownership(program_id, &accounts[..tracking_accounts])?;
+    check_registry_account(program_id, &instruction, accounts)?;
+    match instruction {
+        ProgramInstruction::InitializeAccount => initialize_account(program_id, accounts),
         ProgramInstruction::MintToAccount(key, value) => {
-            mint_keypair_to_account(accounts, key, value)
+            mint_keypair_to_account(program_id, accounts, &limits, key, value)
         }
         ProgramInstruction::TransferBetweenAccounts(key) => {
-            transfer_keypair_to_account(accounts, key)
+            transfer_keypair_to_account(accounts, &limits, key)
         }
-        ProgramInstruction::BurnFromAccount(key) => burn_keypair_from_account(accounts, key),
-        ProgramInstruction::MintToAccountWithFee(key, value) => {
-            mint_keypair_to_account_with_fee(accounts, key, value)
+        ProgramInstruction::BurnFromAccount(key) => {
+            burn_keypair_from_account(program_id, accounts, key)
         }
+        ProgramInstruction::MintToAccountWithFee(key, value) => mint_keypair_to_account_with_fee(
+            program_id,
+            config_account,
+            accounts,
+            &limits,
+            key,
+            value,
+        ),
         ProgramInstruction::TransferBetweenAccountsWithFee(key) => {
-            transfer_keypair_to_account_with_fee(accounts, key)
+            transfer_keypair_to_account_with_fee(program_id, config_account, accounts, &limits, key)
         }
         ProgramInstruction::BurnFromAccountWithFee(key) => {
-            burn_keypair_from_account_with_fee(accounts, key)
+            burn_keypair_from_account_with_fee(program_id, config_account, accounts, key)
+        }
+        ProgramInstruction::InitializeDerivedAccount(seed) => {
+            initialize_derived_account(program_id, accounts, seed)
+        }
+        ProgramInstruction::UpdateValue(key, value) => {
+            update_value_in_account(accounts, &limits, key, value)
+        }
+        ProgramInstruction::UpsertValue(key, value) => {
+            upsert_value_in_account(program_id, accounts, &limits, key, value)
+        }
+        ProgramInstruction::UpdateValueWithFee(key, value) => update_value_in_account_with_fee(
+            program_id,
+            config_account,
+            accounts,
+            &limits,
+            key,
+            value,
+        ),
+        ProgramInstruction::UpsertValueWithFee(key, value) => upsert_value_in_account_with_fee(
+            program_id,
+            config_account,
+            accounts,
+            &limits,
+            key,
+            value,
+        ),
+        ProgramInstruction::Resize(new_size) => resize_account(accounts, new_size),
+        ProgramInstruction::CloseAccount(require_empty) => {
+            close_account(program_id, accounts, require_empty)
+        }
+        ProgramInstruction::MintMany(key_values) => {
+            mint_many_to_account(program_id, accounts, &limits, key_values)
+        }
+        ProgramInstruction::BurnMany(keys) => burn_many_from_account(program_id, accounts, keys),
+        ProgramInstruction::TransferMany(keys) => transfer_many_to_account(accounts, &limits, keys),
+        ProgramInstruction::InitializeConfig(fees, treasury) => {
+            initialize_config(program_id, accounts, fees, treasury)
+        }
+        ProgramInstruction::SetFees(fees) => set_fees(program_id, accounts, fees),
+        ProgramInstruction::SetTreasury(treasury) => set_treasury(program_id, accounts, treasury),
+        ProgramInstruction::Approve(key, delegate) => approve_delegate(accounts, key, delegate),
+        ProgramInstruction::Revoke(key) => revoke_delegate(accounts, key),
+        ProgramInstruction::SwapKeys {
+            key_a,
+            key_b,
+            signer_count_a,
+            signer_count_b,
+        } => swap_keys(
+            accounts,
+            &limits,
+            key_a,
+            key_b,
+            signer_count_a,
+            signer_count_b,
+        ),
+        ProgramInstruction::MintToAccountWithExpiry(key, value, expiry) => {
+            mint_keypair_to_account_with_expiry(program_id, accounts, &limits, key, value, expiry)
+        }
+        ProgramInstruction::PurgeExpired => purge_expired_from_account(program_id, accounts),
+        ProgramInstruction::MigrateAccount => migrate_account(accounts),
+        ProgramInstruction::InitializeZeroCopyAccount => {
+            initialize_zero_copy_account(program_id, accounts)
+        }
+        ProgramInstruction::FreezeAccount => set_account_frozen(accounts, true),
+        ProgramInstruction::ThawAccount => set_account_frozen(accounts, false),
+        ProgramInstruction::LockKey(key) => set_key_locked(accounts, key, true),
+        ProgramInstruction::UnlockKey(key) => set_key_locked(accounts, key, false),
+        ProgramInstruction::SetFreezeAuthority(freeze_authority) => {
+            set_freeze_authority(accounts, freeze_authority)
+        }
+        ProgramInstruction::InitializeMultisig(m) => initialize_multisig(accounts, m),
+        ProgramInstruction::SetAuthority(authority) => set_account_authority(accounts, authority),
+        ProgramInstruction::GetValue(key) => get_value(accounts, key),
+        ProgramInstruction::ContainsKey(key) => contains_key(accounts, key),
+        ProgramInstruction::InitializeRegistry => initialize_registry(program_id, accounts),
+        ProgramInstruction::Pause => set_paused(program_id, accounts, true),
+        ProgramInstruction::Unpause => set_paused(program_id, accounts, false),
+        ProgramInstruction::SetKeyValueLimits(limits) => {
+            set_key_value_limits(program_id, accounts, limits)
         }
     }
 }
@@ -0,0 +1,85 @@
+//! @brief registry_state manages the program wide registry account data
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+};
+
+/// Space allocated for the registry account, leaving room to grow
+pub const REGISTRY_STATE_SPACE: usize = 128;
+
+/// Program wide counters, kept in the singleton registry account, that the
+/// instructions passed the registry add to
+#[derive(BorshDeserialize, BorshSerialize, Debug, Default, PartialEq)]
+pub struct ProgramRegistryState {
+    is_initialized: bool,
+    accounts_initialized: u64,
+    keys_minted: u64,
+    keys_burned: u64,
+    fees_collected: u64,
+}
+
+impl ProgramRegistryState {
+    /// Marks the account as initialized
+    pub fn set_initialized(&mut self) {
+        self.is_initialized = true;
+    }
+    /// Counts a newly initialized program account
+    pub fn add_account_initialized(&mut self) {
+        self.accounts_initialized = self.accounts_initialized.saturating_add(1);
+    }
+    /// Returns how many program accounts have been initialized
+    pub fn accounts_initialized(&self) -> u64 {
+        self.accounts_initialized
+    }
+    /// Counts newly minted keys
+    pub fn add_keys_minted(&mut self, count: u64) {
+        self.keys_minted = self.keys_minted.saturating_add(count);
+    }
+    /// Returns how many keys have been minted
+    pub fn keys_minted(&self) -> u64 {
+        self.keys_minted
+    }
+    /// Counts burned keys
+    pub fn add_keys_burned(&mut self, count: u64) {
+        self.keys_burned = self.keys_burned.saturating_add(count);
+    }
+    /// Returns how many keys have been burned
+    pub fn keys_burned(&self) -> u64 {
+        self.keys_burned
+    }
+    /// Adds lamports collected as service fees
+    pub fn add_fees_collected(&mut self, lamports: u64) {
+        self.fees_collected = self.fees_collected.saturating_add(lamports);
+    }
+    /// Returns the lamports collected as service fees
+    pub fn fees_collected(&self) -> u64 {
+        self.fees_collected
+    }
+}
+
+impl Sealed for ProgramRegistryState {}
+
+impl IsInitialized for ProgramRegistryState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProgramRegistryState {
+    const LEN: usize = REGISTRY_STATE_SPACE;
+
+    /// Store 'state' of the registry to its data area
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let registry_data = self.try_to_vec().unwrap();
+        dst[..registry_data.len()].copy_from_slice(&registry_data);
+    }
+
+    /// Retrieve 'state' of the registry from its data area
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_unchecked::<ProgramRegistryState>(src)
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
@@ -1,60 +1,252 @@
 //! Transaction testing and debugging
 
-use sol_template_shared::{unpack_from_slice, ACCOUNT_STATE_SPACE};
-use solana_cli_template_program_bpf::{instruction::ProgramInstruction, processor::process};
-use solana_program::hash::Hash;
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_traits::FromPrimitive;
+use sol_template_shared::{
+    error::AccountStateError, layout_version_of, pack_into_slice, unpack_from_slice,
+    zero_copy::ZeroCopyAccountState, AccountStateData, Expiry, KeyValueLimits, LayoutVersion,
+    Value, ACCOUNT_STATE_HEADER, ACCOUNT_STATE_SPACE, BTREE_STORAGE, KEY_VALUE_LIMITS,
+};
+use solana_cli_template_program_bpf::{
+    config_state::{ProgramConfigState, ServiceFees},
+    cpi,
+    error::SampleError,
+    event::{ProgramEvent, EVENT_VERSION},
+    find_config_address, find_derived_account_address, find_registry_address,
+    instruction::{self, ProgramInstruction},
+    multisig_state::{ProgramMultisigState, MULTISIG_STATE_SPACE},
+    processor::process,
+    registry_state::ProgramRegistryState,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::Hash,
+    program::MAX_RETURN_DATA,
+    program_error::ProgramError,
+    program_pack::Pack,
+};
 use solana_program_test::*;
 use solana_sdk::{
-    account::Account,
-    instruction::{AccountMeta, Instruction},
+    account::{Account, AccountSharedData},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
 };
+use std::collections::BTreeMap;
 
 /// Sets up the Program test and initializes 'n' program_accounts
 async fn setup(program_id: &Pubkey, program_accounts: &[Pubkey]) -> (BanksClient, Keypair, Hash) {
+    let accounts: Vec<(Pubkey, Vec<u8>)> = program_accounts
+        .iter()
+        .map(|account| (*account, vec![0_u8; ACCOUNT_STATE_SPACE]))
+        .collect();
+    setup_with_data(program_id, &accounts).await
+}
+
+/// Sets up the Program test with program_accounts holding the given data
+async fn setup_with_data(
+    program_id: &Pubkey,
+    program_accounts: &[(Pubkey, Vec<u8>)],
+) -> (BanksClient, Keypair, Hash) {
     let mut program_test = ProgramTest::new(
         "solana_cli_template_program_bpf", // Run the BPF version with `cargo test-bpf`
         *program_id,
         processor!(process), // Run the native version with `cargo test`
     );
-    for account in program_accounts {
+    for (account, data) in program_accounts {
         program_test.add_account(
             *account,
             Account {
                 lamports: 5,
-                data: vec![0_u8; ACCOUNT_STATE_SPACE],
+                data: data.clone(),
                 owner: *program_id,
                 ..Account::default()
             },
         );
     }
-    program_test.start().await
+    start_with_upgrade_authority(program_test, program_id).await
 }
 
-/// Submit transaction with relevant instruction data
-#[allow(clippy::ptr_arg)]
-async fn submit_txn(
+/// Starts the Program test with the payer as the program's upgrade authority
+async fn start_with_upgrade_authority(
+    program_test: ProgramTest,
     program_id: &Pubkey,
-    instruction_data: ProgramInstruction,
-    accounts: &[AccountMeta],
-    payer: &dyn Signer,
+) -> (BanksClient, Keypair, Hash) {
+    // The payer deploys the program, as its upgrade authority
+    let mut context = program_test.start_with_context().await;
+    let program_data = AccountSharedData::new_data(
+        1,
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(context.payer.pubkey()),
+        },
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    context.set_account(
+        &bpf_loader_upgradeable::get_program_data_address(program_id),
+        &program_data,
+    );
+    (context.banks_client, context.payer, context.last_blockhash)
+}
+
+/// Replaces an account of a built instruction with the given meta, the
+/// deliberately wrong accounts of the failing cases
+fn with_account_replaced(
+    instruction: Instruction,
+    pubkey: &Pubkey,
+    meta: AccountMeta,
+) -> Instruction {
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|account| match account.pubkey == *pubkey {
+            true => meta.clone(),
+            false => account.clone(),
+        })
+        .collect();
+    Instruction {
+        accounts,
+        ..instruction
+    }
+}
+
+/// Returns the program's error of a failed transaction
+fn sample_error_of(result: Result<(), BanksClientError>) -> Option<SampleError> {
+    match result.err()?.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            SampleError::from_u32(code)
+        }
+        _ => None,
+    }
+}
+
+/// Funds the accounts to be rent exempt with `extra_lamports` to spare
+async fn fund_rent_exempt(
+    accounts: &[Pubkey],
+    extra_lamports: u64,
+    payer: &Keypair,
     recent_blockhash: Hash,
     banks_client: &mut BanksClient,
 ) -> Result<(), BanksClientError> {
-    let mut transaction = Transaction::new_with_payer(
-        &[Instruction::new_with_borsh(
-            *program_id,
-            &instruction_data,
-            accounts.to_vec(),
-        )],
-        Some(&payer.pubkey()),
-    );
+    let rent = banks_client.get_rent().await?;
+    let instructions: Vec<Instruction> = accounts
+        .iter()
+        .map(|account| {
+            system_instruction::transfer(
+                &payer.pubkey(),
+                account,
+                rent.minimum_balance(ACCOUNT_STATE_SPACE) + extra_lamports,
+            )
+        })
+        .collect();
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
     transaction.sign(&[payer], recent_blockhash);
     banks_client.process_transaction(transaction).await
 }
 
+/// Initializes the accounts with the payer as authority and creates the
+/// program config with the payer as admin
+async fn initialize_with_config(
+    program_id: &Pubkey,
+    accounts: &[Pubkey],
+    fees: ServiceFees,
+    treasury: &Pubkey,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> Result<(), BanksClientError> {
+    for account in accounts {
+        submit_txn(
+            instruction::initialize_account(program_id, account, &payer.pubkey()),
+            payer,
+            recent_blockhash,
+            banks_client,
+        )
+        .await?;
+    }
+    submit_txn(
+        instruction::initialize_config(program_id, &payer.pubkey(), fees, treasury),
+        payer,
+        recent_blockhash,
+        banks_client,
+    )
+    .await
+}
+
+/// Submit transaction with the instruction
+async fn submit_txn(
+    instruction: Instruction,
+    payer: &dyn Signer,
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> Result<(), BanksClientError> {
+    submit_txn_with_signers(instruction, &[payer], recent_blockhash, banks_client).await
+}
+
+/// Submit transaction signed by multiple signers, the first being the payer
+async fn submit_txn_with_signers(
+    instruction: Instruction,
+    signers: &[&dyn Signer],
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> Result<(), BanksClientError> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&signers[0].pubkey()));
+    transaction.sign(&signers.to_vec(), recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+/// Simulates the transaction, signed by multiple signers the first being the
+/// payer, and returns the compute units it consumes. Units are only metered
+/// when the program runs as BPF, with `cargo test-sbf`
+async fn simulate_compute_units(
+    instruction: Instruction,
+    signers: &[&dyn Signer],
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> u64 {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&signers[0].pubkey()));
+    transaction.sign(&signers.to_vec(), recent_blockhash);
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(matches!(simulation.result, Some(Ok(()))));
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+/// Simulates the read only instruction and decodes the borsh encoded data
+/// the program returns. The runtime drops trailing zero bytes from the
+/// return data, so they are restored before decoding
+async fn simulate_return_data<T: BorshDeserialize>(
+    instruction: Instruction,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> T {
+    let program_id = instruction.program_id;
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[payer], recent_blockhash);
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(matches!(simulation.result, Some(Ok(()))));
+    let mut data = match simulation.simulation_details.unwrap().return_data {
+        Some(return_data) => {
+            assert_eq!(return_data.program_id, program_id);
+            return_data.data
+        }
+        None => Vec::new(),
+    };
+    data.resize(MAX_RETURN_DATA, 0);
+    T::deserialize(&mut data.as_slice()).unwrap()
+}
+
 #[tokio::test]
 /// Initialization test
 async fn test_initialize_pass() {
@@ -65,16 +257,14 @@ async fn test_initialize_pass() {
     let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
 
     // Verify account is not yet initialized
-    let (is_initialized, _btree_map) = match banks_client.get_account(account_pubkey).await.unwrap()
-    {
-        Some(account) => unpack_from_slice(&account.data).unwrap(),
-        None => panic!(),
-    };
+    let AccountStateData { is_initialized, .. } =
+        match banks_client.get_account(account_pubkey).await.unwrap() {
+            Some(account) => unpack_from_slice(&account.data).unwrap(),
+            None => panic!(),
+        };
     assert!(!is_initialized);
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::InitializeAccount,
-        &[AccountMeta::new(account_pubkey, false)],
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -93,9 +283,7 @@ async fn test_mint_pass() {
     let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
 
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::InitializeAccount,
-        &[AccountMeta::new(account_pubkey, false)],
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -105,12 +293,17 @@ async fn test_mint_pass() {
 
     // Do mint
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
 
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::MintToAccount(mint_key.clone(), mint_value.clone()),
-        &[AccountMeta::new(account_pubkey, false)],
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -118,8 +311,11 @@ async fn test_mint_pass() {
     .await;
     assert!(result.is_ok());
     // Check the data
-    let (is_initialized, btree_map) = match banks_client.get_account(account_pubkey).await.unwrap()
-    {
+    let AccountStateData {
+        is_initialized,
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(account_pubkey).await.unwrap() {
         Some(account) => unpack_from_slice(&account.data).unwrap(),
         None => panic!(),
     };
@@ -128,6 +324,61 @@ async fn test_mint_pass() {
     assert_eq!(btree_map.get(&mint_key).unwrap(), &mint_value);
 }
 
+#[tokio::test]
+/// Event logging test
+async fn test_mint_event_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
+
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Simulate a mint and verify it logs the mint event
+    let mint_key = String::from("test_key_1");
+    let mint_value = Value::from("value for test_key_1");
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(matches!(simulation.result, Some(Ok(()))));
+    let event = ProgramEvent::Minted {
+        account: account_pubkey,
+        key: mint_key,
+        value: mint_value,
+        expiry: None,
+    };
+    let event_log = format!(
+        "Program data: {}",
+        base64::encode(event.try_to_vec().unwrap())
+    );
+    assert!(simulation
+        .simulation_details
+        .unwrap()
+        .logs
+        .contains(&event_log));
+}
+
 #[tokio::test]
 /// Transfer test
 async fn test_mint_transfer_pass() {
@@ -141,9 +392,7 @@ async fn test_mint_transfer_pass() {
 
     for acc_key in [&start_pubkey, &target_pubkey] {
         let result = submit_txn(
-            &program_id,
-            ProgramInstruction::InitializeAccount,
-            &[AccountMeta::new(*acc_key, false)],
+            instruction::initialize_account(&program_id, acc_key, &payer.pubkey()),
             &payer,
             recent_blockhash,
             &mut banks_client,
@@ -153,13 +402,18 @@ async fn test_mint_transfer_pass() {
     }
 
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
 
     // Do mint
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::MintToAccount(mint_key.clone(), mint_value.clone()),
-        &[AccountMeta::new(start_pubkey, false)],
+        instruction::mint_to_account(
+            &program_id,
+            &start_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -169,12 +423,14 @@ async fn test_mint_transfer_pass() {
 
     // Do transfer
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::TransferBetweenAccounts(mint_key.clone()),
-        &[
-            AccountMeta::new(start_pubkey, false),
-            AccountMeta::new(target_pubkey, false),
-        ],
+        instruction::transfer_between_accounts(
+            &program_id,
+            &start_pubkey,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+        ),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -182,14 +438,22 @@ async fn test_mint_transfer_pass() {
     .await;
     assert!(result.is_ok());
 
-    let (is_initialized, btree_map) = match banks_client.get_account(start_pubkey).await.unwrap() {
+    let AccountStateData {
+        is_initialized,
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(start_pubkey).await.unwrap() {
         Some(account) => unpack_from_slice(&account.data).unwrap(),
         None => panic!(),
     };
     assert!(is_initialized);
     assert!(!btree_map.contains_key(&mint_key));
 
-    let (is_initialized, btree_map) = match banks_client.get_account(target_pubkey).await.unwrap() {
+    let AccountStateData {
+        is_initialized,
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(target_pubkey).await.unwrap() {
         Some(account) => unpack_from_slice(&account.data).unwrap(),
         None => panic!(),
     };
@@ -210,9 +474,7 @@ async fn test_mint_transfer_burn_pass() {
         setup(&program_id, &[start_pubkey, target_pubkey]).await;
     for acc_key in [&start_pubkey, &target_pubkey] {
         let result = submit_txn(
-            &program_id,
-            ProgramInstruction::InitializeAccount,
-            &[AccountMeta::new(*acc_key, false)],
+            instruction::initialize_account(&program_id, acc_key, &payer.pubkey()),
             &payer,
             recent_blockhash,
             &mut banks_client,
@@ -223,13 +485,18 @@ async fn test_mint_transfer_burn_pass() {
 
     // Do mint
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
 
     // Do mint
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::MintToAccount(mint_key.clone(), mint_value.clone()),
-        &[AccountMeta::new(start_pubkey, false)],
+        instruction::mint_to_account(
+            &program_id,
+            &start_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -239,12 +506,14 @@ async fn test_mint_transfer_burn_pass() {
 
     // Do transfer
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::TransferBetweenAccounts(mint_key.clone()),
-        &[
-            AccountMeta::new(start_pubkey, false),
-            AccountMeta::new(target_pubkey, false),
-        ],
+        instruction::transfer_between_accounts(
+            &program_id,
+            &start_pubkey,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+        ),
         &payer,
         recent_blockhash,
         &mut banks_client,
@@ -254,13 +523,4114 @@ async fn test_mint_transfer_burn_pass() {
 
     // Do burn
     let result = submit_txn(
-        &program_id,
-        ProgramInstruction::BurnFromAccount(mint_key.clone()),
-        &[AccountMeta::new(target_pubkey, false)],
+        instruction::burn_from_account(
+            &program_id,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+/// Authority is recorded on initialization
+async fn test_initialize_authority_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
+
+    // Fail when the authority does not sign
+    let authority_pubkey = Pubkey::new_unique();
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::initialize_account(&program_id, &account_pubkey, &authority_pubkey),
+            &authority_pubkey,
+            AccountMeta::new_readonly(authority_pubkey, false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
         &payer,
         recent_blockhash,
         &mut banks_client,
     )
     .await;
     assert!(result.is_ok());
+    let AccountStateData {
+        is_initialized,
+        authority,
+        ..
+    } = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert!(is_initialized);
+    assert_eq!(authority, payer.pubkey());
+}
+
+#[tokio::test]
+/// Mint, transfer and burn fail without the account authority
+async fn test_mint_transfer_burn_authority_fail() {
+    let program_id = Pubkey::new_unique();
+    let start_pubkey = Pubkey::new_unique();
+    let target_pubkey = Pubkey::new_unique();
+    let intruder = Keypair::new();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[start_pubkey, target_pubkey]).await;
+    for acc_key in [&start_pubkey, &target_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_account(&program_id, acc_key, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    let mint_key = String::from("test_key_1");
+    let mint_value = Value::from("value for test_key_1");
+
+    // Fail mint when the authority does not sign
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::mint_to_account(
+                &program_id,
+                &start_pubkey,
+                &intruder.pubkey(),
+                &[],
+                &mint_key,
+                &mint_value,
+            ),
+            &intruder.pubkey(),
+            AccountMeta::new_readonly(intruder.pubkey(), false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Fail mint when signed by someone other than the authority
+    let result = submit_txn_with_signers(
+        instruction::mint_to_account(
+            &program_id,
+            &start_pubkey,
+            &intruder.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
+        &[&payer, &intruder],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Mint with the authority
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &start_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail transfer when signed by someone other than the authority
+    let result = submit_txn_with_signers(
+        instruction::transfer_between_accounts(
+            &program_id,
+            &start_pubkey,
+            &target_pubkey,
+            &intruder.pubkey(),
+            &[],
+            &mint_key,
+        ),
+        &[&payer, &intruder],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Fail burn when signed by someone other than the authority
+    let result = submit_txn_with_signers(
+        instruction::burn_from_account(
+            &program_id,
+            &start_pubkey,
+            &intruder.pubkey(),
+            &[],
+            &mint_key,
+        ),
+        &[&payer, &intruder],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Key remains with the original account
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(start_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert!(btree_map.contains_key(&mint_key));
+}
+
+#[tokio::test]
+/// Derived account initialization and mint test
+async fn test_initialize_derived_account_pass() {
+    let program_id = Pubkey::new_unique();
+    let seed = "account";
+
+    // Setup runtime testing without pre-generated accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[]).await;
+    let (account_pubkey, _) = find_derived_account_address(&program_id, &payer.pubkey(), seed);
+
+    // Fail when the account is not the derived address
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::initialize_derived_account(&program_id, &payer.pubkey(), seed),
+            &account_pubkey,
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let result = submit_txn(
+        instruction::initialize_derived_account(&program_id, &payer.pubkey(), seed),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account = banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, program_id);
+    let AccountStateData {
+        is_initialized,
+        authority,
+        ..
+    } = unpack_from_slice(&account.data).unwrap();
+    assert!(is_initialized);
+    assert_eq!(authority, payer.pubkey());
+
+    // Do mint
+    let mint_key = String::from("test_key_1");
+    let mint_value = Value::from("value for test_key_1");
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(btree_map.get(&mint_key).unwrap(), &mint_value);
+}
+
+#[tokio::test]
+/// Update and upsert test
+async fn test_mint_update_upsert_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
+
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let mint_key = String::from("test_key_1");
+    let mint_value = Value::from("value for test_key_1");
+    let update_value = Value::from("updated value for test_key_1");
+    let upsert_key = String::from("test_key_2");
+    let upsert_value = Value::from("value for test_key_2");
+
+    // Fail update of a key that does not exist
+    let result = submit_txn(
+        instruction::update_value(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &Value::from("no key to update"),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Do mint
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Do update
+    let result = submit_txn(
+        instruction::update_value(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &update_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Do upsert of a new key
+    let result = submit_txn(
+        instruction::upsert_value(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &upsert_key,
+            &upsert_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(btree_map.get(&mint_key).unwrap(), &update_value);
+    assert_eq!(btree_map.get(&upsert_key).unwrap(), &upsert_value);
+}
+
+#[tokio::test]
+/// Resize test
+async fn test_resize_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
+
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Grow the account
+    let grown_size = ACCOUNT_STATE_SPACE * 2;
+    let result = submit_txn(
+        instruction::resize(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            grown_size as u64,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let rent = banks_client.get_rent().await.unwrap();
+    let account = banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), grown_size);
+    assert!(rent.is_exempt(account.lamports, grown_size));
+
+    // Mint values larger than the initial account storage
+    let mint_keys = [String::from("test_key_1"), String::from("test_key_2")];
+    let mint_value = Value::from("v".repeat(BTREE_STORAGE / 2 + 100));
+    for mint_key in &mint_keys {
+        let result = submit_txn(
+            instruction::mint_to_account(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                &mint_key,
+                &mint_value,
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Fail to shrink the account below the size of its state
+    let result = submit_txn(
+        instruction::resize(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            ACCOUNT_STATE_SPACE as u64,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Burn and shrink the account, refunding the excess rent
+    for mint_key in &mint_keys {
+        let result = submit_txn(
+            instruction::burn_from_account(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                &mint_key,
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    let shrunk_size = ACCOUNT_STATE_SPACE / 2;
+
+    // Fail to shrink the account refunding a funder that did not sign
+    let funder = Pubkey::new_unique();
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::resize(
+                &program_id,
+                &account_pubkey,
+                &funder,
+                &payer.pubkey(),
+                &[],
+                shrunk_size as u64,
+            ),
+            &funder,
+            AccountMeta::new(funder, false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(matches!(
+        result.err().unwrap().unwrap(),
+        TransactionError::InstructionError(_, InstructionError::MissingRequiredSignature)
+    ));
+
+    let result = submit_txn(
+        instruction::resize(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            shrunk_size as u64,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account = banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), shrunk_size);
+    assert_eq!(account.lamports, rent.minimum_balance(shrunk_size));
+    let AccountStateData {
+        is_initialized,
+        btree_storage: btree_map,
+        ..
+    } = unpack_from_slice(&account.data).unwrap();
+    assert!(is_initialized);
+    assert!(btree_map.is_empty());
+}
+
+#[tokio::test]
+/// Close test
+async fn test_close_account_pass() {
+    let program_id = Pubkey::new_unique();
+    let seed = "account";
+
+    // Setup runtime testing with a derived account
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[]).await;
+    let (account_pubkey, _) = find_derived_account_address(&program_id, &payer.pubkey(), seed);
+    let result = submit_txn(
+        instruction::initialize_derived_account(&program_id, &payer.pubkey(), seed),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Do mint
+    let mint_key = String::from("test_key_1");
+    let mint_value = Value::from("value for test_key_1");
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &mint_key,
+            &mint_value,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail closing an account that still has keys when required empty
+    let result = submit_txn(
+        instruction::close_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            true,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Close and reclaim the lamports
+    let result = submit_txn(
+        instruction::close_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            false,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    assert!(banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+/// Batched mint, transfer and burn test
+async fn test_mint_transfer_burn_many_pass() {
+    let program_id = Pubkey::new_unique();
+    let start_pubkey = Pubkey::new_unique();
+    let target_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[start_pubkey, target_pubkey]).await;
+    for acc_key in [&start_pubkey, &target_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_account(&program_id, acc_key, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    let key_values: Vec<(String, Value)> = (1..=5)
+        .map(|i| {
+            (
+                format!("test_key_{}", i),
+                Value::from(format!("value for test_key_{}", i)),
+            )
+        })
+        .collect();
+    let keys: Vec<String> = key_values.iter().map(|(key, _)| key.clone()).collect();
+
+    // Do mint many
+    let result = submit_txn(
+        instruction::mint_many(
+            &program_id,
+            &start_pubkey,
+            &payer.pubkey(),
+            &[],
+            &key_values,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail transfer of many when one key does not exist, transferring none
+    let mut bad_keys = keys.clone();
+    bad_keys.push(String::from("bad_key_1"));
+    let result = submit_txn(
+        instruction::transfer_many(
+            &program_id,
+            &start_pubkey,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &bad_keys,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(start_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(btree_map.len(), keys.len());
+
+    // Do transfer many of all but the last key
+    let result = submit_txn(
+        instruction::transfer_many(
+            &program_id,
+            &start_pubkey,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &keys[..4].to_vec(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Do burn many
+    let result = submit_txn(
+        instruction::burn_many(
+            &program_id,
+            &target_pubkey,
+            &payer.pubkey(),
+            &[],
+            &keys[..2].to_vec(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(start_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(btree_map.keys().collect::<Vec<_>>(), vec![&keys[4]]);
+    let AccountStateData {
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(target_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(
+        btree_map.keys().collect::<Vec<_>>(),
+        vec![&keys[2], &keys[3]]
+    );
+}
+
+#[tokio::test]
+/// Transfers to the account transferred from fail rather than panic
+async fn test_transfer_to_same_account_fail() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, treasury_pubkey]).await;
+    let result = fund_rent_exempt(
+        &[account_pubkey, treasury_pubkey],
+        1_000,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = initialize_with_config(
+        &program_id,
+        &[account_pubkey],
+        ServiceFees::default(),
+        &treasury_pubkey,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"value_1".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    for transfer in [
+        instruction::transfer_between_accounts(
+            &program_id,
+            &account_pubkey,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+        ),
+        instruction::transfer_between_accounts_with_fee(
+            &program_id,
+            &account_pubkey,
+            &account_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+        ),
+        instruction::transfer_many(
+            &program_id,
+            &account_pubkey,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &["key_1".to_string()],
+        ),
+    ] {
+        let result = submit_txn(transfer, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(matches!(
+            result.err().unwrap().unwrap(),
+            TransactionError::InstructionError(_, InstructionError::InvalidArgument)
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_config_fees_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+    let (config_pubkey, _) = find_config_address(&program_id);
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, treasury_pubkey]).await;
+    let result = fund_rent_exempt(
+        &[account_pubkey, treasury_pubkey],
+        5,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail when the signer is not the program's upgrade authority, or the
+    // program data account is not the program's
+    let not_admin = Keypair::new();
+    let result = submit_txn_with_signers(
+        instruction::initialize_config(
+            &program_id,
+            &not_admin.pubkey(),
+            ServiceFees::default(),
+            &treasury_pubkey,
+        ),
+        &[&payer, &not_admin],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidAuthority));
+    let mut misplaced_program_data = instruction::initialize_config(
+        &program_id,
+        &payer.pubkey(),
+        ServiceFees::default(),
+        &treasury_pubkey,
+    );
+    misplaced_program_data.accounts[3].pubkey = Pubkey::new_unique();
+    let result = submit_txn(
+        misplaced_program_data,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::InvalidProgramDataAccount)
+    );
+
+    let result = initialize_with_config(
+        &program_id,
+        &[account_pubkey],
+        ServiceFees::default(),
+        &treasury_pubkey,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail when the signer is not the admin
+    let fees = ServiceFees {
+        minting: 3,
+        transfering: 3,
+        burning: 3,
+        updating: 3,
+    };
+    let result = submit_txn_with_signers(
+        instruction::set_fees(&program_id, &not_admin.pubkey(), fees),
+        &[&payer, &not_admin],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let result = submit_txn(
+        instruction::set_fees(&program_id, &payer.pubkey(), fees),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let config = banks_client
+        .get_account(config_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let config_state = ProgramConfigState::unpack(&config.data).unwrap();
+    assert_eq!(config_state.admin(), &payer.pubkey());
+    assert_eq!(config_state.fees(), &fees);
+    assert_eq!(config_state.treasury(), &treasury_pubkey);
+
+    // Fail when the config account is not the programs config
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::mint_to_account_with_fee(
+                &program_id,
+                &account_pubkey,
+                &treasury_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+                &"value_1".into(),
+            ),
+            &config_pubkey,
+            AccountMeta::new_readonly(account_pubkey, false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Mint charges the configured fee
+    let before = banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let result = submit_txn(
+        instruction::mint_to_account_with_fee(
+            &program_id,
+            &account_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &"value_2".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account = banks_client
+        .get_account(account_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.lamports, before.lamports - fees.minting);
+}
+
+#[tokio::test]
+async fn test_fee_charging_pass() {
+    let program_id = Pubkey::new_unique();
+    let from_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+    let poor_pubkey = Pubkey::new_unique();
+    let fees = ServiceFees::default();
+
+    // Setup runtime testing and accounts, the poor account can not
+    // afford a fee without dropping below rent exemption
+    let (mut banks_client, payer, recent_blockhash) = setup(
+        &program_id,
+        &[from_pubkey, to_pubkey, treasury_pubkey, poor_pubkey],
+    )
+    .await;
+    let result = fund_rent_exempt(
+        &[from_pubkey, to_pubkey, treasury_pubkey],
+        1_000,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = fund_rent_exempt(
+        &[poor_pubkey],
+        0,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = initialize_with_config(
+        &program_id,
+        &[from_pubkey, to_pubkey, poor_pubkey],
+        fees,
+        &treasury_pubkey,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let lamports_of = |account: Option<Account>| account.unwrap().lamports;
+    let treasury_start = lamports_of(banks_client.get_account(treasury_pubkey).await.unwrap());
+    let from_start = lamports_of(banks_client.get_account(from_pubkey).await.unwrap());
+    let to_start = lamports_of(banks_client.get_account(to_pubkey).await.unwrap());
+
+    // Fail when fees are routed to an account other than the treasury
+    let result = submit_txn(
+        instruction::mint_to_account_with_fee(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"value_1".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Fail when the fee would drop the account below rent exemption
+    let result = submit_txn(
+        instruction::mint_to_account_with_fee(
+            &program_id,
+            &poor_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &"value_2".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Mint, update and upsert with fees
+    let fee_instructions = [
+        instruction::mint_to_account_with_fee(
+            &program_id,
+            &from_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+            &"value_3".into(),
+        ),
+        instruction::update_value_with_fee(
+            &program_id,
+            &from_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+            &"updated".into(),
+        ),
+        instruction::upsert_value_with_fee(
+            &program_id,
+            &from_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_4",
+            &"value_4".into(),
+        ),
+    ];
+    for instruction in fee_instructions {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+
+    // Transfer with fee charges both accounts
+    let result = submit_txn(
+        instruction::transfer_between_accounts_with_fee(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Burn with fee
+    let result = submit_txn(
+        instruction::burn_from_account_with_fee(
+            &program_id,
+            &from_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_4",
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let from_account = banks_client
+        .get_account(from_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let AccountStateData {
+        btree_storage: from_btree,
+        ..
+    } = unpack_from_slice(&from_account.data).unwrap();
+    assert!(from_btree.is_empty());
+    let to_account = banks_client.get_account(to_pubkey).await.unwrap().unwrap();
+    let AccountStateData {
+        btree_storage: to_btree,
+        ..
+    } = unpack_from_slice(&to_account.data).unwrap();
+    assert_eq!(to_btree.get("key_3").unwrap(), "updated");
+
+    // Fees are debited from the accounts and credited to the treasury
+    let from_fees = fees.minting + 2 * fees.updating + fees.transfering + fees.burning;
+    assert_eq!(from_account.lamports, from_start - from_fees);
+    assert_eq!(to_account.lamports, to_start - fees.minting);
+    let treasury_end = lamports_of(banks_client.get_account(treasury_pubkey).await.unwrap());
+    assert_eq!(treasury_end, treasury_start + from_fees + fees.minting);
+}
+
+#[tokio::test]
+async fn test_approve_revoke_pass() {
+    let program_id = Pubkey::new_unique();
+    let from_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let delegate = Keypair::new();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[from_pubkey, to_pubkey]).await;
+    for account in [from_pubkey, to_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_account(&program_id, &account, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    let key_values: Vec<(String, Value)> = (1..4)
+        .map(|i| (format!("key_{}", i), Value::from(format!("value_{}", i))))
+        .collect();
+    let result = submit_txn(
+        instruction::mint_many(&program_id, &from_pubkey, &payer.pubkey(), &[], &key_values),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let delegate_transfer = |key: &str| {
+        instruction::transfer_between_accounts(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &delegate.pubkey(),
+            &[],
+            key,
+        )
+    };
+
+    // Fail when the delegate was not approved for the key
+    let result = submit_txn_with_signers(
+        delegate_transfer("key_2"),
+        &[&payer, &delegate],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Fail when approving a key that does not exist
+    let result = submit_txn(
+        instruction::approve(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_4",
+            &delegate.pubkey(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Approve the delegate for two keys and revoke one of them
+    for instruction in [
+        instruction::approve(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &delegate.pubkey(),
+        ),
+        instruction::approve(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+            &delegate.pubkey(),
+        ),
+        instruction::revoke(&program_id, &from_pubkey, &payer.pubkey(), &[], "key_3"),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+    let AccountStateData { delegates, .. } =
+        match banks_client.get_account(from_pubkey).await.unwrap() {
+            Some(account) => unpack_from_slice(&account.data).unwrap(),
+            None => panic!(),
+        };
+    assert_eq!(delegates.len(), 1);
+    assert_eq!(delegates.get("key_1").unwrap(), &delegate.pubkey());
+
+    // Fail when the delegate was revoked
+    let result = submit_txn_with_signers(
+        delegate_transfer("key_3"),
+        &[&payer, &delegate],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // The delegate transfers the approved key, clearing the delegation
+    let result = submit_txn_with_signers(
+        delegate_transfer("key_1"),
+        &[&payer, &delegate],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let AccountStateData {
+        btree_storage: btree_map,
+        delegates,
+        ..
+    } = match banks_client.get_account(from_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert!(!btree_map.contains_key("key_1"));
+    assert!(delegates.is_empty());
+    let AccountStateData {
+        btree_storage: btree_map,
+        delegates,
+        ..
+    } = match banks_client.get_account(to_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(btree_map.get("key_1").unwrap(), "value_1");
+    assert!(delegates.is_empty());
+}
+
+#[tokio::test]
+async fn test_swap_keys_pass() {
+    let program_id = Pubkey::new_unique();
+    let a_pubkey = Pubkey::new_unique();
+    let b_pubkey = Pubkey::new_unique();
+    let a_authority = Keypair::new();
+    let b_authority = Keypair::new();
+
+    // Setup runtime testing and accounts, each with its own authority
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[a_pubkey, b_pubkey]).await;
+    for (account, authority) in [(a_pubkey, &a_authority), (b_pubkey, &b_authority)] {
+        let result = submit_txn_with_signers(
+            instruction::initialize_account(&program_id, &account, &authority.pubkey()),
+            &[&payer, authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    // A holds key_a and a key_b that conflicts with the key_b of B
+    for (account, authority, key, value) in [
+        (a_pubkey, &a_authority, "key_a", "value_a"),
+        (a_pubkey, &a_authority, "key_b", "conflict"),
+        (b_pubkey, &b_authority, "key_b", "value_b"),
+    ] {
+        let result = submit_txn_with_signers(
+            instruction::mint_to_account(
+                &program_id,
+                &account,
+                &authority.pubkey(),
+                &[],
+                key,
+                &value.into(),
+            ),
+            &[&payer, authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    let swap = |key_a: &str, key_b: &str| {
+        instruction::swap_keys(
+            &program_id,
+            &a_pubkey,
+            &b_pubkey,
+            &a_authority.pubkey(),
+            &[],
+            &b_authority.pubkey(),
+            &[],
+            key_a,
+            key_b,
+        )
+    };
+
+    // Fail when the authority of B does not sign
+    let result = submit_txn_with_signers(
+        with_account_replaced(
+            swap("key_a", "key_b"),
+            &b_authority.pubkey(),
+            AccountMeta::new_readonly(b_authority.pubkey(), false),
+        ),
+        &[&payer, &a_authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Fail when a key is missing or already exists, moving neither key
+    for instruction in [swap("key_c", "key_b"), swap("key_a", "key_b")] {
+        let result = submit_txn_with_signers(
+            instruction,
+            &[&payer, &a_authority, &b_authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+    let AccountStateData {
+        btree_storage: a_btree,
+        ..
+    } = match banks_client.get_account(a_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    let AccountStateData {
+        btree_storage: b_btree,
+        ..
+    } = match banks_client.get_account(b_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(a_btree.get("key_a").unwrap(), "value_a");
+    assert_eq!(b_btree.get("key_b").unwrap(), "value_b");
+
+    // Burn the conflict and swap
+    let result = submit_txn_with_signers(
+        instruction::burn_from_account(&program_id, &a_pubkey, &a_authority.pubkey(), &[], "key_b"),
+        &[&payer, &a_authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn_with_signers(
+        swap("key_a", "key_b"),
+        &[&payer, &a_authority, &b_authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let AccountStateData {
+        btree_storage: a_btree,
+        ..
+    } = match banks_client.get_account(a_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    let AccountStateData {
+        btree_storage: b_btree,
+        ..
+    } = match banks_client.get_account(b_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(a_btree.len(), 1);
+    assert_eq!(a_btree.get("key_b").unwrap(), "value_b");
+    assert_eq!(b_btree.len(), 1);
+    assert_eq!(b_btree.get("key_a").unwrap(), "value_a");
+}
+
+#[tokio::test]
+async fn test_expiry_purge_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let authority = Keypair::new();
+
+    // Setup runtime testing and accounts owned by the authority
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, to_pubkey]).await;
+    for account in [account_pubkey, to_pubkey] {
+        let result = submit_txn_with_signers(
+            instruction::initialize_account(&program_id, &account, &authority.pubkey()),
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Mint key/values that have expired, that will not expire and that
+    // never expire
+    for instruction in [
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "expired",
+            &"lease".into(),
+            Expiry::Slot(0),
+        ),
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "live_slot",
+            &"lease".into(),
+            Expiry::Slot(u64::MAX),
+        ),
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "live_time",
+            &"session".into(),
+            Expiry::UnixTimestamp(i64::MAX),
+        ),
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "forever",
+            &"value".into(),
+        ),
+    ] {
+        let result = submit_txn_with_signers(
+            instruction,
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    let account_state = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(account_state.btree_storage.len(), 4);
+    assert_eq!(account_state.expiry("expired"), Some(&Expiry::Slot(0)));
+    assert_eq!(account_state.expiry("forever"), None);
+
+    // Fail to transfer or burn the expired key
+    let result = submit_txn_with_signers(
+        instruction::transfer_between_accounts(
+            &program_id,
+            &account_pubkey,
+            &to_pubkey,
+            &authority.pubkey(),
+            &[],
+            "expired",
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+    let result = submit_txn_with_signers(
+        instruction::burn_from_account(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "expired",
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // Anyone may purge, the payer is not the authority
+    let result = submit_txn(
+        instruction::purge_expired(&program_id, &account_pubkey),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account_state = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert!(!account_state.btree_storage.contains_key("expired"));
+    assert_eq!(account_state.btree_storage.len(), 3);
+    assert_eq!(account_state.expiries.len(), 2);
+
+    // Unexpired keys are burned as usual, along with their expiry
+    let result = submit_txn_with_signers(
+        instruction::burn_from_account(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "live_slot",
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account_state = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(account_state.btree_storage.len(), 2);
+    assert_eq!(account_state.expiry("live_slot"), None);
+
+    // An expired key can not be updated, but is minted again by upsert
+    // without its expiry
+    let result = submit_txn_with_signers(
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "stale",
+            &"lease".into(),
+            Expiry::Slot(0),
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn_with_signers(
+        instruction::update_value(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "stale",
+            &"renewed".into(),
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::KeyNotFoundInAccount)
+    );
+    let result = submit_txn_with_signers(
+        instruction::upsert_value(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "stale",
+            &"renewed".into(),
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account_state = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(
+        account_state.btree_storage.get("stale"),
+        Some(&"renewed".into())
+    );
+    assert_eq!(account_state.expiry("stale"), None);
+}
+
+#[tokio::test]
+async fn test_typed_values_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let authority = Keypair::new();
+
+    // Setup runtime testing and accounts owned by the authority
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, to_pubkey]).await;
+    for account in [account_pubkey, to_pubkey] {
+        let result = submit_txn_with_signers(
+            instruction::initialize_account(&program_id, &account, &authority.pubkey()),
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Mint a value of each type
+    let key_values = vec![
+        ("string".to_string(), Value::from("text")),
+        ("u64".to_string(), Value::U64(u64::MAX)),
+        ("i64".to_string(), Value::I64(-42)),
+        ("bytes".to_string(), Value::Bytes(vec![0, 1, 254, 255])),
+        ("pubkey".to_string(), Value::Pubkey(authority.pubkey())),
+        ("bool".to_string(), Value::Bool(true)),
+    ];
+    let result = submit_txn_with_signers(
+        instruction::mint_many(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            &key_values,
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Updates may change the type of a value and transfers keep the type
+    let result = submit_txn_with_signers(
+        instruction::update_value(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            "string",
+            &Value::U64(7),
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn_with_signers(
+        instruction::transfer_between_accounts(
+            &program_id,
+            &account_pubkey,
+            &to_pubkey,
+            &authority.pubkey(),
+            &[],
+            "bytes",
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let account_state = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(account_state.btree_storage.len(), 5);
+    assert_eq!(account_state.btree_storage["string"], Value::U64(7));
+    assert_eq!(account_state.btree_storage["u64"], Value::U64(u64::MAX));
+    assert_eq!(account_state.btree_storage["i64"], Value::I64(-42));
+    assert_eq!(
+        account_state.btree_storage["pubkey"],
+        Value::Pubkey(authority.pubkey())
+    );
+    assert_eq!(account_state.btree_storage["bool"], Value::Bool(true));
+    let to_state = match banks_client.get_account(to_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(
+        to_state.btree_storage["bytes"],
+        Value::Bytes(vec![0, 1, 254, 255])
+    );
+}
+
+/// Returns account data of the `V0` layout, the initialization flag followed
+/// by the length prefixed storage
+fn v0_account_data(storage: &[u8]) -> Vec<u8> {
+    let mut data = vec![1_u8];
+    data.extend((storage.len() as u32).to_le_bytes());
+    data.extend(storage);
+    data
+}
+
+#[tokio::test]
+async fn test_migrate_account_pass() {
+    let program_id = Pubkey::new_unique();
+    let v0_account = Keypair::new();
+    let authority = Keypair::new();
+
+    // An account of the V0 layout, sized to fit its state exactly so
+    // migrating must grow it
+    let v0_data = v0_account_data(
+        &BTreeMap::from([("key_0".to_string(), "value_0".to_string())])
+            .try_to_vec()
+            .unwrap(),
+    );
+    let v0_len = v0_data.len();
+    assert_eq!(layout_version_of(&v0_data).unwrap(), LayoutVersion::V0);
+    let (mut banks_client, payer, recent_blockhash) =
+        setup_with_data(&program_id, &[(v0_account.pubkey(), v0_data)]).await;
+    let result = fund_rent_exempt(
+        &[authority.pubkey()],
+        0,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Accounts of earlier layouts must be migrated before use
+    let mint = |key: &str| {
+        instruction::mint_to_account(
+            &program_id,
+            &v0_account.pubkey(),
+            &authority.pubkey(),
+            &[],
+            key,
+            &"value_1".into(),
+        )
+    };
+    let result = submit_txn_with_signers(
+        mint("key_1"),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    // The V0 account has no authority, so must sign its own migration
+    let v0_migrate = |account_signs: bool| {
+        instruction::migrate_account(
+            &program_id,
+            &v0_account.pubkey(),
+            &authority.pubkey(),
+            &authority.pubkey(),
+            &[],
+            account_signs,
+        )
+    };
+    let result = submit_txn_with_signers(
+        v0_migrate(false),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+    let result = submit_txn_with_signers(
+        v0_migrate(true),
+        &[&payer, &authority, &v0_account],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // The migrated account is of the current layout, grown to hold its state,
+    // keeps its values and has the signing wallet as its authority
+    let migrated = banks_client
+        .get_account(v0_account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(migrated.data[0], LayoutVersion::CURRENT as u8);
+    assert_eq!(
+        layout_version_of(&migrated.data).unwrap(),
+        LayoutVersion::CURRENT
+    );
+    assert!(migrated.data.len() > v0_len);
+    let account_state = unpack_from_slice(&migrated.data).unwrap();
+    assert!(account_state.is_initialized);
+    assert_eq!(account_state.btree_storage.get("key_0").unwrap(), "value_0");
+    assert_eq!(account_state.authority, authority.pubkey());
+
+    // The migrated account is usable
+    let result = submit_txn_with_signers(
+        mint("key_1"),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_zero_copy_account_pass() {
+    let program_id = Pubkey::new_unique();
+    let from_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+
+    // Setup runtime testing and zero-copy accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[from_pubkey, to_pubkey]).await;
+    for account in [from_pubkey, to_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_zero_copy_account(&program_id, &account, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    let other_wallet = Keypair::new();
+    let result = submit_txn_with_signers(
+        instruction::initialize_zero_copy_account(
+            &program_id,
+            &from_pubkey,
+            &other_wallet.pubkey(),
+        ),
+        &[&payer, &other_wallet],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+
+    let instructions = [
+        // Keys are minted out of order and kept sorted
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+            &"value_3".into(),
+        ),
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &Value::U64(1),
+        ),
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &"value_2".into(),
+        ),
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_5",
+            &"value_5".into(),
+            Expiry::Slot(u64::MAX),
+        ),
+        instruction::update_value(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"a longer value_1".into(),
+        ),
+        instruction::approve(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &delegate,
+        ),
+        instruction::upsert_value(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_4",
+            &Value::Bool(true),
+        ),
+        instruction::burn_from_account(&program_id, &from_pubkey, &payer.pubkey(), &[], "key_3"),
+    ];
+    for instruction in instructions {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &"again".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_err());
+    let result = submit_txn(
+        instruction::transfer_between_accounts(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // The zero-copy accounts decode as any other account state
+    let from_account = banks_client
+        .get_account(from_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        layout_version_of(&from_account.data).unwrap(),
+        LayoutVersion::ZeroCopy
+    );
+    let from_state = unpack_from_slice(&from_account.data).unwrap();
+    assert!(from_state.is_initialized);
+    assert_eq!(from_state.authority, payer.pubkey());
+    assert_eq!(
+        from_state.btree_storage.keys().collect::<Vec<_>>(),
+        ["key_2", "key_4", "key_5"]
+    );
+    assert_eq!(from_state.btree_storage["key_4"], Value::Bool(true));
+    assert_eq!(from_state.delegates["key_2"], delegate);
+    assert_eq!(from_state.expiries["key_5"], Expiry::Slot(u64::MAX));
+    let to_account = banks_client.get_account(to_pubkey).await.unwrap().unwrap();
+    let to_state = unpack_from_slice(&to_account.data).unwrap();
+    assert_eq!(
+        to_state.btree_storage.get("key_1").unwrap(),
+        "a longer value_1"
+    );
+
+    // Resizing moves the entries to the end of the data area
+    for size in [ACCOUNT_STATE_SPACE * 2, ACCOUNT_STATE_SPACE / 2] {
+        let result = submit_txn(
+            instruction::resize(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &[],
+                size as u64,
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        let account = banks_client
+            .get_account(from_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.data.len(), size);
+        assert_eq!(unpack_from_slice(&account.data).unwrap(), from_state);
+    }
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_0",
+            &"value_0".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let account = banks_client
+        .get_account(from_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let account_state = unpack_from_slice(&account.data).unwrap();
+    assert_eq!(account_state.btree_storage.len(), 4);
+    assert_eq!(account_state.btree_storage.get("key_0").unwrap(), "value_0");
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "test-sbf"), ignore)]
+/// Zero-copy compute units test, run with `cargo test-sbf` as native runs
+/// are not metered
+async fn test_zero_copy_compute_units_pass() {
+    const KEY_COUNT: u64 = 100;
+    let program_id = Pubkey::new_unique();
+    let borsh_pubkey = Pubkey::new_unique();
+    let zero_copy_pubkey = Pubkey::new_unique();
+    let authority = Keypair::new();
+
+    // Accounts holding the same key/value pairs in each layout
+    let key_values: Vec<(String, Value)> = (0..KEY_COUNT)
+        .map(|index| (format!("key_{:03}", index), Value::U64(index)))
+        .collect();
+    let space = ACCOUNT_STATE_SPACE * 4;
+    let mut borsh_data = vec![0_u8; space];
+    pack_into_slice(
+        &AccountStateData {
+            is_initialized: true,
+            authority: authority.pubkey(),
+            btree_storage: key_values.iter().cloned().collect(),
+            ..AccountStateData::default()
+        },
+        &mut borsh_data,
+    )
+    .unwrap();
+    let mut zero_copy_data = vec![0_u8; space];
+    let mut zero_copy_state =
+        ZeroCopyAccountState::initialize(&mut zero_copy_data[..], &authority.pubkey()).unwrap();
+    for (key, value) in &key_values {
+        zero_copy_state.insert(key, value, None, None).unwrap();
+    }
+    assert_eq!(
+        unpack_from_slice(&zero_copy_data).unwrap(),
+        unpack_from_slice(&borsh_data).unwrap()
+    );
+    let (mut banks_client, payer, recent_blockhash) = setup_with_data(
+        &program_id,
+        &[
+            (borsh_pubkey, borsh_data),
+            (zero_copy_pubkey, zero_copy_data),
+        ],
+    )
+    .await;
+
+    // Each instruction is simulated against both accounts
+    let instructions = |account: &Pubkey| {
+        [
+            instruction::mint_to_account(
+                &program_id,
+                account,
+                &authority.pubkey(),
+                &[],
+                "key_new",
+                &Value::U64(0),
+            ),
+            instruction::update_value(
+                &program_id,
+                account,
+                &authority.pubkey(),
+                &[],
+                "key_050",
+                &"value_050".into(),
+            ),
+            instruction::burn_from_account(
+                &program_id,
+                account,
+                &authority.pubkey(),
+                &[],
+                "key_050",
+            ),
+        ]
+    };
+    let mut units = Vec::<Vec<u64>>::new();
+    for account in [borsh_pubkey, zero_copy_pubkey] {
+        let mut account_units = Vec::<u64>::new();
+        for instruction in instructions(&account) {
+            account_units.push(
+                simulate_compute_units(
+                    instruction,
+                    &[&payer, &authority],
+                    recent_blockhash,
+                    &mut banks_client,
+                )
+                .await,
+            );
+        }
+        units.push(account_units);
+    }
+    for (borsh_units, zero_copy_units) in units[0].iter().zip(&units[1]) {
+        assert!(*zero_copy_units > 0);
+        assert!(zero_copy_units < borsh_units);
+    }
+}
+
+#[tokio::test]
+/// Malformed instruction and account data test
+async fn test_malformed_data_fail() {
+    let program_id = Pubkey::new_unique();
+    let garbage_pubkey = Pubkey::new_unique();
+    let truncated_pubkey = Pubkey::new_unique();
+    let truncated_header_pubkey = Pubkey::new_unique();
+    let small_pubkey = Pubkey::new_unique();
+    let authority = Keypair::new();
+
+    // Account data whose storage is garbage, whose storage is cut short of
+    // the content length, whose header is cut short, and which has no room
+    // for a key/value pair
+    let mut garbage_data = vec![LayoutVersion::CURRENT as u8];
+    garbage_data.extend(authority.pubkey().as_ref());
+    garbage_data.extend(8_u32.to_le_bytes());
+    garbage_data.extend([0xff_u8; 8]);
+    let mut truncated_data = vec![LayoutVersion::CURRENT as u8];
+    truncated_data.extend(authority.pubkey().as_ref());
+    truncated_data.extend(100_u32.to_le_bytes());
+    truncated_data.extend([0_u8; 10]);
+    let truncated_header_data = truncated_data[..10].to_vec();
+    let mut small_data = vec![0_u8; ACCOUNT_STATE_HEADER + 4];
+    pack_into_slice(
+        &AccountStateData {
+            is_initialized: true,
+            authority: authority.pubkey(),
+            ..AccountStateData::default()
+        },
+        &mut small_data,
+    )
+    .unwrap();
+    // Packing fails, rather than panics, when the data is too small
+    assert!(matches!(
+        pack_into_slice(
+            &AccountStateData {
+                is_initialized: true,
+                authority: authority.pubkey(),
+                btree_storage: BTreeMap::from([("key".to_string(), Value::from("value"))]),
+                ..AccountStateData::default()
+            },
+            &mut small_data.clone(),
+        ),
+        Err(AccountStateError::AccountDataTooSmall { .. })
+    ));
+    assert!(matches!(
+        unpack_from_slice(&garbage_data),
+        Err(AccountStateError::DeserializationFailure)
+    ));
+    assert!(matches!(
+        unpack_from_slice(&truncated_data),
+        Err(AccountStateError::ContentLengthOverflow { .. })
+    ));
+    assert!(matches!(
+        unpack_from_slice(&truncated_header_data),
+        Err(AccountStateError::AccountDataTooSmall { .. })
+    ));
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup_with_data(
+        &program_id,
+        &[
+            (garbage_pubkey, garbage_data),
+            (truncated_pubkey, truncated_data),
+            (truncated_header_pubkey, truncated_header_data),
+            (small_pubkey, small_data),
+        ],
+    )
+    .await;
+
+    // Garbage and truncated instruction data
+    let mint = instruction::mint_to_account(
+        &program_id,
+        &small_pubkey,
+        &authority.pubkey(),
+        &[],
+        "key",
+        &Value::from("value"),
+    );
+    for data in [vec![0xff_u8, 1], mint.data[..mint.data.len() - 1].to_vec()] {
+        let result = submit_txn_with_signers(
+            Instruction {
+                data,
+                ..mint.clone()
+            },
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::InvalidInstruction)
+        );
+    }
+
+    // Malformed account data
+    for (account, error) in [
+        (garbage_pubkey, SampleError::DeserializationFailure),
+        (truncated_pubkey, SampleError::DeserializationFailure),
+        (truncated_header_pubkey, SampleError::AccountDataTooSmall),
+        (small_pubkey, SampleError::AccountDataTooSmall),
+    ] {
+        let result = submit_txn_with_signers(
+            instruction::mint_to_account(
+                &program_id,
+                &account,
+                &authority.pubkey(),
+                &[],
+                "key",
+                &Value::from("value"),
+            ),
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(sample_error_of(result), Some(error));
+    }
+}
+
+#[tokio::test]
+/// Instruction wire format test
+async fn test_instruction_wire_format_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+
+    // The version byte, the fixed tag and the borsh encoded fields
+    let golden: [(ProgramInstruction, Vec<u8>); 6] = [
+        (ProgramInstruction::InitializeAccount, vec![128, 0]),
+        (
+            ProgramInstruction::MintToAccount("key".to_string(), Value::from("v")),
+            vec![128, 1, 3, 0, 0, 0, b'k', b'e', b'y', 0, 1, 0, 0, 0, b'v'],
+        ),
+        (
+            ProgramInstruction::Resize(4096),
+            vec![128, 12, 0, 16, 0, 0, 0, 0, 0, 0],
+        ),
+        (ProgramInstruction::CloseAccount(true), vec![128, 13, 1]),
+        (
+            ProgramInstruction::SwapKeys {
+                key_a: "a".to_string(),
+                key_b: "b".to_string(),
+                signer_count_a: 2,
+                signer_count_b: 0,
+            },
+            vec![128, 22, 1, 0, 0, 0, b'a', 1, 0, 0, 0, b'b', 2, 0],
+        ),
+        (ProgramInstruction::InitializeRegistry, vec![128, 36]),
+    ];
+    for (program_instruction, data) in golden {
+        assert_eq!(program_instruction.try_to_vec().unwrap(), data);
+        assert_eq!(ProgramInstruction::unpack(&data), Ok(program_instruction));
+    }
+
+    // Data of earlier clients, the tag without the version byte, decodes as
+    // the same instruction
+    assert_eq!(
+        ProgramInstruction::unpack(&[1, 3, 0, 0, 0, b'k', b'e', b'y', 0, 1, 0, 0, 0, b'v']),
+        Ok(ProgramInstruction::MintToAccount(
+            "key".to_string(),
+            Value::from("v")
+        ))
+    );
+
+    // Unknown tags and versions
+    for data in [vec![100], vec![128, 100], vec![129, 0], vec![128]] {
+        assert_eq!(
+            ProgramInstruction::unpack(&data),
+            Err(SampleError::InvalidInstruction.into())
+        );
+    }
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(&program_id, &[account_pubkey]).await;
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // An earlier client's mint is processed as before
+    let mint = instruction::mint_to_account(
+        &program_id,
+        &account_pubkey,
+        &payer.pubkey(),
+        &[],
+        "key",
+        &Value::from("v"),
+    );
+    let result = submit_txn(
+        Instruction {
+            data: mint.data[1..].to_vec(),
+            ..mint
+        },
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let AccountStateData { btree_storage, .. } =
+        match banks_client.get_account(account_pubkey).await.unwrap() {
+            Some(account) => unpack_from_slice(&account.data).unwrap(),
+            None => panic!(),
+        };
+    assert_eq!(btree_storage.get("key"), Some(&Value::from("v")));
+}
+
+#[tokio::test]
+/// Event wire format test
+async fn test_event_wire_format_pass() {
+    let account = Pubkey::new_from_array([1; 32]);
+    let authority = Pubkey::new_from_array([2; 32]);
+    let with_header = |tag: u8, fields: &[&[u8]]| {
+        let mut data = vec![EVENT_VERSION, tag];
+        fields.iter().for_each(|field| data.extend(*field));
+        data
+    };
+
+    // The version byte, the fixed tag and the borsh encoded fields
+    let golden: [(ProgramEvent, Vec<u8>); 4] = [
+        (
+            ProgramEvent::AccountInitialized { account, authority },
+            with_header(0, &[&[1; 32], &[2; 32]]),
+        ),
+        (
+            ProgramEvent::Burned {
+                account,
+                key: "k".to_string(),
+            },
+            with_header(3, &[&[1; 32], &[1, 0, 0, 0, b'k']]),
+        ),
+        (
+            ProgramEvent::AccountFrozen { account },
+            with_header(16, &[&[1; 32]]),
+        ),
+        (
+            ProgramEvent::KeyValueLimitsSet {
+                limits: KeyValueLimits {
+                    max_key_len: 1,
+                    max_value_len: 2,
+                    max_entries: 3,
+                },
+            },
+            with_header(
+                26,
+                &[
+                    &1_u64.to_le_bytes(),
+                    &2_u64.to_le_bytes(),
+                    &3_u64.to_le_bytes(),
+                ],
+            ),
+        ),
+    ];
+    assert_eq!(EVENT_VERSION, 1);
+    for (event, data) in golden {
+        assert_eq!(event.try_to_vec().unwrap(), data);
+        assert_eq!(ProgramEvent::try_from_slice(&data).unwrap(), event);
+    }
+
+    // Unknown tags and versions
+    for data in [vec![EVENT_VERSION, 100], vec![2, 0], vec![EVENT_VERSION]] {
+        assert!(ProgramEvent::try_from_slice(&data).is_err());
+    }
+}
+
+#[tokio::test]
+/// Account lists of the TypeScript client test
+async fn test_legacy_client_accounts_pass() {
+    let program_id = Pubkey::new_unique();
+    let from_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let (config_pubkey, _) = find_config_address(&program_id);
+    let (registry_pubkey, _) = find_registry_address(&program_id);
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[from_pubkey, to_pubkey]).await;
+    for account in [from_pubkey, to_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_account(&program_id, &account, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // The client sends the tag without the version byte
+    let legacy_data = |instruction: Instruction| instruction.data[1..].to_vec();
+    let mint_data = legacy_data(instruction::mint_to_account(
+        &program_id,
+        &from_pubkey,
+        &payer.pubkey(),
+        &[],
+        "key_1",
+        &Value::from("value_1"),
+    ));
+
+    // The account lists it sent before the config and registry accounts were
+    // required are refused, so the pause switch can not be bypassed
+    let result = submit_txn(
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(from_pubkey, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+            data: mint_data.clone(),
+        },
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::InvalidConfigAccount)
+    );
+
+    // The account lists it sends now, mintKV, transferKV and burnK of
+    // client/lib.ts
+    for (accounts, data) in [
+        (
+            vec![
+                AccountMeta::new_readonly(config_pubkey, false),
+                AccountMeta::new(from_pubkey, false),
+                AccountMeta::new(registry_pubkey, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+            mint_data,
+        ),
+        (
+            vec![
+                AccountMeta::new_readonly(config_pubkey, false),
+                AccountMeta::new(from_pubkey, false),
+                AccountMeta::new(to_pubkey, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+            legacy_data(instruction::transfer_between_accounts(
+                &program_id,
+                &from_pubkey,
+                &to_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+            )),
+        ),
+        (
+            vec![
+                AccountMeta::new_readonly(config_pubkey, false),
+                AccountMeta::new(to_pubkey, false),
+                AccountMeta::new(registry_pubkey, false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+            ],
+            legacy_data(instruction::burn_from_account(
+                &program_id,
+                &to_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+            )),
+        ),
+    ] {
+        let result = submit_txn(
+            Instruction {
+                program_id,
+                accounts,
+                data,
+            },
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+}
+
+#[tokio::test]
+/// Key/value limits test
+async fn test_key_value_limits_fail() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let full_pubkey = Pubkey::new_unique();
+    let authority = Keypair::new();
+
+    // An empty account and an account holding as many key/value pairs as the
+    // limit allows
+    let mut account_data = vec![0_u8; ACCOUNT_STATE_SPACE];
+    pack_into_slice(
+        &AccountStateData {
+            is_initialized: true,
+            authority: authority.pubkey(),
+            ..AccountStateData::default()
+        },
+        &mut account_data,
+    )
+    .unwrap();
+    let mut full_data = vec![0_u8; ACCOUNT_STATE_SPACE * 4];
+    pack_into_slice(
+        &AccountStateData {
+            is_initialized: true,
+            authority: authority.pubkey(),
+            btree_storage: (0..KEY_VALUE_LIMITS.max_entries)
+                .map(|index| (format!("key_{:03}", index), Value::U64(index as u64)))
+                .collect(),
+            ..AccountStateData::default()
+        },
+        &mut full_data,
+    )
+    .unwrap();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup_with_data(
+        &program_id,
+        &[(account_pubkey, account_data), (full_pubkey, full_data)],
+    )
+    .await;
+
+    // Each limit fails with its own error
+    let value = Value::from("value");
+    for (account, key, value, error) in [
+        (
+            account_pubkey,
+            String::new(),
+            value.clone(),
+            SampleError::EmptyKey,
+        ),
+        (
+            account_pubkey,
+            "k".repeat(KEY_VALUE_LIMITS.max_key_len + 1),
+            value.clone(),
+            SampleError::KeyTooLong,
+        ),
+        (
+            account_pubkey,
+            String::from("key\n"),
+            value.clone(),
+            SampleError::InvalidKeyCharacter,
+        ),
+        (
+            account_pubkey,
+            String::from("key"),
+            Value::from("v".repeat(KEY_VALUE_LIMITS.max_value_len)),
+            SampleError::ValueTooLong,
+        ),
+        (
+            full_pubkey,
+            String::from("key"),
+            value.clone(),
+            SampleError::TooManyEntries,
+        ),
+    ] {
+        let result = submit_txn_with_signers(
+            instruction::mint_to_account(
+                &program_id,
+                &account,
+                &authority.pubkey(),
+                &[],
+                &key,
+                &value,
+            ),
+            &[&payer, &authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(sample_error_of(result), Some(error));
+    }
+
+    // Keys and values at the limits are accepted, and replacing a value of
+    // the full account does not add a key/value pair
+    let result = submit_txn_with_signers(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            &"k".repeat(KEY_VALUE_LIMITS.max_key_len),
+            &Value::from("v".repeat(KEY_VALUE_LIMITS.max_value_len - 5)),
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn_with_signers(
+        instruction::upsert_value(
+            &program_id,
+            &full_pubkey,
+            &authority.pubkey(),
+            &[],
+            "key_000",
+            &value,
+        ),
+        &[&payer, &authority],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_freeze_lock_pass() {
+    for zero_copy in [false, true] {
+        let program_id = Pubkey::new_unique();
+        let from_pubkey = Pubkey::new_unique();
+        let to_pubkey = Pubkey::new_unique();
+        let freeze_authority = Keypair::new();
+
+        // Setup runtime testing and accounts, of the borsh and then the
+        // zero-copy layout
+        let (mut banks_client, payer, recent_blockhash) =
+            setup(&program_id, &[from_pubkey, to_pubkey]).await;
+        for account in [from_pubkey, to_pubkey] {
+            let result = submit_txn(
+                match zero_copy {
+                    true => instruction::initialize_zero_copy_account(
+                        &program_id,
+                        &account,
+                        &payer.pubkey(),
+                    ),
+                    false => {
+                        instruction::initialize_account(&program_id, &account, &payer.pubkey())
+                    }
+                },
+                &payer,
+                recent_blockhash,
+                &mut banks_client,
+            )
+            .await;
+            assert!(result.is_ok());
+        }
+        let key_values: Vec<(String, Value)> = (1..4)
+            .map(|i| (format!("key_{}", i), Value::from(format!("value_{}", i))))
+            .collect();
+        let result = submit_txn(
+            instruction::mint_many(&program_id, &from_pubkey, &payer.pubkey(), &[], &key_values),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        // The authority is the freeze authority until it hands it over
+        let result = submit_txn(
+            instruction::set_freeze_authority(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                &freeze_authority.pubkey(),
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        let result = submit_txn(
+            instruction::lock_key(&program_id, &from_pubkey, &payer.pubkey(), &[], "key_1"),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::InvalidFreezeAuthority)
+        );
+
+        // Fail when locking a key that does not exist
+        let result = submit_txn_with_signers(
+            instruction::lock_key(
+                &program_id,
+                &from_pubkey,
+                &freeze_authority.pubkey(),
+                &[],
+                "key_4",
+            ),
+            &[&payer, &freeze_authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::KeyNotFoundInAccount)
+        );
+
+        // Locked keys may not be transferred, burned or updated
+        let result = submit_txn_with_signers(
+            instruction::lock_key(
+                &program_id,
+                &from_pubkey,
+                &freeze_authority.pubkey(),
+                &[],
+                "key_1",
+            ),
+            &[&payer, &freeze_authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        for instruction in [
+            instruction::transfer_between_accounts(
+                &program_id,
+                &from_pubkey,
+                &to_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+            ),
+            instruction::burn_from_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+            ),
+            instruction::update_value(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+                &"updated".into(),
+            ),
+            instruction::close_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &[],
+                false,
+            ),
+        ] {
+            let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+            assert_eq!(sample_error_of(result), Some(SampleError::KeyLocked));
+        }
+        let AccountStateData {
+            btree_storage: btree_map,
+            freeze_authority: stored_freeze_authority,
+            locked_keys,
+            ..
+        } = match banks_client.get_account(from_pubkey).await.unwrap() {
+            Some(account) => unpack_from_slice(&account.data).unwrap(),
+            None => panic!(),
+        };
+        assert_eq!(btree_map.get("key_1").unwrap(), "value_1");
+        assert_eq!(stored_freeze_authority, Some(freeze_authority.pubkey()));
+        assert!(locked_keys.contains("key_1"));
+
+        // Other keys are unaffected by the lock
+        let result = submit_txn(
+            instruction::burn_from_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_2",
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        // A frozen account fails every change to its key/value pairs
+        let result = submit_txn_with_signers(
+            instruction::freeze_account(&program_id, &from_pubkey, &freeze_authority.pubkey(), &[]),
+            &[&payer, &freeze_authority],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        for instruction in [
+            instruction::mint_to_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_4",
+                &"value_4".into(),
+            ),
+            instruction::burn_from_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_3",
+            ),
+            instruction::upsert_value(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_3",
+                &"updated".into(),
+            ),
+            instruction::approve(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_3",
+                &Pubkey::new_unique(),
+            ),
+        ] {
+            let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+            assert_eq!(sample_error_of(result), Some(SampleError::AccountFrozen));
+        }
+
+        // Thawing and unlocking restores the account and the key
+        for instruction in [
+            instruction::thaw_account(&program_id, &from_pubkey, &freeze_authority.pubkey(), &[]),
+            instruction::unlock_key(
+                &program_id,
+                &from_pubkey,
+                &freeze_authority.pubkey(),
+                &[],
+                "key_1",
+            ),
+        ] {
+            let result = submit_txn_with_signers(
+                instruction,
+                &[&payer, &freeze_authority],
+                recent_blockhash,
+                &mut banks_client,
+            )
+            .await;
+            assert!(result.is_ok());
+        }
+        let result = submit_txn(
+            instruction::burn_from_account(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_1",
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        let AccountStateData {
+            btree_storage: btree_map,
+            frozen,
+            locked_keys,
+            ..
+        } = match banks_client.get_account(from_pubkey).await.unwrap() {
+            Some(account) => unpack_from_slice(&account.data).unwrap(),
+            None => panic!(),
+        };
+        assert_eq!(btree_map.len(), 1);
+        assert!(btree_map.contains_key("key_3"));
+        assert!(!frozen);
+        assert!(locked_keys.is_empty());
+    }
+}
+
+/// Creates the multisig account, owned by the program, and initializes it
+/// with `m` of the signers in the same transaction
+async fn create_multisig(
+    program_id: &Pubkey,
+    multisig: &Keypair,
+    m: u8,
+    signers: &[Pubkey],
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    banks_client: &mut BanksClient,
+) -> Result<(), BanksClientError> {
+    let rent = banks_client.get_rent().await?;
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &multisig.pubkey(),
+                rent.minimum_balance(MULTISIG_STATE_SPACE),
+                MULTISIG_STATE_SPACE as u64,
+                program_id,
+            ),
+            instruction::initialize_multisig(program_id, &multisig.pubkey(), signers, m),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, multisig], recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+/// Multisig authority test
+async fn test_multisig_authority_pass() {
+    let program_id = Pubkey::new_unique();
+    let from_pubkey = Pubkey::new_unique();
+    let to_pubkey = Pubkey::new_unique();
+    let multisig = Keypair::new();
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let signer_pubkeys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[from_pubkey, to_pubkey]).await;
+    for account in [from_pubkey, to_pubkey] {
+        let result = submit_txn(
+            instruction::initialize_account(&program_id, &account, &payer.pubkey()),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Fail a threshold greater than the number of signers and a signer listed
+    // twice, which one signature would count for twice, then create a 2 of 3
+    // multisig
+    let result = create_multisig(
+        &program_id,
+        &multisig,
+        4,
+        &signer_pubkeys,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidMultisig));
+    let result = create_multisig(
+        &program_id,
+        &multisig,
+        2,
+        &[signer_pubkeys[0], signer_pubkeys[1], signer_pubkeys[0]],
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidMultisig));
+    let result = create_multisig(
+        &program_id,
+        &multisig,
+        2,
+        &signer_pubkeys,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let multisig_state = match banks_client.get_account(multisig.pubkey()).await.unwrap() {
+        Some(account) => ProgramMultisigState::unpack(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(multisig_state.m(), 2);
+    assert_eq!(multisig_state.signers(), signer_pubkeys.as_slice());
+
+    // Hand the from account to the multisig, after which its previous
+    // authority may no longer mint
+    let result = submit_txn(
+        instruction::set_authority(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            &multisig.pubkey(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"value_1".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidAuthority));
+
+    // Mint, transfer and burn fail with fewer than 2 of the signers and
+    // pass with 2 of them. Signers not of the multisig do not count
+    let outsider = Keypair::new();
+    for step in ["mint", "transfer", "burn"] {
+        let multisig_instruction = |signer_pubkeys: &[Pubkey]| match step {
+            "mint" => instruction::mint_many(
+                &program_id,
+                &from_pubkey,
+                &multisig.pubkey(),
+                signer_pubkeys,
+                &[
+                    ("key_1".to_string(), "value_1".into()),
+                    ("key_2".to_string(), "value_2".into()),
+                ],
+            ),
+            "transfer" => instruction::transfer_between_accounts(
+                &program_id,
+                &from_pubkey,
+                &to_pubkey,
+                &multisig.pubkey(),
+                signer_pubkeys,
+                "key_1",
+            ),
+            _ => instruction::burn_from_account(
+                &program_id,
+                &from_pubkey,
+                &multisig.pubkey(),
+                signer_pubkeys,
+                "key_2",
+            ),
+        };
+        let result = submit_txn_with_signers(
+            multisig_instruction(&[signers[0].pubkey(), outsider.pubkey()]),
+            &[&payer, &signers[0], &outsider],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::MissingMultisigSignatures)
+        );
+        let result = submit_txn_with_signers(
+            multisig_instruction(&[signers[0].pubkey(), signers[2].pubkey()]),
+            &[&payer, &signers[0], &signers[2]],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    let from_state: AccountStateData = match banks_client.get_account(from_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert!(from_state.btree_storage.is_empty());
+    assert_eq!(from_state.authority, multisig.pubkey());
+    let to_state: AccountStateData = match banks_client.get_account(to_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(to_state.btree_storage.get("key_1").unwrap(), "value_1");
+
+    // A multisig holds no lamports, so a separate funder pays for growing
+    // the account and is refunded when it shrinks
+    for size in [ACCOUNT_STATE_SPACE * 2, ACCOUNT_STATE_SPACE] {
+        let resize = |signer_pubkeys: &[Pubkey]| {
+            instruction::resize(
+                &program_id,
+                &from_pubkey,
+                &payer.pubkey(),
+                &multisig.pubkey(),
+                signer_pubkeys,
+                size as u64,
+            )
+        };
+        let result = submit_txn_with_signers(
+            resize(&[signers[0].pubkey()]),
+            &[&payer, &signers[0]],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::MissingMultisigSignatures)
+        );
+        let result = submit_txn_with_signers(
+            resize(&[signers[0].pubkey(), signers[1].pubkey()]),
+            &[&payer, &signers[0], &signers[1]],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        let account = banks_client
+            .get_account(from_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.data.len(), size);
+    }
+
+    // A swap checks the multisig authority of its side like any other
+    // instruction, the wallet authority of the other side signing as usual
+    let result = submit_txn_with_signers(
+        instruction::mint_to_account(
+            &program_id,
+            &from_pubkey,
+            &multisig.pubkey(),
+            &[signers[1].pubkey(), signers[2].pubkey()],
+            "key_3",
+            &"value_3".into(),
+        ),
+        &[&payer, &signers[1], &signers[2]],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let swap = |signer_pubkeys: &[Pubkey]| {
+        instruction::swap_keys(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &multisig.pubkey(),
+            signer_pubkeys,
+            &payer.pubkey(),
+            &[],
+            "key_3",
+            "key_1",
+        )
+    };
+    let result = submit_txn_with_signers(
+        swap(&[signers[0].pubkey(), outsider.pubkey()]),
+        &[&payer, &signers[0], &outsider],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::MissingMultisigSignatures)
+    );
+    let result = submit_txn_with_signers(
+        swap(&[signers[0].pubkey(), signers[2].pubkey()]),
+        &[&payer, &signers[0], &signers[2]],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let from_state: AccountStateData = match banks_client.get_account(from_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(from_state.btree_storage.get("key_1").unwrap(), "value_1");
+    let to_state: AccountStateData = match banks_client.get_account(to_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(to_state.btree_storage.get("key_3").unwrap(), "value_3");
+
+    // The other side's authority may also be one of the multisig signers
+    let result = submit_txn(
+        instruction::set_authority(
+            &program_id,
+            &to_pubkey,
+            &payer.pubkey(),
+            &[],
+            &signers[0].pubkey(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn_with_signers(
+        instruction::swap_keys(
+            &program_id,
+            &from_pubkey,
+            &to_pubkey,
+            &multisig.pubkey(),
+            &[signers[0].pubkey(), signers[1].pubkey()],
+            &signers[0].pubkey(),
+            &[],
+            "key_1",
+            "key_3",
+        ),
+        &[&payer, &signers[0], &signers[1]],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let from_state: AccountStateData = match banks_client.get_account(from_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(from_state.btree_storage.get("key_3").unwrap(), "value_3");
+
+    // The freeze authority defaults to the multisig authority, so freezing
+    // and thawing need the multisig's threshold of signers as well
+    let result = submit_txn_with_signers(
+        instruction::freeze_account(
+            &program_id,
+            &from_pubkey,
+            &multisig.pubkey(),
+            &[signers[0].pubkey()],
+        ),
+        &[&payer, &signers[0]],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::MissingMultisigSignatures)
+    );
+    for instruction in [
+        instruction::freeze_account(
+            &program_id,
+            &from_pubkey,
+            &multisig.pubkey(),
+            &[signers[0].pubkey(), signers[1].pubkey()],
+        ),
+        instruction::thaw_account(
+            &program_id,
+            &from_pubkey,
+            &multisig.pubkey(),
+            &[signers[0].pubkey(), signers[1].pubkey()],
+        ),
+    ] {
+        let result = submit_txn_with_signers(
+            instruction,
+            &[&payer, &signers[0], &signers[1]],
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+}
+
+/// Seed of the caller programs derived address, the authority of the
+/// accounts it drives
+const CALLER_SEED: &[u8] = b"caller";
+
+/// Instructions of the caller program
+#[derive(BorshDeserialize, BorshSerialize)]
+enum CallerInstruction {
+    Initialize,
+    Mint(String, Value),
+    Burn(String),
+}
+
+/// A small program that drives the key/value store through the cpi module,
+/// signing as the authority with its derived address. Accounts are the key/
+/// value program, its config and registry, the account and the derived
+/// authority
+fn process_caller(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let program = next_account_info(account_info_iter)?;
+    let config = next_account_info(account_info_iter)?;
+    let registry = next_account_info(account_info_iter)?;
+    let account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let (_, bump) = Pubkey::find_program_address(&[CALLER_SEED], program_id);
+    let signers_seeds: &[&[&[u8]]] = &[&[CALLER_SEED, &[bump]]];
+    let instruction = CallerInstruction::try_from_slice(input)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        CallerInstruction::Initialize => {
+            cpi::initialize_account(program, config, account, authority, registry, signers_seeds)
+        }
+        CallerInstruction::Mint(key, value) => cpi::mint_to_account(
+            program,
+            config,
+            account,
+            registry,
+            authority,
+            &key,
+            &value,
+            signers_seeds,
+        ),
+        CallerInstruction::Burn(key) => cpi::burn_from_account(
+            program,
+            config,
+            account,
+            registry,
+            authority,
+            &key,
+            signers_seeds,
+        ),
+    }
+}
+
+#[tokio::test]
+/// Cross-program invocation test
+async fn test_cpi_caller_pass() {
+    let program_id = Pubkey::new_unique();
+    let caller_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let (authority, _) = Pubkey::find_program_address(&[CALLER_SEED], &caller_id);
+
+    // Setup runtime testing with the caller program alongside
+    let mut program_test = ProgramTest::new(
+        "solana_cli_template_program_bpf",
+        program_id,
+        processor!(process),
+    );
+    program_test.add_builtin_program("cpi_caller", caller_id, processor!(process_caller).unwrap());
+    program_test.add_account(
+        account_pubkey,
+        Account {
+            lamports: 5,
+            data: vec![0_u8; ACCOUNT_STATE_SPACE],
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) =
+        start_with_upgrade_authority(program_test, &program_id).await;
+    let caller_accounts = vec![
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(find_config_address(&program_id).0, false),
+        AccountMeta::new(find_registry_address(&program_id).0, false),
+        AccountMeta::new(account_pubkey, false),
+        AccountMeta::new_readonly(authority, false),
+    ];
+
+    // The caller initializes the account with its derived address as
+    // authority, mints and burns, each signed with its seeds
+    for instruction in [
+        CallerInstruction::Initialize,
+        CallerInstruction::Mint("key_1".to_string(), "value_1".into()),
+        CallerInstruction::Mint("key_2".to_string(), "value_2".into()),
+        CallerInstruction::Burn("key_1".to_string()),
+    ] {
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_borsh(
+                caller_id,
+                &instruction,
+                caller_accounts.clone(),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        let result = banks_client.process_transaction(transaction).await;
+        assert!(result.is_ok());
+    }
+    let AccountStateData {
+        authority: stored_authority,
+        btree_storage: btree_map,
+        ..
+    } = match banks_client.get_account(account_pubkey).await.unwrap() {
+        Some(account) => unpack_from_slice(&account.data).unwrap(),
+        None => panic!(),
+    };
+    assert_eq!(stored_authority, authority);
+    assert_eq!(btree_map.len(), 1);
+    assert_eq!(btree_map.get("key_2").unwrap(), "value_2");
+
+    // The programs errors reach the caller
+    let mut transaction = Transaction::new_with_payer(
+        &[Instruction::new_with_borsh(
+            caller_id,
+            &CallerInstruction::Mint("key_2".to_string(), "value_3".into()),
+            caller_accounts.clone(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(sample_error_of(result), Some(SampleError::KeyAlreadyExists));
+
+    // Only the caller signs for its derived address
+    let result = submit_txn(
+        instruction::burn_from_account(&program_id, &account_pubkey, &payer.pubkey(), &[], "key_2"),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidAuthority));
+
+    // The pause switch holds the caller's instructions
+    for instruction in [
+        instruction::initialize_config(
+            &program_id,
+            &payer.pubkey(),
+            ServiceFees::default(),
+            &Pubkey::new_unique(),
+        ),
+        instruction::pause(&program_id, &payer.pubkey()),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+    let mut transaction = Transaction::new_with_payer(
+        &[Instruction::new_with_borsh(
+            caller_id,
+            &CallerInstruction::Mint("key_3".to_string(), "value_3".into()),
+            caller_accounts,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(sample_error_of(result), Some(SampleError::ProgramPaused));
+}
+
+#[tokio::test]
+/// Read only GetValue and ContainsKey test
+async fn test_get_value_contains_key_pass() {
+    for zero_copy in [false, true] {
+        let program_id = Pubkey::new_unique();
+        let account_pubkey = Pubkey::new_unique();
+
+        // Setup runtime testing and an account, of the borsh and then the
+        // zero-copy layout, holding values whose encodings end in zeros
+        let (mut banks_client, payer, recent_blockhash) =
+            setup(&program_id, &[account_pubkey]).await;
+        let result = submit_txn(
+            match zero_copy {
+                true => instruction::initialize_zero_copy_account(
+                    &program_id,
+                    &account_pubkey,
+                    &payer.pubkey(),
+                ),
+                false => {
+                    instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey())
+                }
+            },
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+        for instruction in [
+            instruction::mint_many(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                &[
+                    ("count".to_string(), Value::from(256_u64)),
+                    ("flag".to_string(), Value::from(false)),
+                ],
+            ),
+            instruction::mint_to_account_with_expiry(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                "expired",
+                &"lease".into(),
+                Expiry::Slot(0),
+            ),
+        ] {
+            let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+            assert!(result.is_ok());
+        }
+        let account_data = banks_client
+            .get_account(account_pubkey)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+
+        // Values are returned as held, and expired or missing keys are not
+        for (key, expected) in [
+            ("count", Some(Value::from(256_u64))),
+            ("flag", Some(Value::from(false))),
+            ("expired", None),
+            ("missing", None),
+        ] {
+            let value: Option<Value> = simulate_return_data(
+                instruction::get_value(&program_id, &account_pubkey, key),
+                &payer,
+                recent_blockhash,
+                &mut banks_client,
+            )
+            .await;
+            assert_eq!(value, expected);
+            let contains_key: bool = simulate_return_data(
+                instruction::contains_key(&program_id, &account_pubkey, key),
+                &payer,
+                recent_blockhash,
+                &mut banks_client,
+            )
+            .await;
+            assert_eq!(contains_key, expected.is_some());
+        }
+
+        // The account is left untouched
+        let account = banks_client.get_account(account_pubkey).await.unwrap();
+        assert_eq!(account.unwrap().data, account_data);
+    }
+}
+
+#[tokio::test]
+async fn test_registry_stats_pass() {
+    let program_id = Pubkey::new_unique();
+    let early_pubkey = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+    let (registry_pubkey, _) = find_registry_address(&program_id);
+    let fees = ServiceFees::default();
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) = setup(
+        &program_id,
+        &[early_pubkey, account_pubkey, treasury_pubkey],
+    )
+    .await;
+    let result = fund_rent_exempt(
+        &[account_pubkey, treasury_pubkey],
+        1_000,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Instructions passed the registry before it is created leave it be
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &early_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &early_pubkey,
+            &payer.pubkey(),
+            &[],
+            "early",
+            &"value".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail to create the registry at any other address
+    let result = submit_txn(
+        with_account_replaced(
+            instruction::initialize_registry(&program_id, &payer.pubkey()),
+            &registry_pubkey,
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::InvalidRegistryAccount)
+    );
+
+    // Create the registry, once
+    let result = submit_txn(
+        instruction::initialize_registry(&program_id, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::initialize_registry(&program_id, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(
+        sample_error_of(result),
+        Some(SampleError::AlreadyInitializedState)
+    );
+
+    // Initialize, mint and burn with the registry, the registry following
+    // the instructions accounts and preceding the authority
+    let result = submit_txn(
+        instruction::initialize_account(&program_id, &account_pubkey, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::initialize_config(&program_id, &payer.pubkey(), fees, &treasury_pubkey),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    for instruction in [
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"value_1".into(),
+        ),
+        instruction::mint_many(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &[
+                ("key_2".to_string(), "value_2".into()),
+                ("key_3".to_string(), "value_3".into()),
+            ],
+        ),
+        instruction::burn_many(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            &["key_2".to_string(), "key_3".to_string()],
+        ),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+    let result = submit_txn(
+        instruction::burn_from_account_with_fee(
+            &program_id,
+            &account_pubkey,
+            &treasury_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Upserting a new key counts as minted and updating one does not
+    for value in ["value_4", "value_5"] {
+        let result = submit_txn(
+            instruction::upsert_value(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_4",
+                &value.into(),
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    // Purging an expired key counts as burned
+    for instruction in [
+        instruction::mint_to_account_with_expiry(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "expired",
+            &"value".into(),
+            Expiry::Slot(0),
+        ),
+        instruction::purge_expired(&program_id, &account_pubkey),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert!(result.is_ok());
+    }
+
+    // Fail the counted instructions when the registry is left out or another
+    // account is passed in its place
+    let mint = instruction::mint_to_account(
+        &program_id,
+        &account_pubkey,
+        &payer.pubkey(),
+        &[],
+        "key_5",
+        &"value_5".into(),
+    );
+    let mut unregistered_mint = mint.clone();
+    unregistered_mint
+        .accounts
+        .retain(|account| account.pubkey != registry_pubkey);
+    for instruction in [
+        unregistered_mint,
+        with_account_replaced(
+            mint,
+            &registry_pubkey,
+            AccountMeta::new(early_pubkey, false),
+        ),
+        with_account_replaced(
+            instruction::upsert_value(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                "key_5",
+                &"value_5".into(),
+            ),
+            &registry_pubkey,
+            AccountMeta::new(early_pubkey, false),
+        ),
+        with_account_replaced(
+            instruction::close_account(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &[],
+                false,
+            ),
+            &registry_pubkey,
+            AccountMeta::new(early_pubkey, false),
+        ),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::InvalidRegistryAccount)
+        );
+    }
+
+    // Closing the account counts the keys it held as burned
+    let result = submit_txn(
+        instruction::close_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[],
+            false,
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    let registry = banks_client.get_account(registry_pubkey).await.unwrap();
+    let registry_state = ProgramRegistryState::unpack(&registry.unwrap().data).unwrap();
+    assert_eq!(registry_state.accounts_initialized(), 1);
+    assert_eq!(registry_state.keys_minted(), 5);
+    assert_eq!(registry_state.keys_burned(), 5);
+    assert_eq!(registry_state.fees_collected(), fees.burning);
+}
+
+#[tokio::test]
+/// Pause switch test
+async fn test_pause_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+    let (config_pubkey, _) = find_config_address(&program_id);
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, treasury_pubkey]).await;
+    let result = initialize_with_config(
+        &program_id,
+        &[account_pubkey],
+        ServiceFees::default(),
+        &treasury_pubkey,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &"value_1".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail when the signer is not the admin
+    let not_admin = Keypair::new();
+    let result = submit_txn_with_signers(
+        instruction::pause(&program_id, &not_admin.pubkey()),
+        &[&payer, &not_admin],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidAuthority));
+
+    let result = submit_txn(
+        instruction::pause(&program_id, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let config = banks_client.get_account(config_pubkey).await.unwrap();
+    assert!(ProgramConfigState::unpack(&config.unwrap().data)
+        .unwrap()
+        .is_paused());
+
+    // Mutations fail while paused
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_2",
+            &"value_2".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::ProgramPaused));
+    let result = submit_txn(
+        instruction::burn_from_account(&program_id, &account_pubkey, &payer.pubkey(), &[], "key_1"),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::ProgramPaused));
+
+    // Reads and the admin's instructions do not
+    let contains: bool = simulate_return_data(
+        instruction::contains_key(&program_id, &account_pubkey, "key_1"),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(contains);
+    let result = submit_txn(
+        instruction::set_treasury(&program_id, &payer.pubkey(), &treasury_pubkey),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+
+    // Fail when the config account is left out or another account is
+    // passed in its place
+    let mint = instruction::mint_to_account(
+        &program_id,
+        &account_pubkey,
+        &payer.pubkey(),
+        &[],
+        "key_3",
+        &"value_3".into(),
+    );
+    let mut unconfigured_mint = mint.clone();
+    unconfigured_mint
+        .accounts
+        .retain(|account| account.pubkey != config_pubkey);
+    for instruction in [
+        unconfigured_mint,
+        with_account_replaced(
+            mint,
+            &config_pubkey,
+            AccountMeta::new_readonly(treasury_pubkey, false),
+        ),
+    ] {
+        let result = submit_txn(instruction, &payer, recent_blockhash, &mut banks_client).await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::InvalidConfigAccount)
+        );
+    }
+
+    let result = submit_txn(
+        instruction::unpause(&program_id, &payer.pubkey()),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let result = submit_txn(
+        instruction::mint_to_account(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_4",
+            &"value_4".into(),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+/// Configurable key/value limits test
+async fn test_set_key_value_limits_pass() {
+    let program_id = Pubkey::new_unique();
+    let account_pubkey = Pubkey::new_unique();
+    let treasury_pubkey = Pubkey::new_unique();
+    let (config_pubkey, _) = find_config_address(&program_id);
+
+    // Setup runtime testing and accounts
+    let (mut banks_client, payer, recent_blockhash) =
+        setup(&program_id, &[account_pubkey, treasury_pubkey]).await;
+    let result = initialize_with_config(
+        &program_id,
+        &[account_pubkey],
+        ServiceFees::default(),
+        &treasury_pubkey,
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let config = banks_client.get_account(config_pubkey).await.unwrap();
+    assert_eq!(
+        ProgramConfigState::unpack(&config.unwrap().data)
+            .unwrap()
+            .key_value_limits(),
+        KEY_VALUE_LIMITS
+    );
+
+    // Fail when the signer is not the admin
+    let limits = KeyValueLimits {
+        max_key_len: 8,
+        max_value_len: 16,
+        max_entries: 2,
+    };
+    let not_admin = Keypair::new();
+    let result = submit_txn_with_signers(
+        instruction::set_key_value_limits(&program_id, &not_admin.pubkey(), limits),
+        &[&payer, &not_admin],
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::InvalidAuthority));
+
+    // Fail when the limits admit no key/value pair or a key the zero-copy
+    // layout can not store
+    for invalid_limits in [
+        KeyValueLimits {
+            max_entries: 0,
+            ..limits
+        },
+        KeyValueLimits {
+            max_key_len: u16::MAX as usize + 1,
+            ..limits
+        },
+    ] {
+        let result = submit_txn(
+            instruction::set_key_value_limits(&program_id, &payer.pubkey(), invalid_limits),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(
+            sample_error_of(result),
+            Some(SampleError::InvalidKeyValueLimits)
+        );
+    }
+
+    let result = submit_txn(
+        instruction::set_key_value_limits(&program_id, &payer.pubkey(), limits),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert!(result.is_ok());
+    let config = banks_client.get_account(config_pubkey).await.unwrap();
+    assert_eq!(
+        ProgramConfigState::unpack(&config.unwrap().data)
+            .unwrap()
+            .key_value_limits(),
+        limits
+    );
+
+    // The program enforces the limits of the config
+    for (key, value, error) in [
+        (
+            "key_long_1",
+            Value::from("value_1"),
+            Some(SampleError::KeyTooLong),
+        ),
+        (
+            "key_1",
+            Value::from("v".repeat(12)),
+            Some(SampleError::ValueTooLong),
+        ),
+        ("key_1", Value::from("value_1"), None),
+        ("key_2", Value::from("value_2"), None),
+        (
+            "key_3",
+            Value::from("value_3"),
+            Some(SampleError::TooManyEntries),
+        ),
+    ] {
+        let result = submit_txn(
+            instruction::mint_to_account(
+                &program_id,
+                &account_pubkey,
+                &payer.pubkey(),
+                &[],
+                key,
+                &value,
+            ),
+            &payer,
+            recent_blockhash,
+            &mut banks_client,
+        )
+        .await;
+        assert_eq!(sample_error_of(result), error);
+    }
+    let result = submit_txn(
+        instruction::update_value(
+            &program_id,
+            &account_pubkey,
+            &payer.pubkey(),
+            &[],
+            "key_1",
+            &Value::from("v".repeat(12)),
+        ),
+        &payer,
+        recent_blockhash,
+        &mut banks_client,
+    )
+    .await;
+    assert_eq!(sample_error_of(result), Some(SampleError::ValueTooLong));
 }
@@ -0,0 +1,119 @@
+//! error defines the errors of decoding and encoding account state
+
+use {
+    crate::{zero_copy::ZeroCopyError, LayoutVersion},
+    std::{error::Error, fmt},
+};
+
+/// Errors of unpacking and packing account data and of parsing values
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccountStateError {
+    /// The account data is empty
+    EmptyAccountData,
+    /// The account data is smaller than the account state needs
+    AccountDataTooSmall { len: usize, required: usize },
+    /// The content length of the header exceeds the account storage
+    ContentLengthOverflow {
+        content_len: usize,
+        storage_len: usize,
+    },
+    /// Bytes of storage data follow the account state
+    TrailingStorageData(usize),
+    /// The storage data does not decode
+    DeserializationFailure,
+    /// The first byte of account data is not the flag of the layout version
+    LayoutMismatch { flag: u8, version: LayoutVersion },
+    /// The layout version is not one of the known layouts
+    UnrecognizedLayoutVersion(u8),
+    /// The zero-copy account data is inconsistent or can not hold a change
+    ZeroCopy(ZeroCopyError),
+    /// The value type is not one of the value types
+    UnrecognizedValueType(String),
+    /// The value does not parse as a value of the type
+    InvalidValue { type_name: String, value: String },
+    /// The key is empty
+    EmptyKey,
+    /// The key is longer than the limit
+    KeyTooLong { len: usize, max: usize },
+    /// The key holds a control character
+    InvalidKeyCharacter(char),
+    /// The encoded value is longer than the limit
+    ValueTooLong { len: usize, max: usize },
+    /// The account would hold more key/value pairs than the limit
+    TooManyEntries { count: usize, max: usize },
+}
+
+impl fmt::Display for AccountStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountStateError::EmptyAccountData => f.write_str("account data is empty"),
+            AccountStateError::AccountDataTooSmall { len, required } => write!(
+                f,
+                "account data length {} is smaller than the {} bytes required",
+                len, required
+            ),
+            AccountStateError::ContentLengthOverflow {
+                content_len,
+                storage_len,
+            } => write!(
+                f,
+                "content length {} exceeds account storage of {}",
+                content_len, storage_len
+            ),
+            AccountStateError::TrailingStorageData(len) => {
+                write!(f, "{} bytes of storage data follow the account state", len)
+            }
+            AccountStateError::DeserializationFailure => {
+                f.write_str("account storage data does not decode")
+            }
+            AccountStateError::LayoutMismatch { flag, version } => write!(
+                f,
+                "account data flag {} is not of layout version {}",
+                flag, version
+            ),
+            AccountStateError::UnrecognizedLayoutVersion(flag) => {
+                write!(f, "unrecognized layout version {}", flag)
+            }
+            AccountStateError::ZeroCopy(e) => e.fmt(f),
+            AccountStateError::UnrecognizedValueType(type_name) => {
+                write!(f, "unrecognized value type \"{}\"", type_name)
+            }
+            AccountStateError::InvalidValue { type_name, value } => {
+                write!(f, "invalid {} \"{}\"", type_name, value)
+            }
+            AccountStateError::EmptyKey => f.write_str("key is empty"),
+            AccountStateError::KeyTooLong { len, max } => {
+                write!(f, "key length {} exceeds the limit of {}", len, max)
+            }
+            AccountStateError::InvalidKeyCharacter(c) => {
+                write!(f, "key holds the control character {:?}", c)
+            }
+            AccountStateError::ValueTooLong { len, max } => {
+                write!(
+                    f,
+                    "encoded value length {} exceeds the limit of {}",
+                    len, max
+                )
+            }
+            AccountStateError::TooManyEntries { count, max } => write!(
+                f,
+                "{} key/value pairs exceed the limit of {} per account",
+                count, max
+            ),
+        }
+    }
+}
+
+impl Error for AccountStateError {}
+
+impl From<ZeroCopyError> for AccountStateError {
+    fn from(e: ZeroCopyError) -> Self {
+        AccountStateError::ZeroCopy(e)
+    }
+}
+
+impl From<std::io::Error> for AccountStateError {
+    fn from(_: std::io::Error) -> Self {
+        AccountStateError::DeserializationFailure
+    }
+}
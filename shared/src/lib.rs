@@ -3,68 +3,612 @@
 use {
     arrayref::*,
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::program_memory::sol_memcpy,
-    std::{collections::BTreeMap, error::Error},
+    error::AccountStateError,
+    solana_program::{program_memory::sol_memcpy, pubkey::Pubkey},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt,
+    },
 };
 
-/// Initialization flag size for account state
+pub mod error;
+pub mod zero_copy;
+
+/// Layout version size for account state, which is 0 until the account is
+/// initialized
+pub const VERSION_BYTES: usize = 1;
+/// Initialization flag size for account state of the `V0` layout, which the
+/// layout version replaced
 pub const INITIALIZED_BYTES: usize = 1;
-/// Storage for the serialized size of the BTreeMap control
+/// Storage for the pubkey of the accounts authority
+pub const AUTHORITY_BYTES: usize = 32;
+/// Storage for the serialized size of the BTreeMap control, which covers the
+/// key/value BTreeMap and, when there are any, the key delegates and key
+/// expiries BTreeMaps and the freeze and lock controls
 pub const BTREE_LENGTH: usize = 4;
-/// Storage for the serialized BTreeMap container
-pub const BTREE_STORAGE: usize = 1019;
-/// Sum of all account state lengths
-pub const ACCOUNT_STATE_SPACE: usize = INITIALIZED_BYTES + BTREE_LENGTH + BTREE_STORAGE;
+/// Initial storage for the serialized BTreeMap container, accounts may be
+/// resized to hold more or less
+pub const BTREE_STORAGE: usize = 987;
+/// Size of the fixed account state fields preceding the BTreeMap container
+pub const ACCOUNT_STATE_HEADER: usize = VERSION_BYTES + AUTHORITY_BYTES + BTREE_LENGTH;
+/// Sum of all initial account state lengths
+pub const ACCOUNT_STATE_SPACE: usize = ACCOUNT_STATE_HEADER + BTREE_STORAGE;
 
-/// Unpacks the data from slice and return the initialized flag and data content
-#[allow(clippy::ptr_offset_with_cast)]
-pub fn unpack_from_slice(src: &[u8]) -> Result<(bool, BTreeMap<String, String>), Box<dyn Error>> {
-    let src = array_ref![src, 0, ACCOUNT_STATE_SPACE];
-    // Setup pointers to key areas of account state data
-    let (is_initialized_src, data_len_src, data_src) =
-        array_refs![src, INITIALIZED_BYTES, BTREE_LENGTH, BTREE_STORAGE];
-
-    let is_initialized = match is_initialized_src {
-        [0] => false,
-        [1] => true,
-        _ => {
-            return Err(Box::<dyn Error>::from(format!(
-                "unrecognized initialization flag \"{:?}\". in account",
-                is_initialized_src
-            )))
+/// Limits on the key/value pairs of an account, checked by the program as
+/// key/value pairs are added or values replaced and by clients before they
+/// submit a transaction. The admin may replace them in the program config
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeyValueLimits {
+    /// Most bytes of a key
+    pub max_key_len: usize,
+    /// Most bytes of a borsh encoded value
+    pub max_value_len: usize,
+    /// Most key/value pairs of an account
+    pub max_entries: usize,
+}
+
+/// Limits the program enforces until the admin sets others in the program
+/// config, a single key/value pair at the limits fits in the initial account
+/// storage
+pub const KEY_VALUE_LIMITS: KeyValueLimits = KeyValueLimits {
+    max_key_len: 64,
+    max_value_len: 640,
+    max_entries: 128,
+};
+
+impl KeyValueLimits {
+    /// Returns true if the limits admit a key/value pair and a key/value pair
+    /// at the limits can be stored in every layout, the zero-copy layout
+    /// recording key lengths in two bytes and value lengths in four
+    pub fn is_valid(&self) -> bool {
+        self.max_key_len > 0
+            && self.max_key_len <= u16::MAX as usize
+            && self.max_value_len > 0
+            && self.max_value_len <= u32::MAX as usize
+            && self.max_entries > 0
+    }
+
+    /// Checks that the key is not empty, is within the key length limit and
+    /// holds no control characters
+    pub fn check_key(&self, key: &str) -> Result<(), AccountStateError> {
+        if key.is_empty() {
+            return Err(AccountStateError::EmptyKey);
+        }
+        if key.len() > self.max_key_len {
+            return Err(AccountStateError::KeyTooLong {
+                len: key.len(),
+                max: self.max_key_len,
+            });
+        }
+        match key.chars().find(|c| c.is_control()) {
+            Some(c) => Err(AccountStateError::InvalidKeyCharacter(c)),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the encoded value is within the value length limit
+    pub fn check_value(&self, value: &Value) -> Result<(), AccountStateError> {
+        let len = value.try_to_vec()?.len();
+        match len > self.max_value_len {
+            true => Err(AccountStateError::ValueTooLong {
+                len,
+                max: self.max_value_len,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// Checks that an account holding `count` key/value pairs is within the
+    /// entry count limit
+    pub fn check_entry_count(&self, count: usize) -> Result<(), AccountStateError> {
+        match count > self.max_entries {
+            true => Err(AccountStateError::TooManyEntries {
+                count,
+                max: self.max_entries,
+            }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// First byte of uninitialized account data, in every layout
+const UNINITIALIZED_FLAG: u8 = 0;
+/// First byte of initialized account data in the `V0` layout
+const UNVERSIONED_FLAG: u8 = 1;
+
+/// Account data layouts, oldest first
+///
+/// The `V0` layout's first byte is an initialization flag, set to 1 once
+/// initialized. The later layouts store their layout version there instead,
+/// numbered from 2 so that it is never read as the `V0` flag. `ZeroCopy` is an
+/// alternative to the current layout chosen when the account is initialized,
+/// see the `zero_copy` module
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum LayoutVersion {
+    /// Initialization flag followed by the string key/value pairs
+    V0 = 0,
+    /// Layout version and authority followed by the typed key/value pairs,
+    /// the key delegates, the key expiries and the freeze and lock controls
+    V1 = 2,
+    /// Keeps the key/value pairs in place behind a sorted offset table
+    ZeroCopy = 3,
+}
+
+impl LayoutVersion {
+    /// The layout accounts are initialized with and migrated to
+    pub const CURRENT: LayoutVersion = LayoutVersion::V1;
+
+    /// Returns true if the layout is earlier than the current layout, and
+    /// accounts of it need migrating
+    pub fn is_legacy(&self) -> bool {
+        *self < LayoutVersion::CURRENT
+    }
+}
+
+impl fmt::Display for LayoutVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutVersion::V0 => f.write_str("V0"),
+            LayoutVersion::V1 => f.write_str("V1"),
+            LayoutVersion::ZeroCopy => f.write_str("zero-copy"),
+        }
+    }
+}
+
+/// When a key/value pair expires, read against the `Clock` sysvar
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq)]
+pub enum Expiry {
+    /// Expires once the clock reaches the slot
+    Slot(u64),
+    /// Expires once the clock reaches the unix timestamp
+    UnixTimestamp(i64),
+}
+
+impl Expiry {
+    /// Returns true if expired at the clock's slot and unix timestamp
+    pub fn is_expired(&self, slot: u64, unix_timestamp: i64) -> bool {
+        match self {
+            Expiry::Slot(expiry_slot) => slot >= *expiry_slot,
+            Expiry::UnixTimestamp(expiry_timestamp) => unix_timestamp >= *expiry_timestamp,
+        }
+    }
+}
+
+impl fmt::Display for Expiry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expiry::Slot(slot) => write!(f, "slot {}", slot),
+            Expiry::UnixTimestamp(unix_timestamp) => write!(f, "unix time {}", unix_timestamp),
+        }
+    }
+}
+
+/// Typed value of a key/value pair
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    U64(u64),
+    I64(i64),
+    Bytes(Vec<u8>),
+    Pubkey(Pubkey),
+    Bool(bool),
+}
+
+impl Value {
+    /// Returns the name of the value's type, as accepted by `Value::parse`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::U64(_) => "u64",
+            Value::I64(_) => "i64",
+            Value::Bytes(_) => "bytes",
+            Value::Pubkey(_) => "pubkey",
+            Value::Bool(_) => "bool",
+        }
+    }
+
+    /// Parses a value of the named type from its display form, bytes are
+    /// hex encoded with an optional `0x` prefix
+    pub fn parse(type_name: &str, value: &str) -> Result<Value, AccountStateError> {
+        let invalid_value = || AccountStateError::InvalidValue {
+            type_name: type_name.to_string(),
+            value: value.to_string(),
+        };
+        match type_name {
+            "string" => Ok(Value::String(value.to_string())),
+            "u64" => value.parse().map(Value::U64).map_err(|_| invalid_value()),
+            "i64" => value.parse().map(Value::I64).map_err(|_| invalid_value()),
+            "bytes" => decode_hex(value)
+                .map(Value::Bytes)
+                .ok_or_else(invalid_value),
+            "pubkey" => value
+                .parse()
+                .map(Value::Pubkey)
+                .map_err(|_| invalid_value()),
+            "bool" => value.parse().map(Value::Bool).map_err(|_| invalid_value()),
+            _ => Err(AccountStateError::UnrecognizedValueType(
+                type_name.to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{}", value),
+            Value::U64(value) => write!(f, "{}", value),
+            Value::I64(value) => write!(f, "{}", value),
+            Value::Bytes(value) => {
+                write!(f, "0x")?;
+                value.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+            Value::Pubkey(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::U64(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::I64(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl From<Pubkey> for Value {
+    fn from(value: Pubkey) -> Self {
+        Value::Pubkey(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, Value::String(value) if value == other)
+    }
+}
+
+impl PartialEq<String> for Value {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
+/// Decodes a hex string, with an optional `0x` prefix, to bytes
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Account state content of an account data area
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountStateData {
+    pub is_initialized: bool,
+    pub authority: Pubkey,
+    pub btree_storage: BTreeMap<String, Value>,
+    pub delegates: BTreeMap<String, Pubkey>,
+    pub expiries: BTreeMap<String, Expiry>,
+    pub frozen: bool,
+    pub freeze_authority: Option<Pubkey>,
+    pub locked_keys: BTreeSet<String>,
+}
+
+impl AccountStateData {
+    /// Returns the expiry of the keys value, if it expires
+    pub fn expiry(&self, key: &str) -> Option<&Expiry> {
+        self.expiries.get(key)
+    }
+
+    /// Returns the value of the key, treating a key whose expiry the slot or
+    /// the unix timestamp has reached as not held
+    pub fn get_unexpired(&self, key: &str, slot: u64, unix_timestamp: i64) -> Option<&Value> {
+        match self.expiry(key) {
+            Some(expiry) if expiry.is_expired(slot, unix_timestamp) => None,
+            _ => self.btree_storage.get(key),
+        }
+    }
+
+    /// Returns the wallet that may freeze and thaw the account and lock and
+    /// unlock its keys, the authority until another is set
+    pub fn freeze_authority(&self) -> Pubkey {
+        self.freeze_authority.unwrap_or(self.authority)
+    }
+
+    /// Returns true if the account has freeze or lock controls to store
+    fn has_controls(&self) -> bool {
+        self.frozen || self.freeze_authority.is_some() || !self.locked_keys.is_empty()
+    }
+
+    /// Serializes the key/value BTreeMap followed, if there are any, by the
+    /// key delegates, the key expiries and then the freeze and lock controls
+    fn serialize_storage(&self) -> Vec<u8> {
+        let mut storage_data = self.btree_storage.try_to_vec().unwrap();
+        if !self.delegates.is_empty() || !self.expiries.is_empty() || self.has_controls() {
+            storage_data.extend(self.delegates.try_to_vec().unwrap());
+        }
+        if !self.expiries.is_empty() || self.has_controls() {
+            storage_data.extend(self.expiries.try_to_vec().unwrap());
+        }
+        if self.has_controls() {
+            storage_data.extend(self.frozen.try_to_vec().unwrap());
+            storage_data.extend(self.freeze_authority.try_to_vec().unwrap());
+            storage_data.extend(self.locked_keys.try_to_vec().unwrap());
         }
+        storage_data
+    }
+}
+
+/// Returns the account data size needed to hold the account state
+pub fn account_state_space_for(account_state: &AccountStateData) -> usize {
+    ACCOUNT_STATE_HEADER + account_state.serialize_storage().len()
+}
+
+/// Key/value pairs, key delegates and key expiries of the storage data
+type Storage = (
+    BTreeMap<String, Value>,
+    BTreeMap<String, Pubkey>,
+    BTreeMap<String, Expiry>,
+);
+
+/// Frozen flag, freeze authority and locked keys of the storage data
+type Controls = (bool, Option<Pubkey>, BTreeSet<String>);
+
+/// Unpacks the storage data of the current layout. The delegates, then the
+/// expiries, follow the key/value pairs only when there are any and all of the
+/// storage data must be consumed, except for the controls following the
+/// expiries which are returned undecoded
+fn unpack_storage(mut data_src: &[u8]) -> Result<(Storage, &[u8]), AccountStateError> {
+    let btree_storage = if data_src.is_empty() {
+        BTreeMap::<String, Value>::new()
+    } else {
+        BTreeMap::<String, Value>::deserialize(&mut data_src)?
+    };
+    let delegates = if data_src.is_empty() {
+        BTreeMap::<String, Pubkey>::new()
+    } else {
+        BTreeMap::<String, Pubkey>::deserialize(&mut data_src)?
+    };
+    let expiries = if data_src.is_empty() {
+        BTreeMap::<String, Expiry>::new()
+    } else {
+        BTreeMap::<String, Expiry>::deserialize(&mut data_src)?
+    };
+    Ok(((btree_storage, delegates, expiries), data_src))
+}
+
+/// Unpacks the freeze and lock controls following the expiries, which must
+/// consume the storage data
+fn unpack_controls(mut data_src: &[u8]) -> Result<Controls, AccountStateError> {
+    let controls = if data_src.is_empty() {
+        Controls::default()
+    } else {
+        Controls::deserialize(&mut data_src)?
     };
-    // Get current size of content in data area
+    if !data_src.is_empty() {
+        return Err(AccountStateError::TrailingStorageData(data_src.len()));
+    }
+    Ok(controls)
+}
+
+/// Splits the storage data, of the header's data length, from the data
+/// following a header
+fn split_storage(
+    data_len_src: &[u8; BTREE_LENGTH],
+    data_src: &[u8],
+) -> Result<&[u8], AccountStateError> {
     let data_len = u32::from_le_bytes(*data_len_src) as usize;
-    // If emptry, create a default
-    if data_len == 0 {
-        Ok((is_initialized, BTreeMap::<String, String>::new()))
+    if data_len > data_src.len() {
+        return Err(AccountStateError::ContentLengthOverflow {
+            content_len: data_len,
+            storage_len: data_src.len(),
+        });
+    }
+    Ok(&data_src[0..data_len])
+}
+
+/// Unpacks account data of the `V0` layout, which has no authority
+#[allow(clippy::ptr_offset_with_cast)]
+fn unpack_v0_from_slice(src: &[u8]) -> Result<AccountStateData, AccountStateError> {
+    const V0_HEADER: usize = INITIALIZED_BYTES + BTREE_LENGTH;
+    if src.len() < V0_HEADER {
+        return Err(AccountStateError::AccountDataTooSmall {
+            len: src.len(),
+            required: V0_HEADER,
+        });
+    }
+    let (header_src, data_src) = src.split_at(V0_HEADER);
+    let header_src = array_ref![header_src, 0, V0_HEADER];
+    let (is_initialized_src, data_len_src) =
+        array_refs![header_src, INITIALIZED_BYTES, BTREE_LENGTH];
+    let is_initialized = match is_initialized_src[0] {
+        UNINITIALIZED_FLAG => false,
+        UNVERSIONED_FLAG => true,
+        flag => {
+            return Err(AccountStateError::LayoutMismatch {
+                flag,
+                version: LayoutVersion::V0,
+            })
+        }
+    };
+    let mut data_src = split_storage(data_len_src, data_src)?;
+    let btree_storage = if data_src.is_empty() {
+        BTreeMap::<String, String>::new()
     } else {
-        let data_dser = BTreeMap::<String, String>::try_from_slice(&data_src[0..data_len]).unwrap();
-        Ok((is_initialized, data_dser))
+        BTreeMap::<String, String>::deserialize(&mut data_src)?
+    };
+    if !data_src.is_empty() {
+        return Err(AccountStateError::TrailingStorageData(data_src.len()));
+    }
+    Ok(AccountStateData {
+        is_initialized,
+        btree_storage: btree_storage
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect(),
+        ..AccountStateData::default()
+    })
+}
+
+/// Unpacks account data of the `V1` layout
+#[allow(clippy::ptr_offset_with_cast)]
+fn unpack_v1_from_slice(src: &[u8]) -> Result<AccountStateData, AccountStateError> {
+    if src.len() < ACCOUNT_STATE_HEADER {
+        return Err(AccountStateError::AccountDataTooSmall {
+            len: src.len(),
+            required: ACCOUNT_STATE_HEADER,
+        });
+    }
+    let (header_src, data_src) = src.split_at(ACCOUNT_STATE_HEADER);
+    let header_src = array_ref![header_src, 0, ACCOUNT_STATE_HEADER];
+    // Setup pointers to key areas of account state data
+    let (version_src, authority_src, data_len_src) =
+        array_refs![header_src, VERSION_BYTES, AUTHORITY_BYTES, BTREE_LENGTH];
+    let is_initialized = match version_src[0] {
+        UNINITIALIZED_FLAG => false,
+        flag if flag == LayoutVersion::V1 as u8 => true,
+        flag => {
+            return Err(AccountStateError::LayoutMismatch {
+                flag,
+                version: LayoutVersion::V1,
+            })
+        }
+    };
+    let authority = Pubkey::new_from_array(*authority_src);
+    let data_src = split_storage(data_len_src, data_src)?;
+    let ((btree_storage, delegates, expiries), controls_src) = unpack_storage(data_src)?;
+    let (frozen, freeze_authority, locked_keys) = unpack_controls(controls_src)?;
+    Ok(AccountStateData {
+        is_initialized,
+        authority,
+        btree_storage,
+        delegates,
+        expiries,
+        frozen,
+        freeze_authority,
+        locked_keys,
+    })
+}
+
+/// Unpacks account data of the layout version
+pub fn unpack_version_from_slice(
+    version: LayoutVersion,
+    src: &[u8],
+) -> Result<AccountStateData, AccountStateError> {
+    match version {
+        LayoutVersion::V0 => unpack_v0_from_slice(src),
+        LayoutVersion::V1 => unpack_v1_from_slice(src),
+        LayoutVersion::ZeroCopy => {
+            Ok(zero_copy::ZeroCopyAccountState::new(src)?.to_account_state_data()?)
+        }
+    }
+}
+
+/// Returns the layout version of the account data. Uninitialized account data
+/// is of the current layout
+pub fn layout_version_of(src: &[u8]) -> Result<LayoutVersion, AccountStateError> {
+    match src.first() {
+        None => Err(AccountStateError::EmptyAccountData),
+        Some(&UNINITIALIZED_FLAG) => Ok(LayoutVersion::CURRENT),
+        Some(&UNVERSIONED_FLAG) => Ok(LayoutVersion::V0),
+        Some(&flag) if flag == LayoutVersion::V1 as u8 => Ok(LayoutVersion::V1),
+        Some(&flag) if flag == LayoutVersion::ZeroCopy as u8 => Ok(LayoutVersion::ZeroCopy),
+        Some(&flag) => Err(AccountStateError::UnrecognizedLayoutVersion(flag)),
     }
 }
 
-/// Packs the initialized flag and data content into destination slice
+/// Returns true if the account data is of a layout earlier than the current
+/// layout, and needs migrating
+pub fn needs_migration(src: &[u8]) -> bool {
+    src.first() == Some(&UNVERSIONED_FLAG)
+}
+
+/// Unpacks the data from slice, of any layout version, and returns the layout
+/// version and the account state content
+pub fn unpack_versioned_from_slice(
+    src: &[u8],
+) -> Result<(LayoutVersion, AccountStateData), AccountStateError> {
+    let version = layout_version_of(src)?;
+    Ok((version, unpack_version_from_slice(version, src)?))
+}
+
+/// Unpacks the data from slice and return the account state content
+///
+/// The slice may be of any length that holds the account state header and the
+/// serialized BTreeMaps, and must be of the current layout or the zero-copy
+/// layout
+pub fn unpack_from_slice(src: &[u8]) -> Result<AccountStateData, AccountStateError> {
+    match zero_copy::is_zero_copy(src) {
+        true => unpack_version_from_slice(LayoutVersion::ZeroCopy, src),
+        false => unpack_version_from_slice(LayoutVersion::CURRENT, src),
+    }
+}
+
+/// Packs the account state content into destination slice
+///
+/// The slice may be of any length that holds the account state header and the
+/// serialized BTreeMaps, packing fails with `AccountDataTooSmall`, leaving the
+/// slice as is, if it does not
 #[allow(clippy::ptr_offset_with_cast)]
 pub fn pack_into_slice(
-    is_initialized: bool,
-    btree_storage: &BTreeMap<String, String>,
+    account_state: &AccountStateData,
     dst: &mut [u8],
-) {
-    let dst = array_mut_ref![dst, 0, ACCOUNT_STATE_SPACE];
-    // Setup pointers to key areas of account state data
-    let (is_initialized_dst, data_len_dst, data_dst) =
-        mut_array_refs![dst, INITIALIZED_BYTES, BTREE_LENGTH, BTREE_STORAGE];
-    // Set the initialized flag
-    is_initialized_dst[0] = is_initialized as u8;
-    // Store the core data length and serialized content
-    let keyval_store_data = btree_storage.try_to_vec().unwrap();
+) -> Result<(), AccountStateError> {
+    let keyval_store_data = account_state.serialize_storage();
     let data_len = keyval_store_data.len();
-    if data_len < BTREE_STORAGE {
-        data_len_dst[..].copy_from_slice(&(data_len as u32).to_le_bytes());
-        sol_memcpy(data_dst, &keyval_store_data, data_len);
-    } else {
-        panic!();
+    if dst.len() < ACCOUNT_STATE_HEADER + data_len {
+        return Err(AccountStateError::AccountDataTooSmall {
+            len: dst.len(),
+            required: ACCOUNT_STATE_HEADER + data_len,
+        });
     }
+    let (header_dst, data_dst) = dst.split_at_mut(ACCOUNT_STATE_HEADER);
+    let header_dst = array_mut_ref![header_dst, 0, ACCOUNT_STATE_HEADER];
+    // Setup pointers to key areas of account state data
+    let (version_dst, authority_dst, data_len_dst) =
+        mut_array_refs![header_dst, VERSION_BYTES, AUTHORITY_BYTES, BTREE_LENGTH];
+    // Set the layout version, once initialized, and the authority
+    version_dst[0] = match account_state.is_initialized {
+        true => LayoutVersion::CURRENT as u8,
+        false => UNINITIALIZED_FLAG,
+    };
+    authority_dst.copy_from_slice(account_state.authority.as_ref());
+    // Store the core data length and serialized content
+    data_len_dst[..].copy_from_slice(&(data_len as u32).to_le_bytes());
+    sol_memcpy(data_dst, &keyval_store_data, data_len);
+    Ok(())
 }
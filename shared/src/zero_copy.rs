@@ -0,0 +1,646 @@
+//! Zero-copy account data layout
+//!
+//! An alternative to the borsh encoded layout that keeps the key/value pairs
+//! in place in the account data area. Keys are found with a binary search over
+//! a sorted table of entry offsets and inserting or removing a key moves bytes
+//! in the data area, so an instruction no longer decodes and encodes every
+//! key/value pair of the account.
+//!
+//! The header holds the layout version, the authority, the number of entries,
+//! the size of the entry heap, the account flags and the freeze authority,
+//! all zeros until one is set. The offset table follows the header and
+//! grows towards the end of the data area, while the entries are packed at the
+//! end of the data area and grow towards the table. Offsets are measured back
+//! from the end of the data area, so resizing the account only moves the heap.
+//!
+//! Each entry is the key length (u16), the value length (u32) and flags,
+//! followed by the key, the delegate and the expiry when flagged and the borsh
+//! encoded value. A flag also marks locked keys.
+
+use {
+    crate::{AccountStateData, Expiry, LayoutVersion, Value, AUTHORITY_BYTES, VERSION_BYTES},
+    arrayref::*,
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::pubkey::Pubkey,
+    std::{cmp::Ordering, error::Error, fmt},
+};
+
+/// Storage for the number of entries
+pub const ENTRY_COUNT_BYTES: usize = 4;
+/// Storage for the size of the entry heap
+pub const HEAP_SIZE_BYTES: usize = 4;
+/// Storage for the account flags
+pub const ACCOUNT_FLAGS_BYTES: usize = 1;
+/// Storage for the pubkey of the accounts freeze authority
+pub const FREEZE_AUTHORITY_BYTES: usize = 32;
+/// Size of the fixed account state fields preceding the offset table
+pub const ZERO_COPY_HEADER: usize = VERSION_BYTES
+    + AUTHORITY_BYTES
+    + ENTRY_COUNT_BYTES
+    + HEAP_SIZE_BYTES
+    + ACCOUNT_FLAGS_BYTES
+    + FREEZE_AUTHORITY_BYTES;
+/// Storage for each offset of the offset table
+pub const OFFSET_BYTES: usize = 4;
+
+const AUTHORITY_AT: usize = VERSION_BYTES;
+const ENTRY_COUNT_AT: usize = AUTHORITY_AT + AUTHORITY_BYTES;
+const HEAP_SIZE_AT: usize = ENTRY_COUNT_AT + ENTRY_COUNT_BYTES;
+const ACCOUNT_FLAGS_AT: usize = HEAP_SIZE_AT + HEAP_SIZE_BYTES;
+const FREEZE_AUTHORITY_AT: usize = ACCOUNT_FLAGS_AT + ACCOUNT_FLAGS_BYTES;
+
+/// Account flag for a frozen account
+const FROZEN_FLAG: u8 = 1;
+
+const KEY_LEN_BYTES: usize = 2;
+const VALUE_LEN_BYTES: usize = 4;
+const FLAGS_BYTES: usize = 1;
+const ENTRY_HEADER: usize = KEY_LEN_BYTES + VALUE_LEN_BYTES + FLAGS_BYTES;
+const DELEGATE_BYTES: usize = 32;
+const EXPIRY_BYTES: usize = 9;
+
+/// Entry flag for a delegate following the key
+const DELEGATE_FLAG: u8 = 1;
+/// Entry flag for an expiry following the key, and delegate if any
+const EXPIRY_FLAG: u8 = 2;
+/// Entry flag for a locked key
+const LOCKED_FLAG: u8 = 4;
+/// Position of the flags in an entry
+const FLAGS_AT: usize = KEY_LEN_BYTES + VALUE_LEN_BYTES;
+
+/// Errors of operating on zero-copy account data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ZeroCopyError {
+    /// The data area is not of the zero-copy layout or is inconsistent
+    InvalidLayout,
+    /// The key is already in the account
+    KeyAlreadyExists,
+    /// The key is not in the account
+    KeyNotFound,
+    /// The data area has no room for the entry
+    InsufficientSpace,
+    /// The key is longer than an entry can hold
+    KeyTooLong,
+}
+
+impl fmt::Display for ZeroCopyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ZeroCopyError::InvalidLayout => f.write_str("account data is not a zero-copy layout"),
+            ZeroCopyError::KeyAlreadyExists => f.write_str("account already contains key"),
+            ZeroCopyError::KeyNotFound => f.write_str("account does not contain key"),
+            ZeroCopyError::InsufficientSpace => f.write_str("account data has no room for key"),
+            ZeroCopyError::KeyTooLong => f.write_str("key is too long"),
+        }
+    }
+}
+
+impl Error for ZeroCopyError {}
+
+fn read_u16(src: &[u8], at: usize) -> usize {
+    u16::from_le_bytes(*array_ref![src, at, 2]) as usize
+}
+
+fn read_u32(src: &[u8], at: usize) -> usize {
+    u32::from_le_bytes(*array_ref![src, at, 4]) as usize
+}
+
+fn write_u32(dst: &mut [u8], at: usize, value: usize) {
+    array_mut_ref![dst, at, 4].copy_from_slice(&(value as u32).to_le_bytes());
+}
+
+/// Returns true if the account data is of the zero-copy layout
+pub fn is_zero_copy(src: &[u8]) -> bool {
+    src.first() == Some(&(LayoutVersion::ZeroCopy as u8))
+}
+
+/// Moves the entry heap of zero-copy account data ending at `from_end` to end
+/// at `to_end`. Call before shrinking the data area and after growing it.
+/// Account data of other layouts is left as is
+pub fn relocate_entries(
+    data: &mut [u8],
+    from_end: usize,
+    to_end: usize,
+) -> Result<(), ZeroCopyError> {
+    if !is_zero_copy(data) {
+        return Ok(());
+    }
+    if from_end > data.len() || to_end > data.len() {
+        return Err(ZeroCopyError::InvalidLayout);
+    }
+    let account_state = ZeroCopyAccountState::new(&data[..from_end])?;
+    let heap_size = account_state.heap_size();
+    if to_end < account_state.table_end() + heap_size {
+        return Err(ZeroCopyError::InsufficientSpace);
+    }
+    data.copy_within(from_end - heap_size..from_end, to_end - heap_size);
+    if to_end > from_end {
+        data[from_end - heap_size..from_end.min(to_end - heap_size)].fill(0);
+    }
+    Ok(())
+}
+
+/// A key/value pair entry borrowed from zero-copy account data
+#[derive(Clone, Copy, Debug)]
+pub struct Entry<'a> {
+    src: &'a [u8],
+}
+
+impl<'a> Entry<'a> {
+    /// Wraps the entry at the start of `src`, which may run past the entry
+    fn new(src: &'a [u8]) -> Result<Self, ZeroCopyError> {
+        if src.len() < ENTRY_HEADER {
+            return Err(ZeroCopyError::InvalidLayout);
+        }
+        let entry = Entry { src };
+        match entry.len() <= src.len() {
+            true => Ok(entry),
+            false => Err(ZeroCopyError::InvalidLayout),
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        read_u16(self.src, 0)
+    }
+
+    fn value_len(&self) -> usize {
+        read_u32(self.src, KEY_LEN_BYTES)
+    }
+
+    fn flags(&self) -> u8 {
+        self.src[FLAGS_AT]
+    }
+
+    fn delegate_at(&self) -> usize {
+        ENTRY_HEADER + self.key_len()
+    }
+
+    fn expiry_at(&self) -> usize {
+        match self.flags() & DELEGATE_FLAG {
+            0 => self.delegate_at(),
+            _ => self.delegate_at() + DELEGATE_BYTES,
+        }
+    }
+
+    fn value_at(&self) -> usize {
+        match self.flags() & EXPIRY_FLAG {
+            0 => self.expiry_at(),
+            _ => self.expiry_at() + EXPIRY_BYTES,
+        }
+    }
+
+    /// Returns the encoded size of the entry
+    pub fn len(&self) -> usize {
+        self.value_at() + self.value_len()
+    }
+
+    /// Returns true if the entry encodes nothing, which never holds
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the key bytes, which order the entries
+    pub fn key_bytes(&self) -> &'a [u8] {
+        &self.src[ENTRY_HEADER..self.delegate_at()]
+    }
+
+    /// Returns the key
+    pub fn key(&self) -> Result<&'a str, ZeroCopyError> {
+        std::str::from_utf8(self.key_bytes()).map_err(|_| ZeroCopyError::InvalidLayout)
+    }
+
+    /// Returns the delegate approved to transfer the key, if any
+    pub fn delegate(&self) -> Option<Pubkey> {
+        match self.flags() & DELEGATE_FLAG {
+            0 => None,
+            _ => Some(Pubkey::new_from_array(*array_ref![
+                self.src,
+                self.delegate_at(),
+                DELEGATE_BYTES
+            ])),
+        }
+    }
+
+    /// Returns true if the key is locked
+    pub fn is_locked(&self) -> bool {
+        self.flags() & LOCKED_FLAG != 0
+    }
+
+    /// Returns the expiry of the key, if it expires
+    pub fn expiry(&self) -> Result<Option<Expiry>, ZeroCopyError> {
+        match self.flags() & EXPIRY_FLAG {
+            0 => Ok(None),
+            _ => Expiry::try_from_slice(&self.src[self.expiry_at()..self.value_at()])
+                .map(Some)
+                .map_err(|_| ZeroCopyError::InvalidLayout),
+        }
+    }
+
+    /// Decodes the value
+    pub fn value(&self) -> Result<Value, ZeroCopyError> {
+        Value::try_from_slice(&self.src[self.value_at()..self.len()])
+            .map_err(|_| ZeroCopyError::InvalidLayout)
+    }
+}
+
+/// Encodes an entry
+fn encode_entry(
+    key: &[u8],
+    value: &Value,
+    delegate: Option<&Pubkey>,
+    expiry: Option<&Expiry>,
+    locked: bool,
+) -> Result<Vec<u8>, ZeroCopyError> {
+    let key_len = u16::try_from(key.len()).map_err(|_| ZeroCopyError::KeyTooLong)?;
+    let value_data = value
+        .try_to_vec()
+        .map_err(|_| ZeroCopyError::InvalidLayout)?;
+    let mut flags = 0;
+    if delegate.is_some() {
+        flags |= DELEGATE_FLAG;
+    }
+    if expiry.is_some() {
+        flags |= EXPIRY_FLAG;
+    }
+    if locked {
+        flags |= LOCKED_FLAG;
+    }
+    let mut entry = Vec::with_capacity(
+        ENTRY_HEADER + key.len() + DELEGATE_BYTES + EXPIRY_BYTES + value_data.len(),
+    );
+    entry.extend_from_slice(&key_len.to_le_bytes());
+    entry.extend_from_slice(&(value_data.len() as u32).to_le_bytes());
+    entry.push(flags);
+    entry.extend_from_slice(key);
+    if let Some(delegate) = delegate {
+        entry.extend_from_slice(delegate.as_ref());
+    }
+    if let Some(expiry) = expiry {
+        entry.extend(
+            expiry
+                .try_to_vec()
+                .map_err(|_| ZeroCopyError::InvalidLayout)?,
+        );
+    }
+    entry.extend(value_data);
+    Ok(entry)
+}
+
+/// Account state of zero-copy account data, read from and, for mutable data,
+/// written to the data area in place
+#[derive(Debug)]
+pub struct ZeroCopyAccountState<D> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> ZeroCopyAccountState<D> {
+    /// Wraps initialized zero-copy account data
+    pub fn new(data: D) -> Result<Self, ZeroCopyError> {
+        let src = data.as_ref();
+        let data_len = src.len();
+        if !is_zero_copy(src) || data_len < ZERO_COPY_HEADER {
+            return Err(ZeroCopyError::InvalidLayout);
+        }
+        let account_state = ZeroCopyAccountState { data };
+        match account_state.packed_len() <= data_len {
+            true => Ok(account_state),
+            false => Err(ZeroCopyError::InvalidLayout),
+        }
+    }
+
+    /// Returns the wallet that must sign mutations of the account
+    pub fn authority(&self) -> Pubkey {
+        Pubkey::new_from_array(*array_ref![
+            self.data.as_ref(),
+            AUTHORITY_AT,
+            AUTHORITY_BYTES
+        ])
+    }
+
+    /// Returns true if the account is frozen
+    pub fn is_frozen(&self) -> bool {
+        self.data.as_ref()[ACCOUNT_FLAGS_AT] & FROZEN_FLAG != 0
+    }
+
+    /// Returns the freeze authority, if one other than the authority is set
+    pub fn freeze_authority(&self) -> Option<Pubkey> {
+        let freeze_authority = Pubkey::new_from_array(*array_ref![
+            self.data.as_ref(),
+            FREEZE_AUTHORITY_AT,
+            FREEZE_AUTHORITY_BYTES
+        ]);
+        match freeze_authority == Pubkey::default() {
+            true => None,
+            false => Some(freeze_authority),
+        }
+    }
+
+    /// Returns the number of key/value pairs
+    pub fn len(&self) -> usize {
+        read_u32(self.data.as_ref(), ENTRY_COUNT_AT)
+    }
+
+    /// Returns true if the account holds no key/value pairs
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn heap_size(&self) -> usize {
+        read_u32(self.data.as_ref(), HEAP_SIZE_AT)
+    }
+
+    fn table_end(&self) -> usize {
+        ZERO_COPY_HEADER + self.len() * OFFSET_BYTES
+    }
+
+    fn offset_at(&self, index: usize) -> usize {
+        read_u32(self.data.as_ref(), ZERO_COPY_HEADER + index * OFFSET_BYTES)
+    }
+
+    /// Returns the account data size needed to hold the state
+    pub fn packed_len(&self) -> usize {
+        self.table_end() + self.heap_size()
+    }
+
+    /// Returns the unused bytes between the offset table and the entry heap
+    pub fn free_space(&self) -> usize {
+        self.data.as_ref().len() - self.packed_len()
+    }
+
+    /// Returns the entry at the index of the offset table
+    fn entry_at(&self, index: usize) -> Result<Entry<'_>, ZeroCopyError> {
+        let src = self.data.as_ref();
+        let offset = self.offset_at(index);
+        if offset == 0 || offset > self.heap_size() {
+            return Err(ZeroCopyError::InvalidLayout);
+        }
+        Entry::new(&src[src.len() - offset..])
+    }
+
+    /// Binary searches the offset table for the key, returning the index of
+    /// the key or, when not found, the index the key would be inserted at
+    fn search(&self, key: &[u8]) -> Result<Result<usize, usize>, ZeroCopyError> {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.entry_at(mid)?.key_bytes().cmp(key) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+
+    /// Returns the entry of the key, if the account holds the key
+    pub fn get(&self, key: &str) -> Result<Option<Entry<'_>>, ZeroCopyError> {
+        match self.search(key.as_bytes())? {
+            Ok(index) => Ok(Some(self.entry_at(index)?)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns true if the account holds the key
+    pub fn contains_key(&self, key: &str) -> Result<bool, ZeroCopyError> {
+        Ok(self.search(key.as_bytes())?.is_ok())
+    }
+
+    /// Returns the entries in key order
+    pub fn entries(&self) -> impl Iterator<Item = Result<Entry<'_>, ZeroCopyError>> {
+        (0..self.len()).map(move |index| self.entry_at(index))
+    }
+
+    /// Decodes all of the entries into account state content
+    pub fn to_account_state_data(&self) -> Result<AccountStateData, ZeroCopyError> {
+        let mut account_state = AccountStateData {
+            is_initialized: true,
+            authority: self.authority(),
+            frozen: self.is_frozen(),
+            freeze_authority: self.freeze_authority(),
+            ..AccountStateData::default()
+        };
+        for entry in self.entries() {
+            let entry = entry?;
+            let key = entry.key()?.to_string();
+            if let Some(delegate) = entry.delegate() {
+                account_state.delegates.insert(key.clone(), delegate);
+            }
+            if let Some(expiry) = entry.expiry()? {
+                account_state.expiries.insert(key.clone(), expiry);
+            }
+            if entry.is_locked() {
+                account_state.locked_keys.insert(key.clone());
+            }
+            account_state.btree_storage.insert(key, entry.value()?);
+        }
+        Ok(account_state)
+    }
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>> ZeroCopyAccountState<D> {
+    /// Initializes the account data in the zero-copy layout with no
+    /// key/value pairs
+    pub fn initialize(mut data: D, authority: &Pubkey) -> Result<Self, ZeroCopyError> {
+        let dst = data.as_mut();
+        if dst.len() < ZERO_COPY_HEADER {
+            return Err(ZeroCopyError::InsufficientSpace);
+        }
+        dst.fill(0);
+        dst[0] = LayoutVersion::ZeroCopy as u8;
+        let mut account_state = ZeroCopyAccountState { data };
+        account_state.set_authority(authority);
+        Ok(account_state)
+    }
+
+    /// Sets the wallet that must sign mutations of the account
+    pub fn set_authority(&mut self, authority: &Pubkey) {
+        array_mut_ref![self.data.as_mut(), AUTHORITY_AT, AUTHORITY_BYTES]
+            .copy_from_slice(authority.as_ref());
+    }
+
+    /// Sets or clears the frozen account flag
+    pub fn set_frozen(&mut self, frozen: bool) {
+        let flags = &mut self.data.as_mut()[ACCOUNT_FLAGS_AT];
+        match frozen {
+            true => *flags |= FROZEN_FLAG,
+            false => *flags &= !FROZEN_FLAG,
+        }
+    }
+
+    /// Sets or, with `None`, clears the freeze authority
+    pub fn set_freeze_authority(&mut self, freeze_authority: Option<&Pubkey>) {
+        let freeze_authority = freeze_authority.copied().unwrap_or_default();
+        array_mut_ref![
+            self.data.as_mut(),
+            FREEZE_AUTHORITY_AT,
+            FREEZE_AUTHORITY_BYTES
+        ]
+        .copy_from_slice(freeze_authority.as_ref());
+    }
+
+    fn set_len(&mut self, len: usize) {
+        write_u32(self.data.as_mut(), ENTRY_COUNT_AT, len);
+    }
+
+    fn set_heap_size(&mut self, heap_size: usize) {
+        write_u32(self.data.as_mut(), HEAP_SIZE_AT, heap_size);
+    }
+
+    /// Writes the encoded entry to the top of the heap and its offset into the
+    /// offset table at the index
+    fn insert_at(&mut self, index: usize, entry: &[u8]) -> Result<(), ZeroCopyError> {
+        if entry.len() + OFFSET_BYTES > self.free_space() {
+            return Err(ZeroCopyError::InsufficientSpace);
+        }
+        let table_at = ZERO_COPY_HEADER + index * OFFSET_BYTES;
+        let table_end = self.table_end();
+        let len = self.len();
+        let offset = self.heap_size() + entry.len();
+        let dst = self.data.as_mut();
+        let entry_at = dst.len() - offset;
+        dst[entry_at..entry_at + entry.len()].copy_from_slice(entry);
+        dst.copy_within(table_at..table_end, table_at + OFFSET_BYTES);
+        write_u32(dst, table_at, offset);
+        self.set_len(len + 1);
+        self.set_heap_size(offset);
+        Ok(())
+    }
+
+    /// Removes the entry at the index of the offset table, closing the gap it
+    /// leaves in the heap
+    fn remove_at(&mut self, index: usize) -> Result<(), ZeroCopyError> {
+        let entry_len = self.entry_at(index)?.len();
+        let offset = self.offset_at(index);
+        let table_at = ZERO_COPY_HEADER + index * OFFSET_BYTES;
+        let table_end = self.table_end();
+        let len = self.len();
+        let heap_size = self.heap_size();
+        let dst = self.data.as_mut();
+        let heap_at = dst.len() - heap_size;
+        // Entries nearer the table move towards the end to close the gap
+        dst.copy_within(heap_at..dst.len() - offset, heap_at + entry_len);
+        dst[heap_at..heap_at + entry_len].fill(0);
+        for at in (ZERO_COPY_HEADER..table_end).step_by(OFFSET_BYTES) {
+            let moved_offset = read_u32(dst, at);
+            if moved_offset > offset {
+                write_u32(dst, at, moved_offset - entry_len);
+            }
+        }
+        dst.copy_within(table_at + OFFSET_BYTES..table_end, table_at);
+        dst[table_end - OFFSET_BYTES..table_end].fill(0);
+        self.set_len(len - 1);
+        self.set_heap_size(heap_size - entry_len);
+        Ok(())
+    }
+
+    /// Replaces the entry at the index of the offset table, failing without
+    /// change if the data area has no room for the new entry
+    fn replace_at(&mut self, index: usize, entry: &[u8]) -> Result<(), ZeroCopyError> {
+        let current_len = self.entry_at(index)?.len();
+        if entry.len() > self.free_space() + current_len {
+            return Err(ZeroCopyError::InsufficientSpace);
+        }
+        self.remove_at(index)?;
+        self.insert_at(index, entry)
+    }
+
+    /// Inserts a new key/value pair, with an optional delegate and expiry
+    pub fn insert(
+        &mut self,
+        key: &str,
+        value: &Value,
+        delegate: Option<&Pubkey>,
+        expiry: Option<&Expiry>,
+    ) -> Result<(), ZeroCopyError> {
+        match self.search(key.as_bytes())? {
+            Ok(_) => Err(ZeroCopyError::KeyAlreadyExists),
+            Err(index) => {
+                let entry = encode_entry(key.as_bytes(), value, delegate, expiry, false)?;
+                self.insert_at(index, &entry)
+            }
+        }
+    }
+
+    /// Replaces the value of an existing key, keeping its delegate and expiry
+    pub fn update(&mut self, key: &str, value: &Value) -> Result<(), ZeroCopyError> {
+        let index = self
+            .search(key.as_bytes())?
+            .map_err(|_| ZeroCopyError::KeyNotFound)?;
+        let current = self.entry_at(index)?;
+        let entry = encode_entry(
+            key.as_bytes(),
+            value,
+            current.delegate().as_ref(),
+            current.expiry()?.as_ref(),
+            current.is_locked(),
+        )?;
+        self.replace_at(index, &entry)
+    }
+
+    /// Sets or, with `None`, clears the delegate of an existing key
+    pub fn set_delegate(
+        &mut self,
+        key: &str,
+        delegate: Option<&Pubkey>,
+    ) -> Result<(), ZeroCopyError> {
+        let index = self
+            .search(key.as_bytes())?
+            .map_err(|_| ZeroCopyError::KeyNotFound)?;
+        let current = self.entry_at(index)?;
+        let entry = encode_entry(
+            key.as_bytes(),
+            &current.value()?,
+            delegate,
+            current.expiry()?.as_ref(),
+            current.is_locked(),
+        )?;
+        self.replace_at(index, &entry)
+    }
+
+    /// Sets or clears the lock of an existing key, in place
+    pub fn set_locked(&mut self, key: &str, locked: bool) -> Result<(), ZeroCopyError> {
+        let index = self
+            .search(key.as_bytes())?
+            .map_err(|_| ZeroCopyError::KeyNotFound)?;
+        let offset = self.offset_at(index);
+        let dst = self.data.as_mut();
+        let flags_at = dst.len() - offset + FLAGS_AT;
+        match locked {
+            true => dst[flags_at] |= LOCKED_FLAG,
+            false => dst[flags_at] &= !LOCKED_FLAG,
+        }
+        Ok(())
+    }
+
+    /// Removes a key, and its delegate and expiry, and returns the keys value
+    pub fn remove(&mut self, key: &str) -> Result<Value, ZeroCopyError> {
+        let index = self
+            .search(key.as_bytes())?
+            .map_err(|_| ZeroCopyError::KeyNotFound)?;
+        let value = self.entry_at(index)?.value()?;
+        self.remove_at(index)?;
+        Ok(value)
+    }
+
+    /// Removes all unlocked keys expired at the slot and unix timestamp and
+    /// returns how many were removed
+    pub fn remove_expired(
+        &mut self,
+        slot: u64,
+        unix_timestamp: i64,
+    ) -> Result<usize, ZeroCopyError> {
+        let mut removed = 0;
+        let mut index = 0;
+        while index < self.len() {
+            let entry = self.entry_at(index)?;
+            match entry.expiry()? {
+                Some(expiry) if expiry.is_expired(slot, unix_timestamp) && !entry.is_locked() => {
+                    self.remove_at(index)?;
+                    removed += 1;
+                }
+                _ => index += 1,
+            }
+        }
+        Ok(removed)
+    }
+}
@@ -4,9 +4,14 @@ use {
     clap::{
         crate_description, crate_name, crate_version, App, AppSettings, Arg, ArgMatches, SubCommand,
     },
-    solana_clap_utils::input_validators::{is_url_or_moniker, is_valid_pubkey, is_valid_signer},
+    solana_clap_utils::input_validators::{
+        is_parsable, is_url_or_moniker, is_valid_pubkey, is_valid_signer,
+    },
 };
 
+/// Value types accepted by `--type`, bytes are hex encoded
+const VALUE_TYPES: [&str; 6] = ["string", "u64", "i64", "bytes", "pubkey", "bool"];
+
 /// Construct the cli input model and parse command line
 pub fn parse_command_line() -> ArgMatches<'static> {
     App::new(crate_name!())
@@ -54,6 +59,16 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                 .validator(is_url_or_moniker)
                 .help("JSON RPC URL for the cluster [default: value from configuration file]"),
         )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&["display", "json"])
+                .default_value("display")
+                .help("Format of the key/value stores shown"),
+        )
         .subcommand(
             SubCommand::with_name("balance").about("Get balance").arg(
                 Arg::with_name("address")
@@ -66,7 +81,7 @@ pub fn parse_command_line() -> ArgMatches<'static> {
         )
         .subcommand(
             SubCommand::with_name("mint")
-                .about("Mint a new key/value pair to an account")
+                .about("Mint new key/value pairs to an account")
                 .arg(
                     Arg::with_name("to-owner")
                         .display_order(1)
@@ -82,7 +97,7 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                         .display_order(2)
                         .long("key")
                         .short("k")
-                        .required(true)
+                        .required_unless("file")
                         .takes_value(true)
                         .help("The key of key/value pair"),
                 )
@@ -90,14 +105,73 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                     Arg::with_name("value")
                         .display_order(3)
                         .long("value")
-                        .required(true)
+                        .required_unless("file")
                         .min_values(1)
                         .help("The value string of key/value pair"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .display_order(4)
+                        .long("file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&["key", "value"])
+                        .help("File of key/value pairs, one key and its value per line"),
+                )
+                .arg(
+                    Arg::with_name("grow")
+                        .display_order(5)
+                        .long("grow")
+                        .takes_value(false)
+                        .help("Grow the account first if the key/value pair does not fit"),
+                )
+                .arg(
+                    Arg::with_name("expires-slot")
+                        .display_order(6)
+                        .long("expires-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .conflicts_with_all(&["file", "expires-at"])
+                        .help("Slot at which the key/value pair expires"),
+                )
+                .arg(
+                    Arg::with_name("expires-at")
+                        .display_order(7)
+                        .long("expires-at")
+                        .value_name("UNIX_TIMESTAMP")
+                        .takes_value(true)
+                        .validator(is_parsable::<i64>)
+                        .conflicts_with("file")
+                        .help("Unix timestamp at which the key/value pair expires"),
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .display_order(8)
+                        .long("type")
+                        .value_name("TYPE")
+                        .takes_value(true)
+                        .possible_values(&VALUE_TYPES)
+                        .default_value("string")
+                        .help("Type the value, or values in the file, are parsed as"),
+                )
+                .arg(
+                    Arg::with_name("signer")
+                        .display_order(9)
+                        .long("signer")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help(
+                            "Keypair of a signer of the multisig that is the accounts \
+                             authority, may be repeated [default: owners wallet signs]",
+                        ),
                 ),
         )
         .subcommand(
             SubCommand::with_name("transfer")
-                .about("Transfer a key/value pair from one account to another")
+                .about("Transfer key/value pairs from one account to another")
                 .arg(
                     Arg::with_name("from-owner")
                         .display_order(1)
@@ -123,14 +197,38 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                         .display_order(3)
                         .long("key")
                         .short("k")
-                        .required(true)
+                        .required_unless("file")
                         .takes_value(true)
-                        .help("The key of key/value pair to transfer"),
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("The key of key/value pair to transfer, may be repeated"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .display_order(4)
+                        .long("file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with("key")
+                        .help("File of keys to transfer, one key per line"),
+                )
+                .arg(
+                    Arg::with_name("signer")
+                        .display_order(5)
+                        .long("signer")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help(
+                            "Keypair of a signer of the multisig that is the accounts \
+                             authority, may be repeated [default: owners wallet signs]",
+                        ),
                 ),
         )
         .subcommand(
             SubCommand::with_name("burn")
-                .about("Burn (delete) a key/value pair from an account")
+                .about("Burn (delete) key/value pairs from an account")
                 .arg(
                     Arg::with_name("from-owner")
                         .display_order(1)
@@ -141,6 +239,295 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                         .help("Owner to burn key/value from")
                         .possible_values(&["User1", "User2"]),
                 )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required_unless("file")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("The key of key/value pair to burn, may be repeated"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .display_order(3)
+                        .long("file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with("key")
+                        .help("File of keys to burn, one key per line"),
+                )
+                .arg(
+                    Arg::with_name("signer")
+                        .display_order(4)
+                        .long("signer")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help(
+                            "Keypair of a signer of the multisig that is the accounts \
+                             authority, may be repeated [default: owners wallet signs]",
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("update")
+                .about("Update the value of an existing key/value pair in an account")
+                .arg(
+                    Arg::with_name("to-owner")
+                        .display_order(1)
+                        .long("to-owner")
+                        .short("t")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of accounts")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The key of key/value pair to update"),
+                )
+                .arg(
+                    Arg::with_name("value")
+                        .display_order(3)
+                        .long("value")
+                        .required(true)
+                        .min_values(1)
+                        .help("The new value string of key/value pair"),
+                )
+                .arg(
+                    Arg::with_name("upsert")
+                        .display_order(4)
+                        .long("upsert")
+                        .takes_value(false)
+                        .help("Add the key/value pair if the key does not exist"),
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .display_order(5)
+                        .long("type")
+                        .value_name("TYPE")
+                        .takes_value(true)
+                        .possible_values(&VALUE_TYPES)
+                        .default_value("string")
+                        .help("Type the new value is parsed as"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("swap")
+                .about(
+                    "Swap key/value pairs between two accounts. Writes the swap, partially \
+                     signed, to a file or signs and submits a swap from a file",
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner signing the swap")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required_unless("sign")
+                        .takes_value(true)
+                        .help("The key of key/value pair the owner gives"),
+                )
+                .arg(
+                    Arg::with_name("counterparty")
+                        .display_order(3)
+                        .long("counterparty")
+                        .short("c")
+                        .required_unless("sign")
+                        .takes_value(true)
+                        .help("Owner of the account to swap with")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("counterparty-key")
+                        .display_order(4)
+                        .long("counterparty-key")
+                        .required_unless("sign")
+                        .takes_value(true)
+                        .help("The key of key/value pair the counterparty gives"),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .display_order(5)
+                        .long("out")
+                        .value_name("PATH")
+                        .required_unless("sign")
+                        .takes_value(true)
+                        .help("File to write the partially signed swap to"),
+                )
+                .arg(
+                    Arg::with_name("sign")
+                        .display_order(6)
+                        .long("sign")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&["key", "counterparty", "counterparty-key", "out"])
+                        .help("File of a partially signed swap to sign and submit"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("approve")
+                .about("Approve a delegate to transfer a key/value pair from an account")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account holding the key")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The key of key/value pair to delegate"),
+                )
+                .arg(
+                    Arg::with_name("delegate")
+                        .display_order(3)
+                        .long("delegate")
+                        .short("d")
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .help("Wallet approved to transfer the key/value pair"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("revoke")
+                .about("Revoke the delegate of a key/value pair in an account")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account holding the key")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The key of key/value pair to revoke the delegate of"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("freeze")
+                .about("Freeze an account, failing every change to its key/value pairs")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account to freeze")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("freeze-authority")
+                        .display_order(3)
+                        .long("freeze-authority")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Keypair of the accounts freeze authority [default: owners wallet]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("thaw")
+                .about("Thaw a frozen account")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account to thaw")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("freeze-authority")
+                        .display_order(3)
+                        .long("freeze-authority")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Keypair of the accounts freeze authority [default: owners wallet]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lock")
+                .about("Lock a key/value pair in an account against transfers, burns and updates")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account holding the key")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .display_order(2)
+                        .long("key")
+                        .short("k")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The key of key/value pair to lock"),
+                )
+                .arg(
+                    Arg::with_name("freeze-authority")
+                        .display_order(3)
+                        .long("freeze-authority")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Keypair of the accounts freeze authority [default: owners wallet]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("unlock")
+                .about("Unlock a locked key/value pair in an account")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account holding the key")
+                        .possible_values(&["User1", "User2"]),
+                )
                 .arg(
                     Arg::with_name("key")
                         .display_order(2)
@@ -148,7 +535,289 @@ pub fn parse_command_line() -> ArgMatches<'static> {
                         .short("k")
                         .required(true)
                         .takes_value(true)
-                        .help("The key of key/value pair to burn"),
+                        .help("The key of key/value pair to unlock"),
+                )
+                .arg(
+                    Arg::with_name("freeze-authority")
+                        .display_order(3)
+                        .long("freeze-authority")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Keypair of the accounts freeze authority [default: owners wallet]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-freeze-authority")
+                .about("Hand freezing an account and locking its keys to another wallet")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account to set the freeze authority of")
+                        .possible_values(&["User1", "User2"]),
+                )
+                .arg(
+                    Arg::with_name("new-freeze-authority")
+                        .display_order(2)
+                        .long("new-freeze-authority")
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .help("Wallet that becomes the freeze authority"),
+                )
+                .arg(
+                    Arg::with_name("freeze-authority")
+                        .display_order(3)
+                        .long("freeze-authority")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Keypair of the accounts freeze authority [default: owners wallet]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("purge")
+                .about("Purge expired key/value pairs from an account")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account to purge")
+                        .possible_values(&["User1", "User2"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("close")
+                .about("Close an account, reclaiming its rent lamports")
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Owner of account to close")
+                        .possible_values(&["User1", "User2", "Service"]),
+                )
+                .arg(
+                    Arg::with_name("destination")
+                        .display_order(2)
+                        .long("destination")
+                        .short("d")
+                        .validator(is_valid_pubkey)
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .help("Address to send the reclaimed lamports to [default: owners wallet]"),
+                )
+                .arg(
+                    Arg::with_name("require-empty")
+                        .display_order(3)
+                        .long("require-empty")
+                        .takes_value(false)
+                        .help("Fail if the account still contains key/value pairs"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fees")
+                .about("Show or set the service fees in the program config")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(SubCommand::with_name("show").about("Show the service fees"))
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about(
+                            "Set service fees, creating the program config with the \
+                             signer, the program upgrade authority, as admin if needed",
+                        )
+                        .arg(
+                            Arg::with_name("mint")
+                                .display_order(1)
+                                .long("mint")
+                                .value_name("LAMPORTS")
+                                .takes_value(true)
+                                .validator(is_parsable::<u64>)
+                                .help("Fee for minting a key/value pair"),
+                        )
+                        .arg(
+                            Arg::with_name("transfer")
+                                .display_order(2)
+                                .long("transfer")
+                                .value_name("LAMPORTS")
+                                .takes_value(true)
+                                .validator(is_parsable::<u64>)
+                                .help("Fee for transfering a key/value pair"),
+                        )
+                        .arg(
+                            Arg::with_name("burn")
+                                .display_order(3)
+                                .long("burn")
+                                .value_name("LAMPORTS")
+                                .takes_value(true)
+                                .validator(is_parsable::<u64>)
+                                .help("Fee for burning a key/value pair"),
+                        )
+                        .arg(
+                            Arg::with_name("update")
+                                .display_order(4)
+                                .long("update")
+                                .value_name("LAMPORTS")
+                                .takes_value(true)
+                                .validator(is_parsable::<u64>)
+                                .help("Fee for updating or upserting a key/value pair"),
+                        )
+                        .arg(
+                            Arg::with_name("treasury")
+                                .display_order(5)
+                                .long("treasury")
+                                .validator(is_valid_pubkey)
+                                .value_name("ADDRESS")
+                                .takes_value(true)
+                                .help("Program account fees are credited to [default: Service account]"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("admin")
+                .about("Pause, unpause, set the limits of or show the state of the program")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("pause")
+                        .about("Refuse every instruction but the admin's and the read-only ones"),
+                )
+                .subcommand(SubCommand::with_name("unpause").about("Unpause the program"))
+                .subcommand(
+                    SubCommand::with_name("limits")
+                        .about("Set the limits on the key/value pairs of every account")
+                        .arg(
+                            Arg::with_name("max-key-len")
+                                .display_order(1)
+                                .long("max-key-len")
+                                .value_name("BYTES")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Most bytes of a key"),
+                        )
+                        .arg(
+                            Arg::with_name("max-value-len")
+                                .display_order(2)
+                                .long("max-value-len")
+                                .value_name("BYTES")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Most bytes of a borsh encoded value"),
+                        )
+                        .arg(
+                            Arg::with_name("max-entries")
+                                .display_order(3)
+                                .long("max-entries")
+                                .value_name("COUNT")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Most key/value pairs of an account"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("status")
+                        .about("Show whether the program is paused, its admin and its limits"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multisig")
+                .about("Manage M-of-N multisig accounts that may be account authorities")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about(
+                            "Create a multisig, optionally making it the authority of an \
+                             owners account",
+                        )
+                        .arg(
+                            Arg::with_name("threshold")
+                                .display_order(1)
+                                .long("threshold")
+                                .short("m")
+                                .value_name("M")
+                                .required(true)
+                                .takes_value(true)
+                                .validator(is_parsable::<u8>)
+                                .help("Number of the signers that must sign"),
+                        )
+                        .arg(
+                            Arg::with_name("signer")
+                                .display_order(2)
+                                .long("signer")
+                                .value_name("ADDRESS")
+                                .required(true)
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .validator(is_valid_pubkey)
+                                .help("Signer of the multisig, may be repeated"),
+                        )
+                        .arg(
+                            Arg::with_name("owner")
+                                .display_order(3)
+                                .long("owner")
+                                .short("o")
+                                .takes_value(true)
+                                .help("Owner whose account the multisig becomes authority of")
+                                .possible_values(&["User1", "User2"]),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("migrate")
+                .about(
+                    "Show the layout version of the program accounts and migrate those of \
+                     earlier layouts to the current layout",
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .display_order(1)
+                        .long("owner")
+                        .short("o")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("Migrate the accounts of the owner [default: all owners]")
+                        .possible_values(&["User1", "User2", "Service"]),
+                )
+                .arg(
+                    Arg::with_name("legacy-account")
+                        .display_order(2)
+                        .long("legacy-account")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .requires("owner")
+                        .help(
+                            "Keypair of an account of the first layout, which has no \
+                             authority, to migrate with the first owner as its authority",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .display_order(3)
+                        .long("check")
+                        .takes_value(false)
+                        .help("Only show the layout versions"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Show the program wide counters kept in the program registry")
+                .arg(
+                    Arg::with_name("initialize")
+                        .display_order(1)
+                        .long("initialize")
+                        .takes_value(false)
+                        .help("Create the program registry, with the signer paying, if needed"),
                 ),
         )
         .subcommand(SubCommand::with_name("ping").about("Send a ping transaction"))
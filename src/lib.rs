@@ -4,7 +4,7 @@ pub mod utils;
 pub mod prelude {
     pub use crate::utils::{
         account_state::*,
-        keys_db::{KEYS_DB, PROG_KEY},
+        keys_db::{ACCOUNT_SEED, KEYS_DB, PROG_KEY},
         txn_utils::*,
     };
 }
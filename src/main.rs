@@ -1,25 +1,45 @@
 //! @brief Main entry poiint for CLI
 
 use {
+    borsh::BorshDeserialize,
+    clap::ArgMatches,
     cli_program_template::prelude::{
-        burn_instruction, load_account, load_wallet, mint_transaction, ping_instruction,
-        transfer_instruction, unpack_account_data, KEYS_DB, PROG_KEY,
+        account_space_for_mint, approve_instruction, burn_instruction, burn_many_instruction,
+        close_instruction, create_multisig_transaction, fetch_program_events, freeze_instruction,
+        get_account_for, initialize_config_instruction, initialize_registry_instruction,
+        load_account, load_wallet, lock_instruction, migrate_instruction, mint_many_transaction,
+        mint_transaction, mint_with_expiry_transaction, pause_instruction, ping_instruction,
+        purge_expired_instruction, resize_instruction, revoke_instruction,
+        set_authority_instruction, set_fees_instruction, set_freeze_authority_instruction,
+        set_key_value_limits_instruction, set_treasury_instruction, thaw_instruction,
+        transfer_instruction, transfer_many_instruction, unlock_instruction, unpack_account_data,
+        unpack_config_data, unpack_key_value_limits, unpack_program_accounts, unpack_registry_data,
+        unpause_instruction, update_instruction, upsert_instruction, ACCOUNT_SEED, KEYS_DB,
+        PROG_KEY,
     },
     clparse::parse_command_line,
-    sol_template_shared::ACCOUNT_STATE_SPACE,
+    serde_json::json,
+    sol_template_shared::{
+        AccountStateData, Expiry, KeyValueLimits, LayoutVersion, Value, ACCOUNT_STATE_SPACE,
+    },
     solana_clap_utils::{
-        input_parsers::pubkey_of, input_validators::normalize_to_url_if_moniker,
+        input_parsers::{pubkey_of, pubkeys_of, value_of},
+        input_validators::normalize_to_url_if_moniker,
         keypair::DefaultSigner,
     },
+    solana_cli_template_program_bpf::{
+        config_state::ServiceFees, event::ProgramEvent, instruction::ProgramInstruction,
+    },
     solana_client::rpc_client::RpcClient,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
         commitment_config::CommitmentConfig,
-        instruction::AccountMeta,
         native_token::Sol,
-        signature::{Keypair, Signer},
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signature, Signer},
+        transaction::Transaction,
     },
-    std::{process::exit, sync::Arc},
+    std::{collections::BTreeMap, fs, process::exit, sync::Arc},
 };
 pub mod clparse;
 pub mod utils;
@@ -28,13 +48,14 @@ struct Config {
     commitment_config: CommitmentConfig,
     default_signer: Box<dyn Signer>,
     json_rpc_url: String,
+    output_json: bool,
     verbose: bool,
 }
 
 /// Wallet and account verification and load
 ///
-/// Will search KEYS_DB for existence of the owner string and return the wallet and account keys
-/// and, optionally, fund the wallet and create and initialize the account if needed
+/// Will search KEYS_DB for existence of the owner string and return the wallet key and derived
+/// account address and, optionally, fund the wallet and create and initialize the account if needed
 ///
 /// # Example
 /// ```ignore
@@ -45,23 +66,450 @@ fn validate_user_accounts_and_load<'a>(
     funding_source: &dyn Signer,
     commitment_config: CommitmentConfig,
     owner: &str,
-) -> Result<(&'a Keypair, &'a Keypair), Box<dyn std::error::Error>> {
+) -> Result<(&'a Keypair, Pubkey), Box<dyn std::error::Error>> {
     // Check in KEYS_DB for owner
-    let (wallet, account) = KEYS_DB.wallet_and_account(owner.to_string())?;
+    let (wallet, _) = KEYS_DB.wallet_and_account(owner.to_string())?;
     // Fund wallet if required
     load_wallet(rpc_client, wallet, funding_source, commitment_config)?;
     // Create and initialize account if required
-    load_account(
+    let account = load_account(
         rpc_client,
-        account,
         wallet,
         &PROG_KEY.pubkey(),
-        ACCOUNT_STATE_SPACE as u64,
+        ACCOUNT_SEED,
         commitment_config,
     )?;
     Ok((wallet, account))
 }
 
+/// Reads keys, one per line, from a file ignoring blank lines
+fn read_keys_file(path: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Reads key/value pairs, one per line with the key separated from its value
+/// by whitespace, from a file ignoring blank lines. Values are parsed as the
+/// named value type
+fn read_key_values_file(
+    path: &str,
+    value_type: &str,
+) -> Result<Vec<(String, Value)>, Box<dyn std::error::Error>> {
+    let mut key_values = Vec::<(String, Value)>::new();
+    for line in read_keys_file(path)? {
+        match line.split_once(char::is_whitespace) {
+            Some((key, value)) => {
+                key_values.push((key.to_string(), Value::parse(value_type, value.trim())?))
+            }
+            None => {
+                return Err(Box::<dyn std::error::Error>::from(format!(
+                    "no value for key \"{}\" in {}",
+                    line, path
+                )))
+            }
+        }
+    }
+    Ok(key_values)
+}
+
+/// Parses the words of the "value" argument, joined by spaces, as the value
+/// type of the "type" argument
+fn value_from_matches(matches: &ArgMatches) -> Result<Value, Box<dyn std::error::Error>> {
+    let value: Vec<_> = matches.values_of("value").unwrap().collect();
+    Value::parse(matches.value_of("type").unwrap(), &value.join(" "))
+}
+
+/// Collects the keys given with repeated "key" arguments or in a "file"
+fn keys_from_matches(matches: &ArgMatches) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    match matches.value_of("file") {
+        Some(path) => read_keys_file(path),
+        None => Ok(matches
+            .values_of("key")
+            .unwrap()
+            .map(String::from)
+            .collect()),
+    }
+}
+
+/// Reads the keypairs of the multisig signers given with repeated "signer"
+/// arguments
+fn multisig_signers_from_matches(
+    matches: &ArgMatches,
+) -> Result<Vec<Keypair>, Box<dyn std::error::Error>> {
+    let mut signers = Vec::<Keypair>::new();
+    if let Some(paths) = matches.values_of("signer") {
+        for path in paths {
+            signers.push(read_keypair_file(path)?);
+        }
+    }
+    Ok(signers)
+}
+
+/// Returns the authority of the account and the signers of the transaction,
+/// the owners wallet first as it pays. Without multisig signers the wallet
+/// is the authority, otherwise the multisig recorded as the accounts
+/// authority is, signed for by the multisig signers
+fn authority_and_signers<'a>(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet: &'a Keypair,
+    multisig_signers: &'a [Keypair],
+    commitment_config: CommitmentConfig,
+) -> Result<(Pubkey, Vec<&'a dyn Signer>), Box<dyn std::error::Error>> {
+    let mut signers: Vec<&dyn Signer> = vec![wallet];
+    if multisig_signers.is_empty() {
+        return Ok((wallet.pubkey(), signers));
+    }
+    let account_state = unpack_account_data(rpc_client, account, commitment_config)?;
+    for signer in multisig_signers {
+        signers.push(signer);
+    }
+    Ok((account_state.authority, signers))
+}
+
+/// Reads a partially signed swap transaction from a file, confirming it is a
+/// single swap instruction of the program
+fn read_swap_file(path: &str) -> Result<(Transaction, String, String), Box<dyn std::error::Error>> {
+    let transaction: Transaction = bincode::deserialize(&fs::read(path)?)?;
+    let message = &transaction.message;
+    if let [instruction] = message.instructions.as_slice() {
+        if message.account_keys[instruction.program_id_index as usize] == PROG_KEY.pubkey() {
+            if let Ok(ProgramInstruction::SwapKeys { key_a, key_b, .. }) =
+                ProgramInstruction::try_from_slice(&instruction.data)
+            {
+                return Ok((transaction, key_a, key_b));
+            }
+        }
+    }
+    Err(Box::<dyn std::error::Error>::from(format!(
+        "{} is not a swap transaction",
+        path
+    )))
+}
+
+/// Prints the key/value store of an account, with the type of values that
+/// are not strings, the expiry of values that expire, the active key
+/// delegations and the locked keys, if any, and whether the account is
+/// frozen. With `output_json` the account state is printed as a JSON object
+/// instead
+fn print_account_state(label: &str, account_state: &AccountStateData, output_json: bool) {
+    if output_json {
+        let values: serde_json::Map<String, serde_json::Value> = account_state
+            .btree_storage
+            .iter()
+            .map(|(key, value)| {
+                let mut typed_value = json!({
+                    "type": value.type_name(),
+                    "value": json_value(value),
+                });
+                if let Some(expiry) = account_state.expiry(key) {
+                    typed_value["expires"] = json_expiry(expiry);
+                }
+                (key.clone(), typed_value)
+            })
+            .collect();
+        let delegates: serde_json::Map<String, serde_json::Value> = account_state
+            .delegates
+            .iter()
+            .map(|(key, delegate)| (key.clone(), json!(delegate.to_string())))
+            .collect();
+        println!(
+            "{}",
+            json!({
+                "label": label,
+                "authority": account_state.authority.to_string(),
+                "values": values,
+                "delegates": delegates,
+                "frozen": account_state.frozen,
+                "freeze_authority": account_state.freeze_authority().to_string(),
+                "locked": account_state.locked_keys,
+            })
+        );
+        return;
+    }
+    let btree: BTreeMap<&String, String> = account_state
+        .btree_storage
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                _ => format!("{} ({})", value, value.type_name()),
+            };
+            match account_state.expiry(key) {
+                Some(expiry) => (key, format!("{} (expires at {})", value, expiry)),
+                None => (key, value),
+            }
+        })
+        .collect();
+    println!("{} key/value store {:?}", label, btree);
+    if !account_state.delegates.is_empty() {
+        println!("{} key delegates {:?}", label, account_state.delegates);
+    }
+    if !account_state.locked_keys.is_empty() {
+        println!("{} locked keys {:?}", label, account_state.locked_keys);
+    }
+    if account_state.frozen {
+        println!(
+            "{} is frozen by {}",
+            label,
+            account_state.freeze_authority()
+        );
+    }
+}
+
+/// Converts a value to JSON, bytes and pubkeys as their display strings
+fn json_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(value) => json!(value),
+        Value::U64(value) => json!(value),
+        Value::I64(value) => json!(value),
+        Value::Bool(value) => json!(value),
+        Value::Bytes(_) | Value::Pubkey(_) => json!(value.to_string()),
+    }
+}
+
+/// Converts an expiry to JSON
+fn json_expiry(expiry: &Expiry) -> serde_json::Value {
+    match expiry {
+        Expiry::Slot(slot) => json!({ "slot": slot }),
+        Expiry::UnixTimestamp(unix_timestamp) => json!({ "unix_timestamp": unix_timestamp }),
+    }
+}
+
+/// Prints the events the program logged in the transactions of a command.
+/// With `output_json` each event is printed as a JSON object instead
+fn print_program_events(
+    rpc_client: &RpcClient,
+    signatures: &[Signature],
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for signature in signatures {
+        for event in fetch_program_events(rpc_client, signature, config.commitment_config)? {
+            if config.output_json {
+                println!("{}", json_event(signature, &event));
+            } else {
+                println!("Event: {:?}", event);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts a program event to JSON, tagged with its name and the signature
+/// of the transaction that logged it
+fn json_event(signature: &Signature, event: &ProgramEvent) -> serde_json::Value {
+    let (name, fields) = match event {
+        ProgramEvent::AccountInitialized { account, authority } => (
+            "AccountInitialized",
+            json!({ "account": account.to_string(), "authority": authority.to_string() }),
+        ),
+        ProgramEvent::Minted {
+            account,
+            key,
+            value,
+            expiry,
+        } => (
+            "Minted",
+            json!({
+                "account": account.to_string(),
+                "key": key,
+                "type": value.type_name(),
+                "value": json_value(value),
+                "expires": expiry.as_ref().map(json_expiry),
+            }),
+        ),
+        ProgramEvent::Transferred { from, to, key } => (
+            "Transferred",
+            json!({ "from": from.to_string(), "to": to.to_string(), "key": key }),
+        ),
+        ProgramEvent::Burned { account, key } => (
+            "Burned",
+            json!({ "account": account.to_string(), "key": key }),
+        ),
+        ProgramEvent::ValueUpdated {
+            account,
+            key,
+            value,
+        } => (
+            "ValueUpdated",
+            json!({
+                "account": account.to_string(),
+                "key": key,
+                "type": value.type_name(),
+                "value": json_value(value),
+            }),
+        ),
+        ProgramEvent::ExpiredPurged { account, count } => (
+            "ExpiredPurged",
+            json!({ "account": account.to_string(), "count": count }),
+        ),
+        ProgramEvent::AccountResized { account, size } => (
+            "AccountResized",
+            json!({ "account": account.to_string(), "size": size }),
+        ),
+        ProgramEvent::AccountMigrated {
+            account,
+            from_version,
+            to_version,
+        } => (
+            "AccountMigrated",
+            json!({
+                "account": account.to_string(),
+                "from_version": from_version,
+                "to_version": to_version,
+            }),
+        ),
+        ProgramEvent::AccountClosed {
+            account,
+            destination,
+            lamports,
+        } => (
+            "AccountClosed",
+            json!({
+                "account": account.to_string(),
+                "destination": destination.to_string(),
+                "lamports": lamports,
+            }),
+        ),
+        ProgramEvent::KeysSwapped {
+            account_a,
+            key_a,
+            account_b,
+            key_b,
+        } => (
+            "KeysSwapped",
+            json!({
+                "account_a": account_a.to_string(),
+                "key_a": key_a,
+                "account_b": account_b.to_string(),
+                "key_b": key_b,
+            }),
+        ),
+        ProgramEvent::DelegateApproved {
+            account,
+            key,
+            delegate,
+        } => (
+            "DelegateApproved",
+            json!({
+                "account": account.to_string(),
+                "key": key,
+                "delegate": delegate.to_string(),
+            }),
+        ),
+        ProgramEvent::DelegateRevoked { account, key } => (
+            "DelegateRevoked",
+            json!({ "account": account.to_string(), "key": key }),
+        ),
+        ProgramEvent::ConfigInitialized {
+            admin,
+            treasury,
+            fees,
+        } => (
+            "ConfigInitialized",
+            json!({
+                "admin": admin.to_string(),
+                "treasury": treasury.to_string(),
+                "fees": json_fees(fees),
+            }),
+        ),
+        ProgramEvent::FeesSet { fees } => ("FeesSet", json!({ "fees": json_fees(fees) })),
+        ProgramEvent::TreasurySet { treasury } => {
+            ("TreasurySet", json!({ "treasury": treasury.to_string() }))
+        }
+        ProgramEvent::FeeCharged {
+            account,
+            treasury,
+            amount,
+        } => (
+            "FeeCharged",
+            json!({
+                "account": account.to_string(),
+                "treasury": treasury.to_string(),
+                "amount": amount,
+            }),
+        ),
+        ProgramEvent::AccountFrozen { account } => {
+            ("AccountFrozen", json!({ "account": account.to_string() }))
+        }
+        ProgramEvent::AccountThawed { account } => {
+            ("AccountThawed", json!({ "account": account.to_string() }))
+        }
+        ProgramEvent::KeyLocked { account, key } => (
+            "KeyLocked",
+            json!({ "account": account.to_string(), "key": key }),
+        ),
+        ProgramEvent::KeyUnlocked { account, key } => (
+            "KeyUnlocked",
+            json!({ "account": account.to_string(), "key": key }),
+        ),
+        ProgramEvent::FreezeAuthoritySet {
+            account,
+            freeze_authority,
+        } => (
+            "FreezeAuthoritySet",
+            json!({
+                "account": account.to_string(),
+                "freeze_authority": freeze_authority.to_string(),
+            }),
+        ),
+        ProgramEvent::MultisigInitialized {
+            multisig,
+            m,
+            signers,
+        } => (
+            "MultisigInitialized",
+            json!({
+                "multisig": multisig.to_string(),
+                "m": m,
+                "signers": signers.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            }),
+        ),
+        ProgramEvent::AuthoritySet { account, authority } => (
+            "AuthoritySet",
+            json!({ "account": account.to_string(), "authority": authority.to_string() }),
+        ),
+        ProgramEvent::RegistryInitialized { registry } => (
+            "RegistryInitialized",
+            json!({ "registry": registry.to_string() }),
+        ),
+        ProgramEvent::ProgramPaused { admin } => {
+            ("ProgramPaused", json!({ "admin": admin.to_string() }))
+        }
+        ProgramEvent::ProgramUnpaused { admin } => {
+            ("ProgramUnpaused", json!({ "admin": admin.to_string() }))
+        }
+        ProgramEvent::KeyValueLimitsSet { limits } => (
+            "KeyValueLimitsSet",
+            json!({ "limits": json_key_value_limits(limits) }),
+        ),
+    };
+    json!({ "signature": signature.to_string(), "event": name, "fields": fields })
+}
+
+/// Converts key/value limits to JSON
+fn json_key_value_limits(limits: &KeyValueLimits) -> serde_json::Value {
+    json!({
+        "max_key_len": limits.max_key_len,
+        "max_value_len": limits.max_value_len,
+        "max_entries": limits.max_entries,
+    })
+}
+
+/// Converts a fee schedule to JSON
+fn json_fees(fees: &ServiceFees) -> serde_json::Value {
+    json!({
+        "minting": fees.minting,
+        "transfering": fees.transfering,
+        "burning": fees.burning,
+        "updating": fees.updating,
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app_matches = parse_command_line();
@@ -97,6 +545,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     eprintln!("error: {}", err);
                     exit(1);
                 }),
+            output_json: matches.value_of("output") == Some("json"),
             verbose: matches.is_present("verbose"),
             commitment_config: CommitmentConfig::confirmed(),
         }
@@ -108,6 +557,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     let rpc_client = RpcClient::new(config.json_rpc_url.clone());
     // Load the keys_db
+    // Signatures of the transactions the command submits, their events are
+    // printed once the command completes
+    let mut signatures = Vec::<Signature>::new();
 
     match (sub_command, sub_matches) {
         ("balance", Some(arg_matches)) => {
@@ -123,10 +575,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         ("mint", Some(_arg_matchs)) => {
             let owner = matches.value_of("to-owner").unwrap();
-            let key = matches.value_of("key").unwrap();
-            let value = {
-                let value: Vec<_> = matches.values_of("value").unwrap().collect();
-                value.join(" ")
+            let key_values = match matches.value_of("file") {
+                Some(path) => read_key_values_file(path, matches.value_of("type").unwrap())?,
+                None => {
+                    let key = matches.value_of("key").unwrap();
+                    vec![(key.to_string(), value_from_matches(matches)?)]
+                }
             };
             // Verify the owner is a valid account
             let (wallet, account) = validate_user_accounts_and_load(
@@ -135,25 +589,91 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 config.commitment_config,
                 owner,
             )?;
-            // Execute command
-            mint_transaction(
+            let multisig_signers = multisig_signers_from_matches(matches)?;
+            let (authority, signers) = authority_and_signers(
                 &rpc_client,
-                &[
-                    AccountMeta::new(account.pubkey(), false),
-                    AccountMeta::new(wallet.pubkey(), true),
-                ],
+                &account,
                 wallet,
-                key,
-                &value,
+                &multisig_signers,
                 config.commitment_config,
             )?;
-            let (_, btree) = unpack_account_data(&rpc_client, account, config.commitment_config)?;
-            println!("{} to account key/value store {:?}", owner, btree);
+            // Grow the account, with room to spare, if the key/values do not fit
+            if matches.is_present("grow") {
+                let (current_space, required_space) = account_space_for_mint(
+                    &rpc_client,
+                    &account,
+                    &key_values,
+                    config.commitment_config,
+                )?;
+                if required_space > current_space {
+                    signatures.push(resize_instruction(
+                        &rpc_client,
+                        &account,
+                        &authority,
+                        &signers,
+                        (required_space + ACCOUNT_STATE_SPACE) as u64,
+                        config.commitment_config,
+                    )?);
+                }
+            }
+            // Execute command
+            let expiry = match (
+                value_of(matches, "expires-slot"),
+                value_of(matches, "expires-at"),
+            ) {
+                (Some(slot), _) => Some(Expiry::Slot(slot)),
+                (_, Some(unix_timestamp)) => Some(Expiry::UnixTimestamp(unix_timestamp)),
+                _ => None,
+            };
+            match key_values.as_slice() {
+                [(key, value)] => match expiry {
+                    Some(expiry) => {
+                        signatures.push(mint_with_expiry_transaction(
+                            &rpc_client,
+                            &account,
+                            &authority,
+                            &signers,
+                            key,
+                            value,
+                            expiry,
+                            config.commitment_config,
+                        )?);
+                    }
+                    None => {
+                        signatures.push(mint_transaction(
+                            &rpc_client,
+                            &account,
+                            &authority,
+                            &signers,
+                            key,
+                            value,
+                            config.commitment_config,
+                        )?);
+                    }
+                },
+                _ => {
+                    signatures.extend(mint_many_transaction(
+                        &rpc_client,
+                        &account,
+                        &authority,
+                        &signers,
+                        &key_values,
+                        config.commitment_config,
+                    )?);
+                }
+            }
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} to account", owner),
+                &account_state,
+                config.output_json,
+            );
         }
         ("transfer", Some(_arg_matchs)) => {
             let from_owner = matches.value_of("from-owner").unwrap();
             let to_owner = matches.value_of("to-owner").unwrap();
-            let key = matches.value_of("key").unwrap();
+            let keys = keys_from_matches(matches)?;
             // Verify that from and to owners are different and both are
             // valid
             let (from_wallet, from_account) = validate_user_accounts_and_load(
@@ -169,27 +689,56 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 to_owner,
             )?;
             // Execute command
-            transfer_instruction(
+            let multisig_signers = multisig_signers_from_matches(matches)?;
+            let (authority, signers) = authority_and_signers(
                 &rpc_client,
-                &[
-                    AccountMeta::new(from_account.pubkey(), false),
-                    AccountMeta::new(to_account.pubkey(), false),
-                    AccountMeta::new(from_wallet.pubkey(), true),
-                ],
+                &from_account,
                 from_wallet,
-                key,
+                &multisig_signers,
                 config.commitment_config,
             )?;
-            let (_, btree) =
-                unpack_account_data(&rpc_client, from_account, config.commitment_config)?;
-            println!("{} from account key/value store {:?}", from_owner, btree);
-            let (_, btree) =
-                unpack_account_data(&rpc_client, to_account, config.commitment_config)?;
-            println!("{} to account key/value store {:?}", to_owner, btree);
+            match keys.as_slice() {
+                [key] => {
+                    signatures.push(transfer_instruction(
+                        &rpc_client,
+                        &from_account,
+                        &to_account,
+                        &authority,
+                        &signers,
+                        key,
+                        config.commitment_config,
+                    )?);
+                }
+                _ => {
+                    signatures.extend(transfer_many_instruction(
+                        &rpc_client,
+                        &from_account,
+                        &to_account,
+                        &authority,
+                        &signers,
+                        &keys,
+                        config.commitment_config,
+                    )?);
+                }
+            }
+            let account_state =
+                unpack_account_data(&rpc_client, &from_account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} from account", from_owner),
+                &account_state,
+                config.output_json,
+            );
+            let account_state =
+                unpack_account_data(&rpc_client, &to_account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} to account", to_owner),
+                &account_state,
+                config.output_json,
+            );
         }
         ("burn", Some(_arg_matchs)) => {
             let owner = matches.value_of("from-owner").unwrap();
-            let key = matches.value_of("key").unwrap();
+            let keys = keys_from_matches(matches)?;
             // Verify the owner is a valid account
             let (wallet, account) = validate_user_accounts_and_load(
                 &rpc_client,
@@ -198,18 +747,553 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 owner,
             )?;
             // Execute command
-            burn_instruction(
+            let multisig_signers = multisig_signers_from_matches(matches)?;
+            let (authority, signers) = authority_and_signers(
                 &rpc_client,
-                &[
-                    AccountMeta::new(account.pubkey(), false),
-                    AccountMeta::new(wallet.pubkey(), true),
-                ],
+                &account,
                 wallet,
-                key,
+                &multisig_signers,
+                config.commitment_config,
+            )?;
+            match keys.as_slice() {
+                [key] => {
+                    signatures.push(burn_instruction(
+                        &rpc_client,
+                        &account,
+                        &authority,
+                        &signers,
+                        key,
+                        config.commitment_config,
+                    )?);
+                }
+                _ => {
+                    signatures.extend(burn_many_instruction(
+                        &rpc_client,
+                        &account,
+                        &authority,
+                        &signers,
+                        &keys,
+                        config.commitment_config,
+                    )?);
+                }
+            }
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} from account", owner),
+                &account_state,
+                config.output_json,
+            );
+        }
+        ("update", Some(_arg_matchs)) => {
+            let owner = matches.value_of("to-owner").unwrap();
+            let key = matches.value_of("key").unwrap();
+            let value = value_from_matches(matches)?;
+            // Verify the owner is a valid account
+            let (wallet, account) = validate_user_accounts_and_load(
+                &rpc_client,
+                config.default_signer.as_ref(),
+                config.commitment_config,
+                owner,
+            )?;
+            // Execute command
+            if matches.is_present("upsert") {
+                signatures.push(upsert_instruction(
+                    &rpc_client,
+                    &account,
+                    wallet,
+                    key,
+                    &value,
+                    config.commitment_config,
+                )?);
+            } else {
+                signatures.push(update_instruction(
+                    &rpc_client,
+                    &account,
+                    wallet,
+                    key,
+                    &value,
+                    config.commitment_config,
+                )?);
+            }
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} to account", owner),
+                &account_state,
+                config.output_json,
+            );
+        }
+        ("swap", Some(_arg_matchs)) => {
+            let owner = matches.value_of("owner").unwrap();
+            let (wallet, account) = validate_user_accounts_and_load(
+                &rpc_client,
+                config.default_signer.as_ref(),
+                config.commitment_config,
+                owner,
+            )?;
+            match matches.value_of("sign") {
+                // Complete and submit the counterparty's swap
+                Some(path) => {
+                    let (transaction, key_a, key_b) = read_swap_file(path)?;
+                    println!("Swapping {} to {} for {}", key_a, owner, key_b);
+                    signatures.push(submit_partial_transaction(
+                        &rpc_client,
+                        transaction,
+                        wallet,
+                        config.commitment_config,
+                    )?);
+                    let account_state =
+                        unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+                    print_account_state(
+                        &format!("{} account", owner),
+                        &account_state,
+                        config.output_json,
+                    );
+                }
+                // Build and partially sign the swap for the counterparty
+                None => {
+                    let key = matches.value_of("key").unwrap();
+                    let counterparty = matches.value_of("counterparty").unwrap();
+                    let counterparty_key = matches.value_of("counterparty-key").unwrap();
+                    let path = matches.value_of("out").unwrap();
+                    let (counterparty_wallet, counterparty_account) =
+                        validate_user_accounts_and_load(
+                            &rpc_client,
+                            config.default_signer.as_ref(),
+                            config.commitment_config,
+                            counterparty,
+                        )?;
+                    let transaction = swap_partial_transaction(
+                        &rpc_client,
+                        &account,
+                        &counterparty_account,
+                        &counterparty_wallet.pubkey(),
+                        wallet,
+                        key,
+                        counterparty_key,
+                    )?;
+                    fs::write(path, bincode::serialize(&transaction)?)?;
+                    println!(
+                        "Swap written to {}, {} signs it with \"swap --owner {} --sign {}\"",
+                        path, counterparty, counterparty, path
+                    );
+                }
+            }
+        }
+        ("approve", Some(_arg_matchs)) | ("revoke", Some(_arg_matchs)) => {
+            let owner = matches.value_of("owner").unwrap();
+            let key = matches.value_of("key").unwrap();
+            // Verify the owner is a valid account
+            let (wallet, account) = validate_user_accounts_and_load(
+                &rpc_client,
+                config.default_signer.as_ref(),
+                config.commitment_config,
+                owner,
+            )?;
+            // Execute command
+            match pubkey_of(matches, "delegate") {
+                Some(delegate) => signatures.push(approve_instruction(
+                    &rpc_client,
+                    &account,
+                    wallet,
+                    key,
+                    &delegate,
+                    config.commitment_config,
+                )?),
+                // Only approve takes a delegate
+                None => signatures.push(revoke_instruction(
+                    &rpc_client,
+                    &account,
+                    wallet,
+                    key,
+                    config.commitment_config,
+                )?),
+            };
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} account", owner),
+                &account_state,
+                config.output_json,
+            );
+        }
+        ("freeze", Some(_arg_matchs))
+        | ("thaw", Some(_arg_matchs))
+        | ("lock", Some(_arg_matchs))
+        | ("unlock", Some(_arg_matchs))
+        | ("set-freeze-authority", Some(_arg_matchs)) => {
+            let owner = matches.value_of("owner").unwrap();
+            // Verify the owner is a valid account
+            let (wallet, account) = validate_user_accounts_and_load(
+                &rpc_client,
+                config.default_signer.as_ref(),
                 config.commitment_config,
+                owner,
             )?;
-            let (_, btree) = unpack_account_data(&rpc_client, account, config.commitment_config)?;
-            println!("{} from account key/value store {:?}", owner, btree);
+            // The owners wallet pays, and signs unless another wallet was
+            // made the freeze authority
+            let freeze_authority = match matches.value_of("freeze-authority") {
+                Some(path) => Some(read_keypair_file(path)?),
+                None => None,
+            };
+            let mut signers: Vec<&dyn Signer> = vec![wallet];
+            let freeze_authority_pubkey = match &freeze_authority {
+                Some(freeze_authority) => {
+                    if freeze_authority.pubkey() != wallet.pubkey() {
+                        signers.push(freeze_authority);
+                    }
+                    freeze_authority.pubkey()
+                }
+                None => wallet.pubkey(),
+            };
+            // Execute command
+            signatures.push(match sub_command {
+                "freeze" => freeze_instruction(
+                    &rpc_client,
+                    &account,
+                    &freeze_authority_pubkey,
+                    &signers,
+                    config.commitment_config,
+                )?,
+                "thaw" => thaw_instruction(
+                    &rpc_client,
+                    &account,
+                    &freeze_authority_pubkey,
+                    &signers,
+                    config.commitment_config,
+                )?,
+                "lock" => lock_instruction(
+                    &rpc_client,
+                    &account,
+                    &freeze_authority_pubkey,
+                    &signers,
+                    matches.value_of("key").unwrap(),
+                    config.commitment_config,
+                )?,
+                "unlock" => unlock_instruction(
+                    &rpc_client,
+                    &account,
+                    &freeze_authority_pubkey,
+                    &signers,
+                    matches.value_of("key").unwrap(),
+                    config.commitment_config,
+                )?,
+                _ => set_freeze_authority_instruction(
+                    &rpc_client,
+                    &account,
+                    &freeze_authority_pubkey,
+                    &signers,
+                    &pubkey_of(matches, "new-freeze-authority").unwrap(),
+                    config.commitment_config,
+                )?,
+            });
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} account", owner),
+                &account_state,
+                config.output_json,
+            );
+        }
+        ("purge", Some(_arg_matchs)) => {
+            let owner = matches.value_of("owner").unwrap();
+            let (_, account) = KEYS_DB.wallet_and_account(owner.to_string())?;
+            // Execute command, anyone may pay for a purge
+            signatures.push(purge_expired_instruction(
+                &rpc_client,
+                &account,
+                config.default_signer.as_ref(),
+                config.commitment_config,
+            )?);
+            let account_state =
+                unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+            print_account_state(
+                &format!("{} account", owner),
+                &account_state,
+                config.output_json,
+            );
+        }
+        ("close", Some(_arg_matchs)) => {
+            let owner = matches.value_of("owner").unwrap();
+            let require_empty = matches.is_present("require-empty");
+            let (wallet, account) = KEYS_DB.wallet_and_account(owner.to_string())?;
+            let destination = pubkey_of(matches, "destination").unwrap_or_else(|| wallet.pubkey());
+            // Verify the account exists
+            let balance = match get_account_for(&rpc_client, &account, config.commitment_config) {
+                Some(account_) => account_.lamports,
+                None => {
+                    eprintln!("error: {} has no account to close", owner);
+                    exit(1);
+                }
+            };
+            // Execute command
+            signatures.push(close_instruction(
+                &rpc_client,
+                &account,
+                &destination,
+                wallet,
+                require_empty,
+                config.commitment_config,
+            )?);
+            println!(
+                "{} account {} closed, {} reclaimed to {}",
+                owner,
+                account,
+                Sol(balance),
+                destination
+            );
+        }
+        ("multisig", Some(_arg_matchs)) => match matches.subcommand() {
+            ("create", Some(create_matches)) => {
+                let m = value_of(create_matches, "threshold").unwrap();
+                let multisig_signers = pubkeys_of(create_matches, "signer").unwrap();
+                // Execute command
+                let (multisig, signature) = create_multisig_transaction(
+                    &rpc_client,
+                    config.default_signer.as_ref(),
+                    m,
+                    &multisig_signers,
+                    config.commitment_config,
+                )?;
+                signatures.push(signature);
+                println!("Multisig: {}", multisig);
+                if let Some(owner) = create_matches.value_of("owner") {
+                    // Verify the owner is a valid account
+                    let (wallet, account) = validate_user_accounts_and_load(
+                        &rpc_client,
+                        config.default_signer.as_ref(),
+                        config.commitment_config,
+                        owner,
+                    )?;
+                    signatures.push(set_authority_instruction(
+                        &rpc_client,
+                        &account,
+                        &wallet.pubkey(),
+                        &[wallet],
+                        &multisig,
+                        config.commitment_config,
+                    )?);
+                    let account_state =
+                        unpack_account_data(&rpc_client, &account, config.commitment_config)?;
+                    print_account_state(
+                        &format!("{} account", owner),
+                        &account_state,
+                        config.output_json,
+                    );
+                }
+            }
+            _ => unreachable!(),
+        },
+        ("fees", Some(_arg_matchs)) => match matches.subcommand() {
+            ("show", Some(_)) => match unpack_config_data(&rpc_client, config.commitment_config)? {
+                Some(config_state) => {
+                    println!("Admin: {}", config_state.admin());
+                    println!("Treasury: {}", config_state.treasury());
+                    println!("{:?}", config_state.fees());
+                }
+                None => println!("Program config not created, see \"fees set\""),
+            },
+            ("set", Some(set_matches)) => {
+                let current = unpack_config_data(&rpc_client, config.commitment_config)?;
+                // Fees not given keep their current, or default, value
+                let mut fees = current
+                    .as_ref()
+                    .map_or_else(ServiceFees::default, |config_state| *config_state.fees());
+                fees.minting = value_of(set_matches, "mint").unwrap_or(fees.minting);
+                fees.transfering = value_of(set_matches, "transfer").unwrap_or(fees.transfering);
+                fees.burning = value_of(set_matches, "burn").unwrap_or(fees.burning);
+                fees.updating = value_of(set_matches, "update").unwrap_or(fees.updating);
+                let treasury = pubkey_of(set_matches, "treasury");
+                // Execute command
+                match current {
+                    Some(config_state) => {
+                        if config_state.fees() != &fees {
+                            signatures.push(set_fees_instruction(
+                                &rpc_client,
+                                config.default_signer.as_ref(),
+                                fees,
+                                config.commitment_config,
+                            )?);
+                        }
+                        if let Some(treasury) = treasury {
+                            signatures.push(set_treasury_instruction(
+                                &rpc_client,
+                                config.default_signer.as_ref(),
+                                &treasury,
+                                config.commitment_config,
+                            )?);
+                        }
+                    }
+                    None => {
+                        let treasury = match treasury {
+                            Some(treasury) => treasury,
+                            None => KEYS_DB.wallet_and_account("Service".to_string())?.1,
+                        };
+                        signatures.push(initialize_config_instruction(
+                            &rpc_client,
+                            config.default_signer.as_ref(),
+                            fees,
+                            &treasury,
+                            config.commitment_config,
+                        )?);
+                    }
+                };
+                if let Some(config_state) =
+                    unpack_config_data(&rpc_client, config.commitment_config)?
+                {
+                    println!("Treasury: {}", config_state.treasury());
+                    println!("{:?}", config_state.fees());
+                }
+            }
+            _ => unreachable!(),
+        },
+        ("admin", Some(_arg_matchs)) => {
+            match matches.subcommand() {
+                ("pause", Some(_)) => signatures.push(pause_instruction(
+                    &rpc_client,
+                    config.default_signer.as_ref(),
+                    config.commitment_config,
+                )?),
+                ("unpause", Some(_)) => signatures.push(unpause_instruction(
+                    &rpc_client,
+                    config.default_signer.as_ref(),
+                    config.commitment_config,
+                )?),
+                ("limits", Some(limits_matches)) => {
+                    // Limits not given keep their current value
+                    let mut limits =
+                        unpack_key_value_limits(&rpc_client, config.commitment_config)?;
+                    limits.max_key_len =
+                        value_of(limits_matches, "max-key-len").unwrap_or(limits.max_key_len);
+                    limits.max_value_len =
+                        value_of(limits_matches, "max-value-len").unwrap_or(limits.max_value_len);
+                    limits.max_entries =
+                        value_of(limits_matches, "max-entries").unwrap_or(limits.max_entries);
+                    if !limits.is_valid() {
+                        eprintln!("error: invalid key/value limits {:?}", limits);
+                        exit(1);
+                    }
+                    signatures.push(set_key_value_limits_instruction(
+                        &rpc_client,
+                        config.default_signer.as_ref(),
+                        limits,
+                        config.commitment_config,
+                    )?)
+                }
+                ("status", Some(_)) => (),
+                _ => unreachable!(),
+            }
+            match unpack_config_data(&rpc_client, config.commitment_config)? {
+                Some(config_state) => {
+                    let limits = config_state.key_value_limits();
+                    if config.output_json {
+                        println!(
+                            "{}",
+                            json!({
+                                "admin": config_state.admin().to_string(),
+                                "paused": config_state.is_paused(),
+                                "limits": json_key_value_limits(&limits),
+                            })
+                        );
+                    } else {
+                        println!("Admin: {}", config_state.admin());
+                        println!("Paused: {}", config_state.is_paused());
+                        println!("{:?}", limits);
+                    }
+                }
+                None => println!("Program config not created, see \"fees set\""),
+            }
+        }
+        ("migrate", Some(_arg_matchs)) => {
+            let owners: Vec<String> = match matches.values_of("owner") {
+                Some(owners) => owners.map(String::from).collect(),
+                None => KEYS_DB.key_owners(),
+            };
+            let mut wallets = Vec::<(String, &Keypair)>::new();
+            for owner in &owners {
+                let (wallet, _) = KEYS_DB.wallet_and_account(owner.clone())?;
+                wallets.push((owner.clone(), wallet));
+            }
+            let mut legacy_accounts = Vec::<Keypair>::new();
+            for path in matches.values_of("legacy-account").into_iter().flatten() {
+                legacy_accounts.push(read_keypair_file(path)?);
+            }
+            for (account, version, account_state) in unpack_program_accounts(&rpc_client)? {
+                // Accounts of the first layout have no authority, the first
+                // owner becomes the authority of those with a keypair given
+                let (owner, account_signer) = match version {
+                    LayoutVersion::V0 => (
+                        wallets.first(),
+                        legacy_accounts
+                            .iter()
+                            .find(|keypair| keypair.pubkey() == account),
+                    ),
+                    _ => (
+                        wallets
+                            .iter()
+                            .find(|(_, wallet)| wallet.pubkey() == account_state.authority),
+                        None,
+                    ),
+                };
+                let owner_name = owner.map_or("unknown owner", |(name, _)| name.as_str());
+                println!("{} ({}) layout version {}", account, owner_name, version);
+                if !version.is_legacy() || matches.is_present("check") {
+                    continue;
+                }
+                // Only the `V0` layout is earlier than the current layout
+                match (owner, account_signer) {
+                    (Some((_, wallet)), Some(account_signer)) => {
+                        signatures.push(migrate_instruction(
+                            &rpc_client,
+                            &account,
+                            *wallet,
+                            Some(account_signer),
+                            config.commitment_config,
+                        )?);
+                    }
+                    _ => {
+                        println!("  skipped, no signers to migrate it");
+                        continue;
+                    }
+                }
+                println!("  migrated to layout version {}", LayoutVersion::CURRENT);
+            }
+        }
+        ("stats", Some(_arg_matchs)) => {
+            if matches.is_present("initialize")
+                && unpack_registry_data(&rpc_client, config.commitment_config)?.is_none()
+            {
+                signatures.push(initialize_registry_instruction(
+                    &rpc_client,
+                    config.default_signer.as_ref(),
+                    config.commitment_config,
+                )?);
+            }
+            match unpack_registry_data(&rpc_client, config.commitment_config)? {
+                Some(registry_state) => {
+                    if config.output_json {
+                        println!(
+                            "{}",
+                            json!({
+                                "accounts_initialized": registry_state.accounts_initialized(),
+                                "keys_minted": registry_state.keys_minted(),
+                                "keys_burned": registry_state.keys_burned(),
+                                "fees_collected": registry_state.fees_collected(),
+                            })
+                        );
+                    } else {
+                        println!(
+                            "Accounts initialized: {}",
+                            registry_state.accounts_initialized()
+                        );
+                        println!("Keys minted: {}", registry_state.keys_minted());
+                        println!("Keys burned: {}", registry_state.keys_burned());
+                        println!("Fees collected: {}", Sol(registry_state.fees_collected()));
+                    }
+                }
+                None => println!("Program registry not created, see \"stats --initialize\""),
+            }
         }
         ("ping", Some(_arg_matches)) => {
             let signature = ping_instruction(
@@ -225,6 +1309,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         _ => unreachable!(),
     };
+    print_program_events(&rpc_client, &signatures, &config)?;
 
     Ok(())
 }
@@ -1,24 +1,114 @@
 //! @brief Account state access
 
 use {
-    crate::utils::txn_utils::get_account_for,
-    sol_template_shared::unpack_from_slice,
+    crate::utils::{keys_db::PROG_KEY, txn_utils::get_account_for},
+    sol_template_shared::{
+        account_state_space_for, unpack_from_slice, unpack_versioned_from_slice, AccountStateData,
+        KeyValueLimits, LayoutVersion, Value, KEY_VALUE_LIMITS,
+    },
+    solana_cli_template_program_bpf::{
+        config_state::ProgramConfigState, find_config_address, find_registry_address,
+        registry_state::ProgramRegistryState,
+    },
     solana_client::rpc_client::RpcClient,
-    solana_sdk::{commitment_config::CommitmentConfig, signature::Keypair, signer::Signer},
-    std::{collections::BTreeMap, error::Error},
+    solana_sdk::{
+        commitment_config::CommitmentConfig, program_pack::Pack, pubkey::Pubkey, signer::Signer,
+    },
+    std::error::Error,
 };
 
 /// Unpacks token state for the accumulator
 pub fn unpack_account_data(
     rpc_client: &RpcClient,
-    account: &Keypair,
+    account: &Pubkey,
     commitment_config: CommitmentConfig,
-) -> Result<(bool, BTreeMap<String, String>), Box<dyn Error>> {
-    match get_account_for(rpc_client, &account.pubkey(), commitment_config) {
-        Some(account_) => Ok(unpack_from_slice(&account_.data).unwrap()),
+) -> Result<AccountStateData, Box<dyn Error>> {
+    match get_account_for(rpc_client, account, commitment_config) {
+        Some(account_) => Ok(unpack_from_slice(&account_.data)?),
         None => Err(Box::<dyn Error>::from(format!(
             "account not found for \"{:?}\". ",
             account
         ))),
     }
 }
+
+/// Returns the current account data size and the size required to
+/// also hold the key/value pairs
+pub fn account_space_for_mint(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    key_values: &[(String, Value)],
+    commitment_config: CommitmentConfig,
+) -> Result<(usize, usize), Box<dyn Error>> {
+    match get_account_for(rpc_client, account, commitment_config) {
+        Some(account_) => {
+            let mut account_state = unpack_from_slice(&account_.data)?;
+            account_state
+                .btree_storage
+                .extend(key_values.iter().cloned());
+            Ok((account_.data.len(), account_state_space_for(&account_state)))
+        }
+        None => Err(Box::<dyn Error>::from(format!(
+            "account not found for \"{:?}\". ",
+            account
+        ))),
+    }
+}
+
+/// Unpacks the program config state, returning None if the config account
+/// has not been created
+pub fn unpack_config_data(
+    rpc_client: &RpcClient,
+    commitment_config: CommitmentConfig,
+) -> Result<Option<ProgramConfigState>, Box<dyn Error>> {
+    let (config, _) = find_config_address(&PROG_KEY.pubkey());
+    match get_account_for(rpc_client, &config, commitment_config) {
+        Some(account_) => Ok(Some(ProgramConfigState::unpack(&account_.data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Returns the key/value limits the program enforces, those set in the
+/// program config or the default limits until the config account is created
+pub fn unpack_key_value_limits(
+    rpc_client: &RpcClient,
+    commitment_config: CommitmentConfig,
+) -> Result<KeyValueLimits, Box<dyn Error>> {
+    Ok(unpack_config_data(rpc_client, commitment_config)?
+        .map_or(KEY_VALUE_LIMITS, |config_state| {
+            config_state.key_value_limits()
+        }))
+}
+
+/// Unpacks the program registry state, returning None if the registry
+/// account has not been created
+pub fn unpack_registry_data(
+    rpc_client: &RpcClient,
+    commitment_config: CommitmentConfig,
+) -> Result<Option<ProgramRegistryState>, Box<dyn Error>> {
+    let (registry, _) = find_registry_address(&PROG_KEY.pubkey());
+    match get_account_for(rpc_client, &registry, commitment_config) {
+        Some(account_) => Ok(Some(ProgramRegistryState::unpack(&account_.data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Returns the layout version and account state of the program owned
+/// accounts, skipping the program config, the registry and accounts that do
+/// not decode as their layout
+pub fn unpack_program_accounts(
+    rpc_client: &RpcClient,
+) -> Result<Vec<(Pubkey, LayoutVersion, AccountStateData)>, Box<dyn Error>> {
+    let (config, _) = find_config_address(&PROG_KEY.pubkey());
+    let (registry, _) = find_registry_address(&PROG_KEY.pubkey());
+    Ok(rpc_client
+        .get_program_accounts(&PROG_KEY.pubkey())?
+        .into_iter()
+        .filter(|(account, _)| *account != config && *account != registry)
+        .filter_map(|(account, account_)| {
+            unpack_versioned_from_slice(&account_.data)
+                .ok()
+                .map(|(version, account_state)| (account, version, account_state))
+        })
+        .collect())
+}
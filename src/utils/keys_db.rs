@@ -8,7 +8,12 @@ use {
     super::load_keys_config_file,
     lazy_static::lazy_static,
     serde::{Deserialize, Serialize},
-    solana_sdk::signature::{read_keypair_file, Keypair},
+    solana_cli_template_program_bpf::find_derived_account_address,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair},
+        signer::Signer,
+    },
     std::{
         collections::HashMap,
         error, fs,
@@ -22,8 +27,8 @@ const KEYS_DB_CONFIG_PATH: &str = "keys";
 const KEYS_DB_CONFIG_FILE_NAME: &str = "keys_db.yml";
 /// Standardized wallet key name
 const WALLET: &str = "wallet";
-/// Standardized account key name
-const ACCOUNT: &str = "account";
+/// Seed used with an owners wallet to derive the owners program account
+pub const ACCOUNT_SEED: &str = "account";
 /// The base folder for the program
 const KEY_PROGRAM_PATH: &str = "program";
 /// Our fee receiving account owner
@@ -138,10 +143,6 @@ impl KeysDB {
                 WALLET.to_string().to_string(),
                 Self::load_keypair(accounts.1.get(WALLET).unwrap()).unwrap(),
             );
-            keys_hm.insert(
-                ACCOUNT.to_string().to_string(),
-                Self::load_keypair(accounts.1.get(ACCOUNT).unwrap()).unwrap(),
-            );
             keys_reg.insert(accounts.0.clone(), keys_hm);
         }
         KeysDB {
@@ -171,15 +172,20 @@ impl KeysDB {
         );
         all_owners
     }
-    /// Get a wallet and account keypair for name
+    /// Get a wallet keypair and derived program account address for name
     pub fn wallet_and_account(
         &self,
         name: String,
-    ) -> Result<(&Keypair, &Keypair), Box<dyn error::Error>> {
+    ) -> Result<(&Keypair, Pubkey), Box<dyn error::Error>> {
         match self.keys_registry.contains_key(&name) {
             true => {
-                let owner = self.keys_registry.get(&name).unwrap();
-                Ok((owner.get(WALLET).unwrap(), owner.get(ACCOUNT).unwrap()))
+                let wallet = self.keys_registry.get(&name).unwrap().get(WALLET).unwrap();
+                let (account, _) = find_derived_account_address(
+                    &PROG_KEY.pubkey(),
+                    &wallet.pubkey(),
+                    ACCOUNT_SEED,
+                );
+                Ok((wallet, account))
             }
             false => Err(Box::<dyn error::Error>::from(format!(
                 "could not find owner \"{}\". key in DB",
@@ -191,8 +197,6 @@ impl KeysDB {
 
 #[cfg(test)]
 mod tests {
-    use solana_sdk::signer::Signer;
-
     use super::*;
 
     #[test]
@@ -211,18 +215,23 @@ mod tests {
         assert!(KEYS_DB.keys_registry().contains_key("User2"));
         if let Some(user) = KEYS_DB.keys_registry().get(SERVICE_OWNER) {
             assert!(user.contains_key(WALLET));
-            assert!(user.contains_key(ACCOUNT));
         }
         if let Some(user) = KEYS_DB.keys_registry().get("User1") {
             assert!(user.contains_key(WALLET));
-            assert!(user.contains_key(ACCOUNT));
         }
         if let Some(user) = KEYS_DB.keys_registry().get("User2") {
             assert!(user.contains_key(WALLET));
-            assert!(user.contains_key(ACCOUNT));
         }
     }
 
+    #[test]
+    fn test_derived_account() {
+        let (wallet, account) = KEYS_DB.wallet_and_account("User1".to_string()).unwrap();
+        let (derived, _) =
+            find_derived_account_address(&PROG_KEY.pubkey(), &wallet.pubkey(), ACCOUNT_SEED);
+        assert_eq!(account, derived);
+    }
+
     #[test]
     fn test_list_key_holders() {
         let key_owners = KEYS_DB.key_owners();
@@ -1,21 +1,35 @@
 //! @brief Transaction utilities
 
-use solana_cli_template_program_bpf::instruction::ProgramInstruction;
+use solana_cli_template_program_bpf::{
+    config_state::ServiceFees, event::ProgramEvent, find_derived_account_address, instruction,
+    multisig_state::MULTISIG_STATE_SPACE,
+};
 
 use {
-    crate::utils::keys_db::PROG_KEY,
-    solana_client::rpc_client::RpcClient,
+    crate::utils::{
+        account_state::{unpack_account_data, unpack_key_value_limits},
+        keys_db::PROG_KEY,
+    },
+    borsh::BorshDeserialize,
+    sol_template_shared::{Expiry, KeyValueLimits, Value},
+    solana_client::{
+        rpc_client::RpcClient,
+        rpc_config::{RpcSimulateTransactionConfig, RpcTransactionConfig},
+    },
     solana_sdk::{
         account::Account,
         commitment_config::CommitmentConfig,
-        instruction::{AccountMeta, Instruction},
+        instruction::Instruction,
         message::Message,
+        packet::PACKET_DATA_SIZE,
+        program::MAX_RETURN_DATA,
         pubkey::Pubkey,
         signature::{Keypair, Signature},
         signer::Signer,
         system_instruction,
         transaction::Transaction,
     },
+    solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding},
 };
 
 /// Checks for existence of account
@@ -90,82 +104,41 @@ pub fn load_wallet(
     Ok(())
 }
 
-/// Create a new program account with account state data allocation
+/// Create and initialize a new program account derived from the wallet and seed
 fn new_account(
     rpc_client: &RpcClient,
     wallet_signer: &dyn Signer,
-    account_pair: &dyn Signer,
     program_owner: &Pubkey,
-    state_space: u64,
+    seed: &str,
     commitment_config: CommitmentConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let account_lamports = rpc_client
-        .get_minimum_balance_for_rent_exemption(state_space as usize)
-        .unwrap();
-
-    let mut transaction = Transaction::new_with_payer(
-        &[
-            system_instruction::create_account(
-                &wallet_signer.pubkey(),
-                &account_pair.pubkey(),
-                account_lamports,
-                state_space,
-                program_owner,
-            ),
-            Instruction::new_with_borsh(
-                *program_owner,
-                &ProgramInstruction::InitializeAccount,
-                vec![
-                    AccountMeta::new(account_pair.pubkey(), false),
-                    AccountMeta::new(wallet_signer.pubkey(), false),
-                ],
-            ),
-        ],
-        Some(&wallet_signer.pubkey()),
-    );
-
-    let recent_blockhash = rpc_client
-        .get_latest_blockhash()
-        .map_err(|err| format!("error: unable to get recent blockhash: {}", err))
-        .unwrap();
-    transaction
-        .try_sign(&vec![wallet_signer, account_pair], recent_blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {}", err))
-        .unwrap();
-    let _signature = rpc_client
-        .send_and_confirm_transaction_with_spinner_and_commitment(&transaction, commitment_config)
-        .map_err(|err| format!("error: send transaction: {}", err))
-        .unwrap();
-    let _account = rpc_client
-        .get_account_with_commitment(&account_pair.pubkey(), commitment_config)
-        .map_err(|err| format!("error: getting account after initialization: {}", err))
-        .unwrap();
-
-    Ok(())
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let (account, _) = find_derived_account_address(program_owner, &wallet_signer.pubkey(), seed);
+    let instruction =
+        instruction::initialize_derived_account(program_owner, &wallet_signer.pubkey(), seed);
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)?;
+    Ok(account)
 }
 
-/// Load account with size
+/// Derives the program account for the wallet and seed and, if needed,
+/// creates and initializes it
 pub fn load_account(
     rpc_client: &RpcClient,
-    account_pair: &Keypair,
     wallet_signer: &dyn Signer,
     program_owner: &Pubkey,
-    space: u64,
+    seed: &str,
     commitment_config: CommitmentConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    match get_account_for(rpc_client, &account_pair.pubkey(), commitment_config) {
-        Some(_) => {}
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let (account, _) = find_derived_account_address(program_owner, &wallet_signer.pubkey(), seed);
+    match get_account_for(rpc_client, &account, commitment_config) {
+        Some(_) => Ok(account),
         None => new_account(
             rpc_client,
             wallet_signer,
-            account_pair,
             program_owner,
-            space,
+            seed,
             commitment_config,
-        )
-        .unwrap(),
-    };
-    Ok(())
+        ),
+    }
 }
 
 /// Submits the program instruction as per the
@@ -175,14 +148,25 @@ pub fn submit_transaction(
     wallet_signer: &dyn Signer,
     instruction: Instruction,
     commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    submit_transaction_with_signers(rpc_client, &[wallet_signer], instruction, commitment_config)
+}
+
+/// Submits the program instruction signed by all of the signers, the first
+/// of which pays for the transaction
+pub fn submit_transaction_with_signers(
+    rpc_client: &RpcClient,
+    signers: &[&dyn Signer],
+    instruction: Instruction,
+    commitment_config: CommitmentConfig,
 ) -> Result<Signature, Box<dyn std::error::Error>> {
     let mut transaction =
-        Transaction::new_unsigned(Message::new(&[instruction], Some(&wallet_signer.pubkey())));
+        Transaction::new_unsigned(Message::new(&[instruction], Some(&signers[0].pubkey())));
     let recent_blockhash = rpc_client
         .get_latest_blockhash()
         .map_err(|err| format!("error: unable to get recent blockhash: {}", err))?;
     transaction
-        .try_sign(&vec![wallet_signer], recent_blockhash)
+        .try_sign(&signers.to_vec(), recent_blockhash)
         .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
     let signature = rpc_client
         .send_and_confirm_transaction_with_spinner_and_commitment(&transaction, commitment_config)
@@ -190,55 +174,840 @@ pub fn submit_transaction(
     Ok(signature)
 }
 
+/// Checks key/value pairs about to be added to the account against the
+/// key/value limits the program enforces, read from the program config, so
+/// a transaction that would fail is not submitted. Keys the account already
+/// holds are not counted again
+pub fn check_key_values<'a>(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    key_values: impl IntoIterator<Item = (&'a str, &'a Value)>,
+    commitment_config: CommitmentConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let limits = unpack_key_value_limits(rpc_client, commitment_config)?;
+    let account_state = unpack_account_data(rpc_client, account, commitment_config)?;
+    let mut count = account_state.btree_storage.len();
+    for (key, value) in key_values {
+        limits.check_key(key)?;
+        limits.check_value(value)?;
+        if !account_state.btree_storage.contains_key(key) {
+            count += 1;
+        }
+    }
+    limits.check_entry_count(count)?;
+    Ok(())
+}
+
+/// Checks the key/value pairs about to be transferred from one account to
+/// another against the key/value limits, as `check_key_values` does for the
+/// receiving account. Keys the sending account does not hold are left for
+/// the program to refuse
+fn check_transfer_key_values<'a>(
+    rpc_client: &RpcClient,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    keys: impl IntoIterator<Item = &'a str>,
+    commitment_config: CommitmentConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let from_state = unpack_account_data(rpc_client, from_account, commitment_config)?;
+    check_key_values(
+        rpc_client,
+        to_account,
+        keys.into_iter().filter_map(|key| {
+            from_state
+                .btree_storage
+                .get_key_value(key)
+                .map(|(key, value)| (key.as_str(), value))
+        }),
+        commitment_config,
+    )
+}
+
+/// Returns the signers of the authority when it is a multisig, which are
+/// the signers following the paying wallet when the authority is not itself
+/// one of the signers
+fn multisig_signers_of(authority: &Pubkey, signers: &[&dyn Signer]) -> Vec<Pubkey> {
+    if signers.iter().any(|signer| signer.pubkey() == *authority) {
+        Vec::new()
+    } else {
+        signers
+            .iter()
+            .skip(1)
+            .map(|signer| signer.pubkey())
+            .collect()
+    }
+}
+
 /// Perform a mint transaction consisting of a key/value pair
 pub fn mint_transaction(
     rpc_client: &RpcClient,
-    accounts: &[AccountMeta],
-    wallet_signer: &dyn Signer,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
     mint_key: &str,
-    mint_value: &str,
+    mint_value: &Value,
     commitment_config: CommitmentConfig,
 ) -> Result<Signature, Box<dyn std::error::Error>> {
-    let instruction = Instruction::new_with_borsh(
-        PROG_KEY.pubkey(),
-        &ProgramInstruction::MintToAccount(mint_key.to_string(), mint_value.to_string()),
-        accounts.to_vec(),
+    check_key_values(
+        rpc_client,
+        account,
+        [(mint_key, mint_value)],
+        commitment_config,
+    )?;
+    let instruction = instruction::mint_to_account(
+        &PROG_KEY.pubkey(),
+        account,
+        authority,
+        &multisig_signers_of(authority, signers),
+        mint_key,
+        mint_value,
     );
-    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Perform a mint transaction consisting of a key/value pair that expires
+#[allow(clippy::too_many_arguments)]
+pub fn mint_with_expiry_transaction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    mint_key: &str,
+    mint_value: &Value,
+    expiry: Expiry,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    check_key_values(
+        rpc_client,
+        account,
+        [(mint_key, mint_value)],
+        commitment_config,
+    )?;
+    let instruction = instruction::mint_to_account_with_expiry(
+        &PROG_KEY.pubkey(),
+        account,
+        authority,
+        &multisig_signers_of(authority, signers),
+        mint_key,
+        mint_value,
+        expiry,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Purge the expired key/values from an account, which anyone may sign for
+pub fn purge_expired_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    signer: &dyn Signer,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::purge_expired(&PROG_KEY.pubkey(), account);
+    submit_transaction(rpc_client, signer, instruction, commitment_config)
 }
 
 /// Transfer a minted key/value from one account to another account
 pub fn transfer_instruction(
     rpc_client: &RpcClient,
-    accounts: &[AccountMeta],
-    wallet_signer: &dyn Signer,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
     transfer_key: &str,
     commitment_config: CommitmentConfig,
 ) -> Result<Signature, Box<dyn std::error::Error>> {
-    let instruction = Instruction::new_with_borsh(
-        PROG_KEY.pubkey(),
-        &ProgramInstruction::TransferBetweenAccounts(transfer_key.to_string()),
-        accounts.to_vec(),
+    check_transfer_key_values(
+        rpc_client,
+        from_account,
+        to_account,
+        [transfer_key],
+        commitment_config,
+    )?;
+    let instruction = instruction::transfer_between_accounts(
+        &PROG_KEY.pubkey(),
+        from_account,
+        to_account,
+        authority,
+        &multisig_signers_of(authority, signers),
+        transfer_key,
     );
-    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
 }
 
 /// Burn, delete, the key/value from the owning account
 pub fn burn_instruction(
     rpc_client: &RpcClient,
-    accounts: &[AccountMeta],
-    wallet_signer: &dyn Signer,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
     burn_key: &str,
     commitment_config: CommitmentConfig,
 ) -> Result<Signature, Box<dyn std::error::Error>> {
-    let instruction = Instruction::new_with_borsh(
-        PROG_KEY.pubkey(),
-        &ProgramInstruction::BurnFromAccount(burn_key.to_string()),
-        accounts.to_vec(),
+    let instruction = instruction::burn_from_account(
+        &PROG_KEY.pubkey(),
+        account,
+        authority,
+        &multisig_signers_of(authority, signers),
+        burn_key,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Update the value of an existing key in the owning account
+pub fn update_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet_signer: &dyn Signer,
+    update_key: &str,
+    update_value: &Value,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    unpack_key_value_limits(rpc_client, commitment_config)?.check_value(update_value)?;
+    let instruction = instruction::update_value(
+        &PROG_KEY.pubkey(),
+        account,
+        &wallet_signer.pubkey(),
+        &[],
+        update_key,
+        update_value,
+    );
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Update the value of a key in the owning account, adding the key if it does not exist
+pub fn upsert_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet_signer: &dyn Signer,
+    upsert_key: &str,
+    upsert_value: &Value,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    check_key_values(
+        rpc_client,
+        account,
+        [(upsert_key, upsert_value)],
+        commitment_config,
+    )?;
+    let instruction = instruction::upsert_value(
+        &PROG_KEY.pubkey(),
+        account,
+        &wallet_signer.pubkey(),
+        &[],
+        upsert_key,
+        upsert_value,
+    );
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Resize the owning account, signed by its authority, funding or refunding
+/// rent from the paying wallet, which is the first of the signers
+pub fn resize_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    new_size: u64,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::resize(
+        &PROG_KEY.pubkey(),
+        account,
+        &signers[0].pubkey(),
+        authority,
+        &multisig_signers_of(authority, signers),
+        new_size,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Close the owning account, moving its lamports to the destination
+pub fn close_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    destination: &Pubkey,
+    wallet_signer: &dyn Signer,
+    require_empty: bool,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::close_account(
+        &PROG_KEY.pubkey(),
+        account,
+        destination,
+        &wallet_signer.pubkey(),
+        &[],
+        require_empty,
+    );
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Migrate the account from an earlier layout to the current layout, the
+/// wallet funding any growth of the account. An account of the `V0` layout
+/// must also be signed for by the account keypair, and the wallet becomes
+/// its authority
+pub fn migrate_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet_signer: &dyn Signer,
+    account_signer: Option<&dyn Signer>,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::migrate_account(
+        &PROG_KEY.pubkey(),
+        account,
+        &wallet_signer.pubkey(),
+        &wallet_signer.pubkey(),
+        &[],
+        account_signer.is_some(),
+    );
+    let mut signers = vec![wallet_signer];
+    signers.extend(account_signer);
+    submit_transaction_with_signers(rpc_client, &signers, instruction, commitment_config)
+}
+
+/// Build a swap of key_a, from the first account, for key_b, from the second
+/// account, partially signed by the wallet owning the first account who also
+/// pays for the transaction. The wallet owning the second account must sign
+/// before the recent blockhash expires
+pub fn swap_partial_transaction(
+    rpc_client: &RpcClient,
+    account_a: &Pubkey,
+    account_b: &Pubkey,
+    authority_b: &Pubkey,
+    wallet_signer: &dyn Signer,
+    key_a: &str,
+    key_b: &str,
+) -> Result<Transaction, Box<dyn std::error::Error>> {
+    let instruction = instruction::swap_keys(
+        &PROG_KEY.pubkey(),
+        account_a,
+        account_b,
+        &wallet_signer.pubkey(),
+        &[],
+        authority_b,
+        &[],
+        key_a,
+        key_b,
+    );
+    let mut transaction =
+        Transaction::new_unsigned(Message::new(&[instruction], Some(&wallet_signer.pubkey())));
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| format!("error: unable to get recent blockhash: {}", err))?;
+    transaction
+        .try_partial_sign(&vec![wallet_signer], recent_blockhash)
+        .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
+    Ok(transaction)
+}
+
+/// Completes the signing of a partially signed transaction and submits it
+pub fn submit_partial_transaction(
+    rpc_client: &RpcClient,
+    mut transaction: Transaction,
+    wallet_signer: &dyn Signer,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let recent_blockhash = transaction.message.recent_blockhash;
+    transaction
+        .try_partial_sign(&vec![wallet_signer], recent_blockhash)
+        .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
+    if !transaction.is_signed() {
+        return Err(Box::<dyn std::error::Error>::from(
+            "error: transaction is missing signatures",
+        ));
+    }
+    let signature = rpc_client
+        .send_and_confirm_transaction_with_spinner_and_commitment(&transaction, commitment_config)
+        .map_err(|err| format!("error: send transaction: {}", err))?;
+    Ok(signature)
+}
+
+/// Approve a delegate to transfer a key from the owning account
+pub fn approve_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet_signer: &dyn Signer,
+    approve_key: &str,
+    delegate: &Pubkey,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::approve(
+        &PROG_KEY.pubkey(),
+        account,
+        &wallet_signer.pubkey(),
+        &[],
+        approve_key,
+        delegate,
     );
     submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
 }
 
+/// Revoke the delegate of a key in the owning account
+pub fn revoke_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    wallet_signer: &dyn Signer,
+    revoke_key: &str,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::revoke(
+        &PROG_KEY.pubkey(),
+        account,
+        &wallet_signer.pubkey(),
+        &[],
+        revoke_key,
+    );
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Freeze the owning account, signed by its freeze authority
+pub fn freeze_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signers: &[&dyn Signer],
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::freeze_account(
+        &PROG_KEY.pubkey(),
+        account,
+        freeze_authority,
+        &multisig_signers_of(freeze_authority, signers),
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Thaw the owning account, signed by its freeze authority
+pub fn thaw_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signers: &[&dyn Signer],
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::thaw_account(
+        &PROG_KEY.pubkey(),
+        account,
+        freeze_authority,
+        &multisig_signers_of(freeze_authority, signers),
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Lock a key in the owning account, signed by its freeze authority
+pub fn lock_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signers: &[&dyn Signer],
+    lock_key: &str,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::lock_key(
+        &PROG_KEY.pubkey(),
+        account,
+        freeze_authority,
+        &multisig_signers_of(freeze_authority, signers),
+        lock_key,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Unlock a key in the owning account, signed by its freeze authority
+pub fn unlock_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signers: &[&dyn Signer],
+    unlock_key: &str,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::unlock_key(
+        &PROG_KEY.pubkey(),
+        account,
+        freeze_authority,
+        &multisig_signers_of(freeze_authority, signers),
+        unlock_key,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Hand the owning account's freeze authority to a new wallet, signed by
+/// the current freeze authority
+pub fn set_freeze_authority_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    freeze_authority: &Pubkey,
+    signers: &[&dyn Signer],
+    new_freeze_authority: &Pubkey,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::set_freeze_authority(
+        &PROG_KEY.pubkey(),
+        account,
+        freeze_authority,
+        &multisig_signers_of(freeze_authority, signers),
+        new_freeze_authority,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Create the program config account with the fee schedule and treasury,
+/// the wallet becoming its admin
+pub fn initialize_config_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    fees: ServiceFees,
+    treasury: &Pubkey,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction =
+        instruction::initialize_config(&PROG_KEY.pubkey(), &wallet_signer.pubkey(), fees, treasury);
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Create the program registry account, the wallet paying for it
+pub fn initialize_registry_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::initialize_registry(&PROG_KEY.pubkey(), &wallet_signer.pubkey());
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Replace the fee schedule in the program config account, the wallet
+/// must be its admin
+pub fn set_fees_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    fees: ServiceFees,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::set_fees(&PROG_KEY.pubkey(), &wallet_signer.pubkey(), fees);
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Replace the treasury in the program config account, the wallet must be
+/// its admin
+pub fn set_treasury_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    treasury: &Pubkey,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction =
+        instruction::set_treasury(&PROG_KEY.pubkey(), &wallet_signer.pubkey(), treasury);
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Pause the program, refusing every instruction but the admin's and the
+/// read-only ones, the wallet must be the admin of the program config
+pub fn pause_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::pause(&PROG_KEY.pubkey(), &wallet_signer.pubkey());
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Unpause the program, the wallet must be the admin of the program config
+pub fn unpause_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::unpause(&PROG_KEY.pubkey(), &wallet_signer.pubkey());
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Replace the key/value limits the program enforces, the wallet must be
+/// the admin of the program config
+pub fn set_key_value_limits_instruction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    limits: KeyValueLimits,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction =
+        instruction::set_key_value_limits(&PROG_KEY.pubkey(), &wallet_signer.pubkey(), limits);
+    submit_transaction(rpc_client, wallet_signer, instruction, commitment_config)
+}
+
+/// Create a multisig account requiring `m` of the signers, funded by the
+/// wallet and initialized in the same transaction. Returns the address of
+/// the multisig
+pub fn create_multisig_transaction(
+    rpc_client: &RpcClient,
+    wallet_signer: &dyn Signer,
+    m: u8,
+    signers: &[Pubkey],
+    commitment_config: CommitmentConfig,
+) -> Result<(Pubkey, Signature), Box<dyn std::error::Error>> {
+    let multisig = Keypair::new();
+    let lamports = rpc_client.get_minimum_balance_for_rent_exemption(MULTISIG_STATE_SPACE)?;
+    let instructions = [
+        system_instruction::create_account(
+            &wallet_signer.pubkey(),
+            &multisig.pubkey(),
+            lamports,
+            MULTISIG_STATE_SPACE as u64,
+            &PROG_KEY.pubkey(),
+        ),
+        instruction::initialize_multisig(&PROG_KEY.pubkey(), &multisig.pubkey(), signers, m),
+    ];
+    let mut transaction =
+        Transaction::new_unsigned(Message::new(&instructions, Some(&wallet_signer.pubkey())));
+    let recent_blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| format!("error: unable to get recent blockhash: {}", err))?;
+    transaction
+        .try_sign(&vec![wallet_signer, &multisig], recent_blockhash)
+        .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
+    let signature = rpc_client
+        .send_and_confirm_transaction_with_spinner_and_commitment(&transaction, commitment_config)
+        .map_err(|err| format!("error: send transaction: {}", err))?;
+    Ok((multisig.pubkey(), signature))
+}
+
+/// Hand the owning account to a new authority, a wallet or a multisig. The
+/// current authority must sign
+pub fn set_authority_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    new_authority: &Pubkey,
+    commitment_config: CommitmentConfig,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let instruction = instruction::set_authority(
+        &PROG_KEY.pubkey(),
+        account,
+        authority,
+        &multisig_signers_of(authority, signers),
+        new_authority,
+    );
+    submit_transaction_with_signers(rpc_client, signers, instruction, commitment_config)
+}
+
+/// Simulates a read only instruction, with the payer paying
+/// the simulated fee, and decodes the borsh encoded data the program returns.
+/// The runtime drops trailing zero bytes from the return data, so they are
+/// restored before decoding
+fn simulate_return_data<T: BorshDeserialize>(
+    rpc_client: &RpcClient,
+    payer: &Pubkey,
+    instruction: Instruction,
+    commitment_config: CommitmentConfig,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(payer)));
+    let simulation = rpc_client
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(commitment_config),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .map_err(|err| format!("error: simulate transaction: {}", err))?
+        .value;
+    if let Some(err) = simulation.err {
+        return Err(format!("error: simulate transaction: {}", err).into());
+    }
+    let mut data = match simulation.return_data {
+        Some(return_data) => base64::decode(return_data.data.0)?,
+        None => Vec::new(),
+    };
+    data.resize(MAX_RETURN_DATA, 0);
+    Ok(T::deserialize(&mut data.as_slice())?)
+}
+
+/// Gets the value of the key in the account, or none if the account does
+/// not hold it or it expired, by simulating `GetValue`
+pub fn get_value_simulation(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    payer: &Pubkey,
+    key: &str,
+    commitment_config: CommitmentConfig,
+) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    simulate_return_data(
+        rpc_client,
+        payer,
+        instruction::get_value(&PROG_KEY.pubkey(), account, key),
+        commitment_config,
+    )
+}
+
+/// Checks whether the account holds the key and it has not expired by
+/// simulating `ContainsKey`
+pub fn contains_key_simulation(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    payer: &Pubkey,
+    key: &str,
+    commitment_config: CommitmentConfig,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    simulate_return_data(
+        rpc_client,
+        payer,
+        instruction::contains_key(&PROG_KEY.pubkey(), account, key),
+        commitment_config,
+    )
+}
+
+/// Returns true if the transaction of the instruction, paid for by the
+/// payer and signed by every signer the instruction requires, fits in a
+/// transaction packet
+fn fits_in_packet(instruction: Instruction, payer: &Pubkey) -> bool {
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(payer)));
+    bincode::serialized_size(&transaction).map_or(false, |size| size as usize <= PACKET_DATA_SIZE)
+}
+
+/// Splits items into chunks whose batched instruction, built by
+/// `build_instruction`, fits in a transaction paid for by the payer. The
+/// size is that of the whole transaction, so the accounts and signatures of
+/// the instruction, such as the signers of a multisig authority, are counted
+pub fn batch_chunks<'a, T>(
+    items: &'a [T],
+    payer: &Pubkey,
+    build_instruction: impl Fn(&[T]) -> Instruction,
+) -> Vec<&'a [T]> {
+    let mut chunks = Vec::<&[T]>::new();
+    let mut chunk_start = 0;
+    for index in 0..items.len() {
+        if index > chunk_start
+            && !fits_in_packet(build_instruction(&items[chunk_start..=index]), payer)
+        {
+            chunks.push(&items[chunk_start..index]);
+            chunk_start = index;
+        }
+    }
+    if chunk_start < items.len() {
+        chunks.push(&items[chunk_start..]);
+    }
+    chunks
+}
+
+/// Mint many key/value pairs, one transaction per chunk that fits a transaction
+///
+/// Each transaction mints all or none of its chunk
+pub fn mint_many_transaction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    key_values: &[(String, Value)],
+    commitment_config: CommitmentConfig,
+) -> Result<Vec<Signature>, Box<dyn std::error::Error>> {
+    check_key_values(
+        rpc_client,
+        account,
+        key_values.iter().map(|(key, value)| (key.as_str(), value)),
+        commitment_config,
+    )?;
+    let multisig_signers = multisig_signers_of(authority, signers);
+    let build_instruction = |chunk: &[(String, Value)]| {
+        instruction::mint_many(
+            &PROG_KEY.pubkey(),
+            account,
+            authority,
+            &multisig_signers,
+            chunk,
+        )
+    };
+    let mut signatures = Vec::<Signature>::new();
+    for chunk in batch_chunks(key_values, &signers[0].pubkey(), build_instruction) {
+        signatures.push(submit_transaction_with_signers(
+            rpc_client,
+            signers,
+            build_instruction(chunk),
+            commitment_config,
+        )?);
+    }
+    Ok(signatures)
+}
+
+/// Transfer many minted keys from one account to another account, one
+/// transaction per chunk that fits a transaction
+///
+/// Each transaction transfers all or none of its chunk
+pub fn transfer_many_instruction(
+    rpc_client: &RpcClient,
+    from_account: &Pubkey,
+    to_account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    transfer_keys: &[String],
+    commitment_config: CommitmentConfig,
+) -> Result<Vec<Signature>, Box<dyn std::error::Error>> {
+    check_transfer_key_values(
+        rpc_client,
+        from_account,
+        to_account,
+        transfer_keys.iter().map(String::as_str),
+        commitment_config,
+    )?;
+    let multisig_signers = multisig_signers_of(authority, signers);
+    let build_instruction = |chunk: &[String]| {
+        instruction::transfer_many(
+            &PROG_KEY.pubkey(),
+            from_account,
+            to_account,
+            authority,
+            &multisig_signers,
+            chunk,
+        )
+    };
+    let mut signatures = Vec::<Signature>::new();
+    for chunk in batch_chunks(transfer_keys, &signers[0].pubkey(), build_instruction) {
+        signatures.push(submit_transaction_with_signers(
+            rpc_client,
+            signers,
+            build_instruction(chunk),
+            commitment_config,
+        )?);
+    }
+    Ok(signatures)
+}
+
+/// Burn many keys from the owning account, one transaction per chunk that
+/// fits a transaction
+///
+/// Each transaction burns all or none of its chunk
+pub fn burn_many_instruction(
+    rpc_client: &RpcClient,
+    account: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&dyn Signer],
+    burn_keys: &[String],
+    commitment_config: CommitmentConfig,
+) -> Result<Vec<Signature>, Box<dyn std::error::Error>> {
+    let multisig_signers = multisig_signers_of(authority, signers);
+    let build_instruction = |chunk: &[String]| {
+        instruction::burn_many(
+            &PROG_KEY.pubkey(),
+            account,
+            authority,
+            &multisig_signers,
+            chunk,
+        )
+    };
+    let mut signatures = Vec::<Signature>::new();
+    for chunk in batch_chunks(burn_keys, &signers[0].pubkey(), build_instruction) {
+        signatures.push(submit_transaction_with_signers(
+            rpc_client,
+            signers,
+            build_instruction(chunk),
+            commitment_config,
+        )?);
+    }
+    Ok(signatures)
+}
+
 pub fn ping_instruction(
     rpc_client: &RpcClient,
     signer: &dyn Signer,
@@ -252,3 +1021,147 @@ pub fn ping_instruction(
         commitment_config,
     )
 }
+
+/// Decodes the events the program logged with `sol_log_data` from the log
+/// messages of a transaction. Data logged by other programs, including those
+/// the program invokes, is skipped
+pub fn decode_program_events(program_id: &Pubkey, log_messages: &[String]) -> Vec<ProgramEvent> {
+    let mut events = Vec::<ProgramEvent>::new();
+    // Whether each program of the invocation stack is the program
+    let mut invocations = Vec::<bool>::new();
+    for message in log_messages {
+        if let Some(fields) = message.strip_prefix("Program data: ") {
+            if invocations.last() == Some(&true) {
+                events.extend(
+                    fields
+                        .split(' ')
+                        .filter_map(|field| base64::decode(field).ok())
+                        .filter_map(|data| ProgramEvent::try_from_slice(&data).ok()),
+                );
+            }
+            continue;
+        }
+        let mut words = message.split(' ');
+        match (
+            words.next(),
+            words.next().map(str::parse::<Pubkey>),
+            words.next(),
+        ) {
+            (Some("Program"), Some(Ok(invoked)), Some("invoke")) => {
+                invocations.push(invoked == *program_id)
+            }
+            (Some("Program"), Some(Ok(_)), Some("success" | "failed:")) => {
+                invocations.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Fetches the confirmed transaction and decodes the events the program
+/// logged
+pub fn fetch_program_events(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    commitment_config: CommitmentConfig,
+) -> Result<Vec<ProgramEvent>, Box<dyn std::error::Error>> {
+    let transaction = rpc_client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(commitment_config),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let log_messages = match transaction.transaction.meta {
+        Some(meta) => match meta.log_messages {
+            OptionSerializer::Some(log_messages) => log_messages,
+            _ => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    Ok(decode_program_events(&PROG_KEY.pubkey(), &log_messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn test_batch_chunks() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let keys: Vec<String> = (0..200).map(|i| format!("test_key_{}", i)).collect();
+
+        // A wallet authority and a multisig authority with five signers,
+        // whose signatures and accounts leave less room for keys
+        let signers: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let mut chunk_counts = Vec::<usize>::new();
+        for signer_pubkeys in [&[][..], &signers[..]] {
+            let build_instruction = |chunk: &[String]| {
+                instruction::burn_many(&program_id, &account, &authority, signer_pubkeys, chunk)
+            };
+            let chunks = batch_chunks(&keys, &payer, build_instruction);
+            assert!(chunks.len() > 1);
+            assert_eq!(chunks.concat(), keys);
+            // Each chunk fits and, but for the last, one more key would not
+            let mut chunk_start = 0;
+            for chunk in &chunks {
+                assert!(fits_in_packet(build_instruction(chunk), &payer));
+                let chunk_end = chunk_start + chunk.len();
+                if chunk_end < keys.len() {
+                    assert!(!fits_in_packet(
+                        build_instruction(&keys[chunk_start..=chunk_end]),
+                        &payer
+                    ));
+                }
+                chunk_start = chunk_end;
+            }
+            chunk_counts.push(chunks.len());
+        }
+        assert!(chunk_counts[1] > chunk_counts[0]);
+        assert!(batch_chunks::<String>(&[], &payer, |_| unreachable!()).is_empty());
+    }
+
+    #[test]
+    fn test_decode_program_events() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let event_data = |event: &ProgramEvent| base64::encode(event.try_to_vec().unwrap());
+        let minted = ProgramEvent::Minted {
+            account,
+            key: "key".to_string(),
+            value: Value::U64(1),
+            expiry: None,
+        };
+        let burned = ProgramEvent::Burned {
+            account,
+            key: "key".to_string(),
+        };
+        let log_messages: Vec<String> = [
+            format!("Program {} invoke [1]", program_id),
+            "Program log: success".to_string(),
+            format!("Program data: {}", event_data(&minted)),
+            format!("Program {} invoke [2]", other_program_id),
+            format!("Program data: {}", event_data(&burned)),
+            format!("Program {} success", other_program_id),
+            format!("Program data: {} bm90IGFuIGV2ZW50", event_data(&burned)),
+            format!(
+                "Program {} consumed 1000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+            format!("Program data: {}", event_data(&minted)),
+        ]
+        .to_vec();
+        assert_eq!(
+            decode_program_events(&program_id, &log_messages),
+            vec![minted, burned]
+        );
+    }
+}
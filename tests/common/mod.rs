@@ -2,12 +2,12 @@
 
 use {
     cli_program_template::prelude::{
-        get_account_for, load_account, load_wallet, KEYS_DB, PROG_KEY,
+        get_account_for, load_account, load_wallet, ACCOUNT_SEED, KEYS_DB, PROG_KEY,
     },
-    sol_template_shared::ACCOUNT_STATE_SPACE,
     solana_client::rpc_client::RpcClient,
     solana_sdk::{
         commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
         signature::{read_keypair_file, Keypair},
         signer::Signer,
     },
@@ -57,28 +57,27 @@ pub fn load_user_wallets<'a>(
     wallets
 }
 
-/// Batch load all user wallets and accounts (User1, User2, Service)
-pub fn load_and_initialize_accounts<'a>(
+/// Batch load all user wallets and derived accounts (User1, User2, Service)
+pub fn load_and_initialize_accounts(
     rpc_client: &RpcClient,
     commitment_config: CommitmentConfig,
-) -> Vec<&'a Keypair> {
-    let mut accounts = Vec::<&Keypair>::new();
+) -> Vec<Pubkey> {
+    let mut accounts = Vec::<Pubkey>::new();
     for holder in KEYS_DB.key_owners() {
         let (wallet, account) = KEYS_DB.wallet_and_account(holder.clone()).unwrap();
         // May already exist
-        if let Some(_account) = get_account_for(rpc_client, &account.pubkey(), commitment_config) {
+        if let Some(_account) = get_account_for(rpc_client, &account, commitment_config) {
             accounts.push(account);
         } else {
             let result = load_account(
                 rpc_client,
-                account,
                 wallet,
                 &PROG_KEY.pubkey(),
-                ACCOUNT_STATE_SPACE as u64,
+                ACCOUNT_SEED,
                 commitment_config,
             );
             assert!(result.is_ok());
-            accounts.push(account);
+            accounts.push(result.unwrap());
         }
     }
     accounts
@@ -10,11 +10,12 @@ pub mod common;
 
 use {
     cli_program_template::prelude::{
-        burn_instruction, get_account_for, mint_transaction, transfer_instruction,
-        unpack_account_data, KEYS_DB, PROG_KEY,
+        burn_instruction, contains_key_simulation, get_account_for, get_value_simulation,
+        mint_transaction, transfer_instruction, unpack_account_data, KEYS_DB, PROG_KEY,
     },
     common::{clean_ledger_setup_validator, load_and_initialize_accounts, load_user_wallets},
-    solana_sdk::{commitment_config::CommitmentConfig, instruction::AccountMeta, signer::Signer},
+    sol_template_shared::Value,
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer},
 };
 
 #[test]
@@ -45,7 +46,9 @@ fn test_wallet_and_account_initialization_pass() {
     let initialized_accounts = load_and_initialize_accounts(&rpc_client, cc);
     assert_eq!(initialized_accounts.len(), 3);
     for account in initialized_accounts {
-        let (initialized, _) = unpack_account_data(&rpc_client, account, cc).unwrap();
+        let initialized = unpack_account_data(&rpc_client, &account, cc)
+            .unwrap()
+            .is_initialized;
         assert!(initialized);
     }
 }
@@ -63,58 +66,67 @@ fn test_load_mint_transfer_burn_no_fee_pass() {
     let user1 = String::from("User1");
     let user2 = String::from("User2");
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
     let (wallet1, account1) = KEYS_DB.wallet_and_account(user1).unwrap();
     let (wallet2, account2) = KEYS_DB.wallet_and_account(user2).unwrap();
 
     // Do mint to User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         cc,
     );
     assert!(mint_result.is_ok());
-    let (_, btree) = unpack_account_data(&rpc_client, account1, cc).unwrap();
+    let btree = unpack_account_data(&rpc_client, &account1, cc)
+        .unwrap()
+        .btree_storage;
     assert!(btree.contains_key(&mint_key));
 
     // Do transfer of key/value from User1 to User2
     let transfer_result = transfer_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &account2,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         cc,
     );
     assert!(transfer_result.is_ok());
-    let (_, btree1) = unpack_account_data(&rpc_client, account1, cc).unwrap();
-    let (_, btree2) = unpack_account_data(&rpc_client, account2, cc).unwrap();
+    let btree1 = unpack_account_data(&rpc_client, &account1, cc)
+        .unwrap()
+        .btree_storage;
+    let btree2 = unpack_account_data(&rpc_client, &account2, cc)
+        .unwrap()
+        .btree_storage;
     assert!(!btree1.contains_key(&mint_key));
     assert!(btree2.contains_key(&mint_key));
     assert_eq!(btree2.get(&mint_key).unwrap(), &mint_value);
 
+    // Read the key/value back as the program returns it
+    let get_result = get_value_simulation(&rpc_client, &account2, &wallet2.pubkey(), &mint_key, cc);
+    assert_eq!(get_result.unwrap(), Some(mint_value));
+    let contains_result =
+        contains_key_simulation(&rpc_client, &account1, &wallet1.pubkey(), &mint_key, cc);
+    assert!(!contains_result.unwrap());
+
     // Burn the key/value just transfered to User2
     let burn_result = burn_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet2.pubkey(), true),
-        ],
-        wallet2,
+        &account2,
+        &wallet2.pubkey(),
+        &[wallet2],
         &mint_key,
         cc,
     );
     assert!(burn_result.is_ok());
-    let (_, btree2) = unpack_account_data(&rpc_client, account2, cc).unwrap();
+    let btree2 = unpack_account_data(&rpc_client, &account2, cc)
+        .unwrap()
+        .btree_storage;
     assert!(!btree2.contains_key(&mint_key));
 }
 
@@ -131,39 +143,57 @@ fn test_mint_transfer_burn_fail() {
     let user1 = String::from("User1");
     let user2 = String::from("User2");
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
     let bad_key = String::from("bad_key_1");
     let (wallet1, account1) = KEYS_DB.wallet_and_account(user1).unwrap();
     let (wallet2, account2) = KEYS_DB.wallet_and_account(user2).unwrap();
 
-    // Fail empty accounts
-    let mint_result = mint_transaction(&rpc_client, &[], wallet1, &mint_key, &mint_value, cc);
+    // Fail an account that does not exist
+    let mint_result = mint_transaction(
+        &rpc_client,
+        &Pubkey::new_unique(),
+        &wallet1.pubkey(),
+        &[wallet1],
+        &mint_key,
+        &mint_value,
+        cc,
+    );
+    assert!(mint_result.is_err());
+
+    // Fail an empty key before submitting
+    let mint_result = mint_transaction(
+        &rpc_client,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
+        "",
+        &mint_value,
+        cc,
+    );
     assert!(mint_result.is_err());
 
     // Do mint to User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         cc,
     );
     assert!(mint_result.is_ok());
-    let (_, btree) = unpack_account_data(&rpc_client, account1, cc).unwrap();
+    let btree = unpack_account_data(&rpc_client, &account1, cc)
+        .unwrap()
+        .btree_storage;
     assert!(btree.contains_key(&mint_key));
 
     // Attempt to mint something already minted for User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         cc,
@@ -173,12 +203,10 @@ fn test_mint_transfer_burn_fail() {
     // Attempt to transfer something that does exist
     let transfer_result = transfer_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &account2,
+        &wallet1.pubkey(),
+        &[wallet1],
         &bad_key,
         cc,
     );
@@ -187,11 +215,9 @@ fn test_mint_transfer_burn_fail() {
     // Attempt to burn something that does not exist
     let burn_result = burn_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet2.pubkey(), true),
-        ],
-        wallet2,
+        &account2,
+        &wallet2.pubkey(),
+        &[wallet2],
         &mint_key,
         cc,
     );
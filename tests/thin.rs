@@ -24,7 +24,8 @@ use {
         unpack_account_data, KEYS_DB, PROG_KEY,
     },
     common::{load_and_initialize_accounts, load_user_wallets, rpc_client_from_config},
-    solana_sdk::{instruction::AccountMeta, signer::Signer},
+    sol_template_shared::Value,
+    solana_sdk::{pubkey::Pubkey, signer::Signer},
 };
 
 #[test]
@@ -56,8 +57,9 @@ fn test_wallet_and_account_initialization_pass() {
     let initialized_accounts = load_and_initialize_accounts(&rpc_client, rpc_client.commitment());
     assert_eq!(initialized_accounts.len(), 3);
     for account in initialized_accounts {
-        let (initialized, _) =
-            unpack_account_data(&rpc_client, account, rpc_client.commitment()).unwrap();
+        let initialized = unpack_account_data(&rpc_client, &account, rpc_client.commitment())
+            .unwrap()
+            .is_initialized;
         assert!(initialized);
     }
 }
@@ -72,41 +74,43 @@ fn test_load_mint_transfer_burn_no_fee_pass() {
     let user1 = String::from("User1");
     let user2 = String::from("User2");
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
     let (wallet1, account1) = KEYS_DB.wallet_and_account(user1).unwrap();
     let (wallet2, account2) = KEYS_DB.wallet_and_account(user2).unwrap();
 
     // Do mint to User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         rpc_client.commitment(),
     );
     assert!(mint_result.is_ok());
-    let (_, btree) = unpack_account_data(&rpc_client, account1, rpc_client.commitment()).unwrap();
+    let btree = unpack_account_data(&rpc_client, &account1, rpc_client.commitment())
+        .unwrap()
+        .btree_storage;
     assert!(btree.contains_key(&mint_key));
 
     // Do transfer of key/value from User1 to User2
     let transfer_result = transfer_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &account2,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         rpc_client.commitment(),
     );
     assert!(transfer_result.is_ok());
-    let (_, btree1) = unpack_account_data(&rpc_client, account1, rpc_client.commitment()).unwrap();
-    let (_, btree2) = unpack_account_data(&rpc_client, account2, rpc_client.commitment()).unwrap();
+    let btree1 = unpack_account_data(&rpc_client, &account1, rpc_client.commitment())
+        .unwrap()
+        .btree_storage;
+    let btree2 = unpack_account_data(&rpc_client, &account2, rpc_client.commitment())
+        .unwrap()
+        .btree_storage;
     assert!(!btree1.contains_key(&mint_key));
     assert!(btree2.contains_key(&mint_key));
     assert_eq!(btree2.get(&mint_key).unwrap(), &mint_value);
@@ -114,16 +118,16 @@ fn test_load_mint_transfer_burn_no_fee_pass() {
     // Burn the key/value just transfered to User2
     let burn_result = burn_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet2.pubkey(), true),
-        ],
-        wallet2,
+        &account2,
+        &wallet2.pubkey(),
+        &[wallet2],
         &mint_key,
         rpc_client.commitment(),
     );
     assert!(burn_result.is_ok());
-    let (_, btree2) = unpack_account_data(&rpc_client, account2, rpc_client.commitment()).unwrap();
+    let btree2 = unpack_account_data(&rpc_client, &account2, rpc_client.commitment())
+        .unwrap()
+        .btree_storage;
     assert!(!btree2.contains_key(&mint_key));
 }
 
@@ -138,46 +142,57 @@ fn test_mint_transfer_burn_fail() {
     let user1 = String::from("User1");
     let user2 = String::from("User2");
     let mint_key = String::from("test_key_1");
-    let mint_value = String::from("value for test_key_1");
+    let mint_value = Value::from("value for test_key_1");
     let bad_key = String::from("bad_key_1");
     let (wallet1, account1) = KEYS_DB.wallet_and_account(user1).unwrap();
     let (wallet2, account2) = KEYS_DB.wallet_and_account(user2).unwrap();
 
-    // Fail empty accounts
+    // Fail an account that does not exist
     let mint_result = mint_transaction(
         &rpc_client,
-        &[],
-        wallet1,
+        &Pubkey::new_unique(),
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         rpc_client.commitment(),
     );
     assert!(mint_result.is_err());
 
+    // Fail an empty key before submitting
+    let mint_result = mint_transaction(
+        &rpc_client,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
+        "",
+        &mint_value,
+        rpc_client.commitment(),
+    );
+    assert!(mint_result.is_err());
+
     // Do mint to User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         rpc_client.commitment(),
     );
     assert!(mint_result.is_ok());
-    let (_, btree) = unpack_account_data(&rpc_client, account1, rpc_client.commitment()).unwrap();
+    let btree = unpack_account_data(&rpc_client, &account1, rpc_client.commitment())
+        .unwrap()
+        .btree_storage;
     assert!(btree.contains_key(&mint_key));
 
     // Attempt to mint something already minted for User1
     let mint_result = mint_transaction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &wallet1.pubkey(),
+        &[wallet1],
         &mint_key,
         &mint_value,
         rpc_client.commitment(),
@@ -187,12 +202,10 @@ fn test_mint_transfer_burn_fail() {
     // Attempt to transfer something that does exist
     let transfer_result = transfer_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account1.pubkey(), false),
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet1.pubkey(), true),
-        ],
-        wallet1,
+        &account1,
+        &account2,
+        &wallet1.pubkey(),
+        &[wallet1],
         &bad_key,
         rpc_client.commitment(),
     );
@@ -201,11 +214,9 @@ fn test_mint_transfer_burn_fail() {
     // Attempt to burn something that does not exist
     let burn_result = burn_instruction(
         &rpc_client,
-        &[
-            AccountMeta::new(account2.pubkey(), false),
-            AccountMeta::new(wallet2.pubkey(), true),
-        ],
-        wallet2,
+        &account2,
+        &wallet2.pubkey(),
+        &[wallet2],
         &mint_key,
         rpc_client.commitment(),
     );